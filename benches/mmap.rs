@@ -0,0 +1,41 @@
+//! Hashing a file by reading it (`hash_file`) against through memory maps (`hash_file_mmap`),
+//! run with `cargo bench --features memmap2`
+
+use std::time::{Duration, Instant};
+
+use write_hasher::{crc32c::Crc32c, hash_file, hash_file_mmap, MinDigest};
+
+const LEN: usize = 256 << 20;
+const RUNS: u32 = 5;
+
+fn run<D: MinDigest + Default>(
+    hash: impl Fn(&std::path::Path) -> std::io::Result<D::Output>,
+    path: &std::path::Path,
+) -> Duration {
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        std::hint::black_box(hash(path).unwrap());
+    }
+    start.elapsed() / RUNS
+}
+
+fn bench<D: MinDigest + Default>(name: &str, path: &std::path::Path) {
+    let read = run::<D>(|path| hash_file::<D>(path), path);
+    let mapped = run::<D>(|path| hash_file_mmap::<D>(path), path);
+    let throughput = |elapsed: Duration| LEN as f64 / elapsed.as_secs_f64() / (1 << 20) as f64;
+    println!(
+        "{:>7}: read {:>8.1} MiB/s, mmap {:>8.1} MiB/s",
+        name,
+        throughput(read),
+        throughput(mapped),
+    );
+}
+
+fn main() {
+    let path = std::env::temp_dir().join(format!("write-hasher-bench-{}", std::process::id()));
+    let contents = (0..LEN).map(|i| (i * 31 % 251) as u8).collect::<Vec<u8>>();
+    std::fs::write(&path, contents).unwrap();
+    bench::<sha2::Sha256>("sha256", &path);
+    bench::<Crc32c>("crc32c", &path);
+    std::fs::remove_file(&path).unwrap();
+}
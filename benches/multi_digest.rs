@@ -0,0 +1,37 @@
+//! Sequential (`MultiDigest`) against parallel (`ParallelMultiHasher`) hashing with several
+//! algorithms, run with `cargo bench --features rayon`
+
+use std::time::{Duration, Instant};
+
+use write_hasher::{crc32c::Crc32c, MinDigest, MultiDigest, ParallelMultiHasher};
+
+type Hashers = (sha2::Sha256, sha2::Sha512, Crc32c);
+
+const TOTAL: usize = 256 << 20;
+
+fn run<D: MinDigest>(mut hasher: D, chunk: &[u8]) -> Duration {
+    let start = Instant::now();
+    for _ in 0..TOTAL / chunk.len() {
+        hasher.update(chunk);
+    }
+    std::hint::black_box(hasher.finalize());
+    start.elapsed()
+}
+
+fn main() {
+    for chunk_len in [4 << 10, 64 << 10, 1 << 20] {
+        let chunk = (0..chunk_len)
+            .map(|i| (i * 31 % 251) as u8)
+            .collect::<Vec<u8>>();
+        let sequential = run(MultiDigest::<Hashers>::default(), &chunk);
+        let parallel = run(ParallelMultiHasher::<Hashers>::default(), &chunk);
+        let throughput =
+            |elapsed: Duration| TOTAL as f64 / elapsed.as_secs_f64() / (1 << 20) as f64;
+        println!(
+            "{:>7} byte updates: sequential {:>8.1} MiB/s, parallel {:>8.1} MiB/s",
+            chunk_len,
+            throughput(sequential),
+            throughput(parallel),
+        );
+    }
+}
@@ -78,6 +78,33 @@
             # BINDGEN_EXTRA_CLANG_ARGS = "-I${pkgs.llvmPackages.libclang.lib}/lib/clang/18/include";
           });
         cargoArtifacts = craneLib.buildPackage commonArgs;
+
+        # Feature sets which don't include the defaults, each has to pass clippy, the tests and
+        # the doctests (which nextest skips) on its own
+        featureSets = {
+          no-default = "--no-default-features";
+          crc32c = "--no-default-features --features crc32c";
+          sha2 = "--no-default-features --features sha2";
+          mac = "--no-default-features --features stdio,concrete_impls,mac";
+        };
+        featureChecks = lib.concatMapAttrs (name: features: {
+          "write-hasher-nextest-${name}" = craneLib.cargoNextest (commonArgs
+            // {
+              inherit cargoArtifacts;
+              cargoNextestExtraArgs = features;
+            });
+          "write-hasher-clippy-${name}" = craneLib.cargoClippy (commonArgs
+            // {
+              inherit cargoArtifacts;
+              cargoClippyExtraArgs = "${features} --all-targets -- --deny warnings";
+            });
+          "write-hasher-doctest-${name}" = craneLib.cargoTest (commonArgs
+            // {
+              inherit cargoArtifacts;
+              cargoTestExtraArgs = "${features} --doc";
+            });
+        })
+        featureSets;
       in {
         checks =
           {
@@ -107,6 +134,7 @@
                 partitionType = "count";
               });
           }
+          // featureChecks
           // lib.optionalAttrs (!pkgs.stdenv.isDarwin) {
             write-hasher-llvm-cov = craneLibLLvmTools.cargoLlvmCov (commonArgs // {inherit cargoArtifacts;});
           };
@@ -0,0 +1,321 @@
+//! Shorthands for [`WriteHasher`] and [`ReadHasher`] over each of the built-in algorithms, e.g.
+//! [`Crc24Writer<T>`] for `WriteHasher<Crc24, T>`, so `Crc24Writer::new(file)` works without
+//! naming the hasher.
+//!
+//! Each alias is only available when the feature of its algorithm is enabled. Hashers that need
+//! a key or parameters are constructed with `new_with_hasher` instead of `new`.
+
+use crate::{ReadHasher, WriteHasher};
+
+macro_rules! aliases {
+    ($(#[$attr:meta])* $writer:ident, $reader:ident, $hasher:ty) => {
+        aliases!(@alias $(#[$attr])* $writer, $reader, $hasher, "::new(", ")");
+    };
+    ($(#[$attr:meta])* $writer:ident, $reader:ident, $hasher:ty, $make:literal) => {
+        aliases!(
+            @alias $(#[$attr])* $writer, $reader, $hasher,
+            "::new_with_hasher(", concat!(", ", $make, ")")
+        );
+    };
+    (@alias $(#[$attr:meta])* $writer:ident, $reader:ident, $hasher:ty, $open:expr, $close:expr) => {
+        $(#[$attr])*
+        #[doc = concat!("[`WriteHasher`] over [`", stringify!($hasher), "`]")]
+        ///
+        /// ```rust
+        #[doc = concat!("use write_hasher::{MinDigest, ", stringify!($writer), "};")]
+        #[doc = concat!("let mut writer = ", stringify!($writer), $open, "Vec::<u8>::new()", $close, ";")]
+        /// writer.update(b"hello world");
+        /// let _digest = writer.finalize();
+        /// ```
+        pub type $writer<T> = WriteHasher<$hasher, T>;
+
+        $(#[$attr])*
+        #[doc = concat!("[`ReadHasher`] over [`", stringify!($hasher), "`]")]
+        ///
+        /// ```rust
+        #[doc = concat!("use write_hasher::{MinDigest, ", stringify!($reader), "};")]
+        #[doc = concat!("let mut reader = ", stringify!($reader), $open, "&b\"hello world\"[..]", $close, ";")]
+        /// reader.update(b"hello world");
+        /// let _digest = reader.finalize();
+        /// ```
+        pub type $reader<T> = ReadHasher<$hasher, T>;
+    };
+}
+
+aliases!(
+    #[cfg(feature = "crc32c")]
+    Crc32cWriter,
+    Crc32cReader,
+    crate::crc32c::Crc32c
+);
+aliases!(Crc24Writer, Crc24Reader, crate::crc24::Crc24);
+aliases!(
+    #[cfg(feature = "adler")]
+    Adler32Writer,
+    Adler32Reader,
+    crate::adler32::Adler32
+);
+aliases!(
+    #[cfg(feature = "crc64")]
+    Crc64XzWriter,
+    Crc64XzReader,
+    crate::crc64::Crc64Xz
+);
+aliases!(
+    #[cfg(feature = "crc64")]
+    Crc64NvmeWriter,
+    Crc64NvmeReader,
+    crate::crc64::Crc64Nvme
+);
+
+aliases!(
+    #[cfg(feature = "sha2")]
+    Sha224Writer,
+    Sha224Reader,
+    sha2::Sha224
+);
+aliases!(
+    #[cfg(feature = "sha2")]
+    Sha256Writer,
+    Sha256Reader,
+    sha2::Sha256
+);
+aliases!(
+    #[cfg(feature = "sha2")]
+    Sha384Writer,
+    Sha384Reader,
+    sha2::Sha384
+);
+aliases!(
+    #[cfg(feature = "sha2")]
+    Sha512Writer,
+    Sha512Reader,
+    sha2::Sha512
+);
+aliases!(
+    #[cfg(feature = "sha2")]
+    Sha512_224Writer,
+    Sha512_224Reader,
+    sha2::Sha512_224
+);
+aliases!(
+    #[cfg(feature = "sha2")]
+    Sha512_256Writer,
+    Sha512_256Reader,
+    sha2::Sha512_256
+);
+aliases!(
+    #[cfg(feature = "sha1")]
+    Sha1Writer,
+    Sha1Reader,
+    sha1::Sha1
+);
+aliases!(
+    #[cfg(feature = "md2")]
+    Md2Writer,
+    Md2Reader,
+    md2::Md2
+);
+aliases!(
+    #[cfg(feature = "md4")]
+    Md4Writer,
+    Md4Reader,
+    md4::Md4
+);
+aliases!(
+    #[cfg(feature = "md5")]
+    /// This is the `md5` crate's `Context`, its output is an `md5::Digest`. [`Md5RustCryptoWriter`]
+    /// (with the `md-5` feature) computes the same digest as a `GenericArray`, like the other
+    /// RustCrypto hashers.
+    ///
+    Md5Writer,
+    Md5Reader,
+    md5::Context,
+    "write_hasher::md5::Context::new()"
+);
+aliases!(
+    #[cfg(feature = "md-5")]
+    /// This is RustCrypto's `md_5::Md5` (the `md-5` crate), its output is a `GenericArray`.
+    /// [`Md5Writer`] (with the `md5` feature) uses the `md5` crate's `Context` instead.
+    ///
+    Md5RustCryptoWriter,
+    Md5RustCryptoReader,
+    md_5::Md5
+);
+aliases!(
+    #[cfg(feature = "blake2")]
+    Blake2b512Writer,
+    Blake2b512Reader,
+    blake2::Blake2b512
+);
+aliases!(
+    #[cfg(feature = "blake2")]
+    Blake2s256Writer,
+    Blake2s256Reader,
+    blake2::Blake2s256
+);
+aliases!(
+    #[cfg(feature = "crc32fast")]
+    Crc32Writer,
+    Crc32Reader,
+    crc32fast::Hasher
+);
+aliases!(
+    #[cfg(feature = "skein")]
+    Skein256Writer,
+    Skein256Reader,
+    skein::Skein256
+);
+aliases!(
+    #[cfg(feature = "skein")]
+    Skein512Writer,
+    Skein512Reader,
+    skein::Skein512
+);
+aliases!(
+    #[cfg(feature = "skein")]
+    Skein1024Writer,
+    Skein1024Reader,
+    skein::Skein1024
+);
+aliases!(
+    #[cfg(feature = "ripemd")]
+    Ripemd160Writer,
+    Ripemd160Reader,
+    ripemd::Ripemd160
+);
+aliases!(
+    #[cfg(feature = "blake3")]
+    Blake3Writer,
+    Blake3Reader,
+    blake3::Hasher
+);
+aliases!(
+    #[cfg(feature = "ascon")]
+    AsconHash256Writer,
+    AsconHash256Reader,
+    ascon_hash::AsconHash256
+);
+aliases!(
+    #[cfg(feature = "sha1cd")]
+    Sha1CdWriter,
+    Sha1CdReader,
+    crate::sha1cd::Sha1Cd
+);
+aliases!(
+    #[cfg(feature = "xxh3")]
+    Xxh3_64Writer,
+    Xxh3_64Reader,
+    crate::xxh3::Xxh3_64
+);
+aliases!(
+    #[cfg(feature = "xxh3")]
+    Xxh3_128Writer,
+    Xxh3_128Reader,
+    crate::xxh3::Xxh3_128
+);
+aliases!(
+    #[cfg(feature = "xxh64")]
+    Xxh64Writer,
+    Xxh64Reader,
+    crate::xxh64::Xxh64
+);
+aliases!(
+    #[cfg(feature = "sha3")]
+    Sha3_224Writer,
+    Sha3_224Reader,
+    sha3::Sha3_224
+);
+aliases!(
+    #[cfg(feature = "sha3")]
+    Sha3_256Writer,
+    Sha3_256Reader,
+    sha3::Sha3_256
+);
+aliases!(
+    #[cfg(feature = "sha3")]
+    Sha3_384Writer,
+    Sha3_384Reader,
+    sha3::Sha3_384
+);
+aliases!(
+    #[cfg(feature = "sha3")]
+    Sha3_512Writer,
+    Sha3_512Reader,
+    sha3::Sha3_512
+);
+aliases!(
+    #[cfg(feature = "sha3")]
+    Keccak256Writer,
+    Keccak256Reader,
+    sha3::Keccak256
+);
+aliases!(
+    #[cfg(feature = "sha3")]
+    Keccak512Writer,
+    Keccak512Reader,
+    sha3::Keccak512
+);
+aliases!(
+    #[cfg(feature = "sha3")]
+    Shake128Writer,
+    Shake128Reader,
+    crate::xof::Shake128,
+    "write_hasher::xof::Shake128::new(32)"
+);
+aliases!(
+    #[cfg(feature = "sha3")]
+    Shake256Writer,
+    Shake256Reader,
+    crate::xof::Shake256,
+    "write_hasher::xof::Shake256::new(64)"
+);
+aliases!(
+    #[cfg(feature = "sha3")]
+    CShake128Writer,
+    CShake128Reader,
+    crate::sp800_185::CShake128,
+    "write_hasher::sp800_185::CShake128::new(b\"app\", 32)"
+);
+aliases!(
+    #[cfg(feature = "sha3")]
+    CShake256Writer,
+    CShake256Reader,
+    crate::sp800_185::CShake256,
+    "write_hasher::sp800_185::CShake256::new(b\"app\", 64)"
+);
+aliases!(
+    #[cfg(feature = "sha3")]
+    TupleHash128Writer,
+    TupleHash128Reader,
+    crate::sp800_185::TupleHash128,
+    "write_hasher::sp800_185::TupleHash128::new(b\"app\", 32)"
+);
+aliases!(
+    #[cfg(feature = "sha3")]
+    TupleHash256Writer,
+    TupleHash256Reader,
+    crate::sp800_185::TupleHash256,
+    "write_hasher::sp800_185::TupleHash256::new(b\"app\", 64)"
+);
+aliases!(
+    #[cfg(feature = "sha3")]
+    ParallelHash128Writer,
+    ParallelHash128Reader,
+    crate::sp800_185::ParallelHash128,
+    "write_hasher::sp800_185::ParallelHash128::new(8192, b\"app\", 32)"
+);
+aliases!(
+    #[cfg(feature = "sha3")]
+    ParallelHash256Writer,
+    ParallelHash256Reader,
+    crate::sp800_185::ParallelHash256,
+    "write_hasher::sp800_185::ParallelHash256::new(8192, b\"app\", 64)"
+);
+aliases!(
+    #[cfg(feature = "poly1305")]
+    Poly1305Writer,
+    Poly1305Reader,
+    crate::poly1305::Poly1305,
+    "write_hasher::poly1305::Poly1305::new(&[7; 32])"
+);
@@ -0,0 +1,148 @@
+//! [`AsyncMinDigest`] for hashing backends that are themselves async (kernel crypto offload, a
+//! remote service, an HSM) and [`AsyncBackendWriteHasher`] feeding one from an async writer
+
+use core::{future::Future, pin::Pin, task::Poll};
+
+use bytes::Bytes;
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// The async counterpart of [`MinDigest`](crate::MinDigest)
+///
+/// Implementations can use `async fn` as long as the futures are `Send`.
+pub trait AsyncMinDigest {
+    type Output;
+    fn update(&mut self, data: Bytes) -> impl Future<Output = ()> + Send;
+    fn finalize(self) -> impl Future<Output = Self::Output> + Send;
+}
+
+/// An object safe version of [`AsyncMinDigest`], implemented for every `AsyncMinDigest` so
+/// backends can be picked at runtime as `Box<dyn DynAsyncMinDigest<Output = _>>`, which
+/// implements `AsyncMinDigest` in turn
+pub trait DynAsyncMinDigest: Send {
+    type Output;
+    fn update_boxed(&mut self, data: Bytes) -> BoxFuture<'_, ()>;
+    fn finalize_boxed(self: Box<Self>) -> BoxFuture<'static, Self::Output>;
+}
+
+impl<D> DynAsyncMinDigest for D
+where
+    D: AsyncMinDigest + Send + 'static,
+{
+    type Output = D::Output;
+    fn update_boxed(&mut self, data: Bytes) -> BoxFuture<'_, ()> {
+        Box::pin(self.update(data))
+    }
+    fn finalize_boxed(self: Box<Self>) -> BoxFuture<'static, Self::Output> {
+        Box::pin((*self).finalize())
+    }
+}
+
+impl<O: 'static> AsyncMinDigest for Box<dyn DynAsyncMinDigest<Output = O>> {
+    type Output = O;
+    fn update(&mut self, data: Bytes) -> impl Future<Output = ()> + Send {
+        // through the trait object, `self` would pick up the blanket impl and recurse
+        (**self).update_boxed(data)
+    }
+    fn finalize(self) -> impl Future<Output = O> + Send {
+        self.finalize_boxed()
+    }
+}
+
+/// A tokio [`AsyncWrite`](tokio::io::AsyncWrite) wrapper that feeds everything written to the
+/// inner writer to an [`AsyncMinDigest`] backend.
+///
+/// Each write is forwarded to the inner writer first, the accepted bytes are then handed to the
+/// backend and its update is driven to completion before the next write, flush or shutdown goes
+/// through, so at most one update is in flight and they happen in order.
+pub struct AsyncBackendWriteHasher<D, T> {
+    hasher: Option<D>,
+    in_flight: Option<BoxFuture<'static, D>>,
+    inner: T,
+}
+
+impl<D, T> AsyncBackendWriteHasher<D, T>
+where
+    D: AsyncMinDigest + Send + 'static,
+{
+    pub fn new_with_hasher(inner: T, hasher: D) -> Self {
+        Self {
+            hasher: Some(hasher),
+            in_flight: None,
+            inner,
+        }
+    }
+
+    pub fn new(inner: T) -> Self
+    where
+        D: Default,
+    {
+        Self::new_with_hasher(inner, Default::default())
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Wait for the update in flight and finalize the backend
+    pub async fn finalize(mut self) -> D::Output {
+        core::future::poll_fn(|cx| self.poll_in_flight(cx)).await;
+        let hasher = self.hasher.take().expect("no update is in flight");
+        hasher.finalize().await
+    }
+
+    fn poll_in_flight(&mut self, cx: &mut std::task::Context<'_>) -> Poll<()> {
+        if let Some(in_flight) = &mut self.in_flight {
+            let hasher = core::task::ready!(in_flight.as_mut().poll(cx));
+            self.hasher = Some(hasher);
+            self.in_flight = None;
+        }
+        Poll::Ready(())
+    }
+}
+
+// the hasher is only ever moved into the boxed update future, never pinned in place
+impl<D, T: std::marker::Unpin> std::marker::Unpin for AsyncBackendWriteHasher<D, T> {}
+
+impl<D, T> tokio::io::AsyncWrite for AsyncBackendWriteHasher<D, T>
+where
+    D: AsyncMinDigest + Send + 'static,
+    T: tokio::io::AsyncWrite + std::marker::Unpin,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = self.get_mut();
+        core::task::ready!(this.poll_in_flight(cx));
+        let n = core::task::ready!(Pin::new(&mut this.inner).poll_write(cx, buf))?;
+        if n > 0 {
+            let mut hasher = this.hasher.take().expect("no update is in flight");
+            let data = Bytes::copy_from_slice(&buf[..n]);
+            this.in_flight = Some(Box::pin(async move {
+                hasher.update(data).await;
+                hasher
+            }));
+            // start the update now, if it finishes right away the slot is already free
+            let _ = this.poll_in_flight(cx);
+        }
+        Poll::Ready(Ok(n))
+    }
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        core::task::ready!(this.poll_in_flight(cx));
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+    fn poll_shutdown(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        core::task::ready!(this.poll_in_flight(cx));
+        Pin::new(&mut this.inner).poll_shutdown(cx)
+    }
+}
@@ -0,0 +1,225 @@
+//! [`BackgroundWriteHasher`], hashing on a dedicated thread so writing and hashing overlap
+
+#[cfg(feature = "tokio")]
+use core::{pin::Pin, task::Poll};
+#[cfg(feature = "tokio")]
+use std::sync::mpsc::TrySendError;
+use std::sync::mpsc::{sync_channel, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::task::Waker;
+use std::thread::JoinHandle;
+
+use crate::MinDigest;
+
+/// A [`WriteHasher`](crate::WriteHasher) that hashes on a thread of its own.
+///
+/// Whatever the inner writer accepts is copied and sent to the thread over a bounded channel, so
+/// at most `capacity` chunks (plus one for the async writer) are waiting to be hashed and a
+/// writer outpacing the hashing waits for it. Errors of the inner writer are returned from the
+/// write as usual, only the bytes it accepted are hashed.
+///
+/// [`finalize`](Self::finalize) waits for the queued chunks to be hashed, which blocks the
+/// calling thread for at most that long.
+///
+/// ```rust
+/// # #[cfg(all(feature = "stdio", any(feature = "sha2", feature = "digest")))] {
+/// extern crate sha2;
+/// use std::io::Write;
+/// use write_hasher::BackgroundWriteHasher;
+/// let mut hasher = BackgroundWriteHasher::<sha2::Sha256, _>::new(Vec::new());
+/// hasher.write_all(b"hashed elsewhere").unwrap();
+/// let (digest, written) = hasher.finalize_into_parts();
+/// assert_eq!(written, b"hashed elsewhere");
+/// assert_eq!(digest, write_hasher::hash_bytes::<sha2::Sha256>(b"hashed elsewhere"));
+/// # }
+/// ```
+#[cfg_attr(feature = "tokio", pin_project::pin_project)]
+pub struct BackgroundWriteHasher<D: MinDigest, T> {
+    #[cfg_attr(feature = "tokio", pin)]
+    inner: T,
+    sender: SyncSender<Vec<u8>>,
+    worker: JoinHandle<D::Output>,
+    /// The task waiting for room in the channel
+    #[cfg(feature = "tokio")]
+    waker: Arc<Mutex<Option<Waker>>>,
+    /// A chunk the inner writer accepted that didn't fit in the channel yet
+    pending: Option<Vec<u8>>,
+}
+
+impl<D, T> BackgroundWriteHasher<D, T>
+where
+    D: MinDigest + Send + 'static,
+    D::Output: Send + 'static,
+{
+    /// Hash on a new thread with room for 16 chunks in the channel
+    pub fn new_with_hasher(inner: T, hasher: D) -> Self {
+        Self::with_capacity(inner, hasher, 16)
+    }
+
+    pub fn new(inner: T) -> Self
+    where
+        D: Default,
+    {
+        Self::new_with_hasher(inner, D::default())
+    }
+
+    /// Hash on a new thread with room for `capacity` chunks in the channel
+    pub fn with_capacity(inner: T, mut hasher: D, capacity: usize) -> Self {
+        let (sender, receiver) = sync_channel::<Vec<u8>>(capacity);
+        let waker = Arc::new(Mutex::new(None::<Waker>));
+        let worker_waker = Arc::clone(&waker);
+        let worker = std::thread::spawn(move || {
+            for chunk in receiver {
+                hasher.update(&chunk);
+                let waker = worker_waker
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .take();
+                if let Some(waker) = waker {
+                    waker.wake();
+                }
+            }
+            hasher.finalize()
+        });
+        Self {
+            inner,
+            sender,
+            worker,
+            #[cfg(feature = "tokio")]
+            waker,
+            pending: None,
+        }
+    }
+}
+
+impl<D: MinDigest, T> BackgroundWriteHasher<D, T> {
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Writing to the inner writer directly bypasses the hasher.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Wait for everything written so far to be hashed and finalize the hash
+    ///
+    /// # Panics
+    /// Re-raises a panic of the hasher on the hashing thread
+    pub fn finalize(self) -> D::Output {
+        self.finalize_into_parts().0
+    }
+
+    /// [`finalize`](Self::finalize), handing back the inner writer as well
+    pub fn finalize_into_parts(self) -> (D::Output, T) {
+        let Self {
+            inner,
+            sender,
+            worker,
+            pending,
+            ..
+        } = self;
+        if let Some(chunk) = pending {
+            // a disconnected channel means the worker panicked, which join reports
+            let _ = sender.send(chunk);
+        }
+        drop(sender);
+        match worker.join() {
+            Ok(output) => (output, inner),
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    }
+}
+
+fn worker_gone() -> std::io::Error {
+    std::io::Error::other("the hashing thread panicked")
+}
+
+#[cfg(feature = "stdio")]
+impl<D: MinDigest, T: std::io::Write> std::io::Write for BackgroundWriteHasher<D, T> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if let Some(chunk) = self.pending.take() {
+            self.sender.send(chunk).map_err(|_| worker_gone())?;
+        }
+        let n = self.inner.write(buf)?;
+        if n > 0 {
+            // blocks while the channel is full
+            self.sender
+                .send(buf[..n].to_vec())
+                .map_err(|_| worker_gone())?;
+        }
+        Ok(n)
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Send the chunk left over from an earlier write, waiting for room in the channel
+#[cfg(feature = "tokio")]
+fn poll_send_pending(
+    sender: &SyncSender<Vec<u8>>,
+    pending: &mut Option<Vec<u8>>,
+    waker: &Mutex<Option<Waker>>,
+    cx: &mut std::task::Context<'_>,
+) -> Poll<std::io::Result<()>> {
+    let Some(chunk) = pending.take() else {
+        return Poll::Ready(Ok(()));
+    };
+    let chunk = match sender.try_send(chunk) {
+        Ok(()) => return Poll::Ready(Ok(())),
+        Err(TrySendError::Full(chunk)) => chunk,
+        Err(TrySendError::Disconnected(_)) => return Poll::Ready(Err(worker_gone())),
+    };
+    *waker.lock().unwrap_or_else(|e| e.into_inner()) = Some(cx.waker().clone());
+    // the worker may have made room before the waker was stored
+    match sender.try_send(chunk) {
+        Ok(()) => Poll::Ready(Ok(())),
+        Err(TrySendError::Full(chunk)) => {
+            *pending = Some(chunk);
+            Poll::Pending
+        }
+        Err(TrySendError::Disconnected(_)) => Poll::Ready(Err(worker_gone())),
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+impl<D: MinDigest, T: tokio::io::AsyncWrite> tokio::io::AsyncWrite for BackgroundWriteHasher<D, T> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        let ah = self.project();
+        core::task::ready!(poll_send_pending(ah.sender, ah.pending, ah.waker, cx))?;
+        let r = ah.inner.poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = r {
+            if n > 0 {
+                if let Err(TrySendError::Full(chunk) | TrySendError::Disconnected(chunk)) =
+                    ah.sender.try_send(buf[..n].to_vec())
+                {
+                    // sent (or reported) by the next call
+                    *ah.pending = Some(chunk);
+                }
+            }
+        }
+        r
+    }
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        let ah = self.project();
+        core::task::ready!(poll_send_pending(ah.sender, ah.pending, ah.waker, cx))?;
+        ah.inner.poll_flush(cx)
+    }
+    fn poll_shutdown(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        let ah = self.project();
+        core::task::ready!(poll_send_pending(ah.sender, ah.pending, ah.waker, cx))?;
+        ah.inner.poll_shutdown(cx)
+    }
+}
@@ -0,0 +1,358 @@
+//! Checksum files in the format of coreutils' `sha256sum` / `b2sum` / ..., one
+//! `<hex digest>  <name>` line per file, which `sha256sum -c` can verify
+//!
+//! Names containing a newline, carriage return or backslash are escaped the way coreutils does
+//! it: the line starts with a `\` and those characters are written as `\n`, `\r` and `\\`.
+//!
+//! [`Writer`] emits checksum files, [`parse`] and [`verify`] (or [`verify_async`] with the
+//! `tokio` feature) check files against them.
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::encoding::bytes_eq;
+use crate::helpers::{decode_hex, encode_hex};
+use crate::{MinDigest, OutputBytes, WriteHasher};
+
+/// Writes the lines of a checksum file
+///
+/// ```rust
+/// # #[cfg(feature = "crc32c")] {
+/// use write_hasher::{checksum_file, crc32c::Crc32c, MinDigest};
+/// let mut sums = checksum_file::Writer::new(Vec::new());
+/// let crc = sums
+///     .hash_entry("digits.txt", Crc32c::new(), |writer| {
+///         writer.update(b"123456789");
+///         Ok(())
+///     })
+///     .unwrap();
+/// assert_eq!(crc, 0xe3069283);
+/// sums.write_entry("new\nline", &[0xab, 0xcd]).unwrap();
+/// assert_eq!(sums.into_inner(), b"e3069283  digits.txt\n\\abcd  new\\nline\n");
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Writer<W> {
+    inner: W,
+    binary: bool,
+}
+
+impl<W: Write> Writer<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            binary: false,
+        }
+    }
+
+    /// Mark the entries as hashed in binary mode, `<hex> *<name>` instead of `<hex>  <name>`.
+    /// There's no difference between the modes on the systems coreutils runs on.
+    pub fn with_binary_marker(mut self, binary: bool) -> Self {
+        self.binary = binary;
+        self
+    }
+
+    /// Write the line of `name` with its digest bytes, e.g. `hasher.finalize_bytes()`. The
+    /// integer checksums are big-endian in their [`OutputBytes`], so a crc32 is 8 hex digits.
+    pub fn write_entry(&mut self, name: impl AsRef<Path>, digest: &[u8]) -> io::Result<()> {
+        let name = name.as_ref().as_os_str().as_encoded_bytes();
+        let escape = name.iter().any(|b| matches!(b, b'\n' | b'\r' | b'\\'));
+        let mut line = Vec::with_capacity(digest.len() * 2 + name.len() + 4);
+        if escape {
+            line.push(b'\\');
+        }
+        line.extend_from_slice(encode_hex(digest).as_bytes());
+        line.extend_from_slice(if self.binary { b" *" } else { b"  " });
+        for &byte in name {
+            match byte {
+                b'\n' if escape => line.extend_from_slice(b"\\n"),
+                b'\r' if escape => line.extend_from_slice(b"\\r"),
+                b'\\' if escape => line.extend_from_slice(b"\\\\"),
+                byte => line.push(byte),
+            }
+        }
+        line.push(b'\n');
+        self.inner.write_all(&line)
+    }
+
+    /// Hash the data `write` writes into the [`WriteHasher`] and write the line of `name` with
+    /// its digest, which is returned as well
+    pub fn hash_entry<D, F>(
+        &mut self,
+        name: impl AsRef<Path>,
+        hasher: D,
+        write: F,
+    ) -> io::Result<D::Output>
+    where
+        D: MinDigest,
+        D::Output: OutputBytes,
+        F: FnOnce(&mut WriteHasher<D, io::Sink>) -> io::Result<()>,
+    {
+        let mut writer = WriteHasher::new_with_hasher(io::sink(), hasher);
+        write(&mut writer)?;
+        let digest = writer.finalize();
+        self.write_entry(name, &digest.to_bytes())?;
+        Ok(digest)
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// A line of a checksum file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// The line number, starting at 1
+    pub line: usize,
+    pub name: PathBuf,
+    pub expected: Vec<u8>,
+    /// Whether the name was marked with `*` (binary mode)
+    pub binary: bool,
+}
+
+/// A line that isn't a valid checksum line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Malformed {
+    /// The line number, starting at 1
+    pub line: usize,
+    pub reason: &'static str,
+}
+
+impl core::fmt::Display for Malformed {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+impl std::error::Error for Malformed {}
+
+/// Parse the lines of a checksum file, empty lines are skipped. A malformed line is reported in
+/// place without affecting the others.
+///
+/// ```rust
+/// use write_hasher::checksum_file;
+/// let entries = checksum_file::parse(b"e3069283  digits.txt\n\\abcd *new\\nline\nbogus\n");
+/// let first = entries[0].as_ref().unwrap();
+/// assert_eq!(first.name, std::path::Path::new("digits.txt"));
+/// assert_eq!(first.expected, [0xe3, 0x06, 0x92, 0x83]);
+/// assert_eq!(entries[1].as_ref().unwrap().name, std::path::Path::new("new\nline"));
+/// assert_eq!(entries[2].as_ref().unwrap_err().line, 3);
+/// ```
+pub fn parse(contents: &[u8]) -> Vec<Result<Entry, Malformed>> {
+    contents
+        .split(|&b| b == b'\n')
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(i, line)| {
+            parse_line(line)
+                .map_err(|reason| Malformed {
+                    line: i + 1,
+                    reason,
+                })
+                .map(|(name, expected, binary)| Entry {
+                    line: i + 1,
+                    name,
+                    expected,
+                    binary,
+                })
+        })
+        .collect()
+}
+
+fn parse_line(line: &[u8]) -> Result<(PathBuf, Vec<u8>, bool), &'static str> {
+    let (escaped, line) = match line.strip_prefix(b"\\") {
+        Some(line) => (true, line),
+        None => (false, line),
+    };
+    let separator = line
+        .iter()
+        .position(|&b| b == b' ')
+        .ok_or("no space after the digest")?;
+    let hex = std::str::from_utf8(&line[..separator]).map_err(|_| "the digest isn't hex")?;
+    let expected = decode_hex(hex)
+        .filter(|digest| !digest.is_empty())
+        .ok_or("the digest isn't hex")?;
+    let (binary, name) = match &line[separator + 1..] {
+        [b' ', name @ ..] => (false, name),
+        [b'*', name @ ..] => (true, name),
+        _ => return Err("expected two spaces or a space and `*` after the digest"),
+    };
+    if name.is_empty() {
+        return Err("no file name");
+    }
+    let name = if escaped {
+        unescape(name).ok_or("invalid escape in the file name")?
+    } else {
+        name.to_vec()
+    };
+    Ok((path_from_bytes(name)?, expected, binary))
+}
+
+fn unescape(name: &[u8]) -> Option<Vec<u8>> {
+    let mut unescaped = Vec::with_capacity(name.len());
+    let mut bytes = name.iter();
+    while let Some(&byte) = bytes.next() {
+        if byte != b'\\' {
+            unescaped.push(byte);
+            continue;
+        }
+        unescaped.push(match bytes.next()? {
+            b'n' => b'\n',
+            b'r' => b'\r',
+            b'\\' => b'\\',
+            _ => return None,
+        });
+    }
+    Some(unescaped)
+}
+
+#[cfg(unix)]
+fn path_from_bytes(name: Vec<u8>) -> Result<PathBuf, &'static str> {
+    use std::os::unix::ffi::OsStringExt;
+    Ok(std::ffi::OsString::from_vec(name).into())
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(name: Vec<u8>) -> Result<PathBuf, &'static str> {
+    String::from_utf8(name)
+        .map(PathBuf::from)
+        .map_err(|_| "the file name isn't UTF-8")
+}
+
+/// The outcome of checking one file
+#[derive(Debug)]
+pub enum Status {
+    Ok,
+    Mismatch {
+        expected: Vec<u8>,
+        actual: Vec<u8>,
+    },
+    /// The file doesn't exist
+    Missing,
+    /// Opening or reading the file failed otherwise
+    ReadError(io::Error),
+}
+
+/// A file checked against its line of a checksum file
+#[derive(Debug)]
+pub struct Report {
+    pub entry: Entry,
+    pub status: Status,
+}
+
+fn status<D: MinDigest>(expected: &[u8], result: io::Result<D::Output>) -> Status
+where
+    D::Output: OutputBytes,
+{
+    match result {
+        Ok(actual) if bytes_eq(&actual.to_bytes(), expected) => Status::Ok,
+        Ok(actual) => Status::Mismatch {
+            expected: expected.to_vec(),
+            actual: actual.to_bytes().into_owned(),
+        },
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Status::Missing,
+        Err(err) => Status::ReadError(err),
+    }
+}
+
+/// Check every file of a checksum file, with names relative to `base`, in the order of the
+/// lines. Each file is streamed through a [`WriteHasher`] into [`io::sink`].
+pub fn verify<D>(contents: &[u8], base: impl AsRef<Path>) -> Vec<Result<Report, Malformed>>
+where
+    D: MinDigest + Default,
+    D::Output: OutputBytes,
+{
+    let base = base.as_ref();
+    parse(contents)
+        .into_iter()
+        .map(|entry| {
+            let entry = entry?;
+            let result = std::fs::File::open(base.join(&entry.name)).and_then(|mut file| {
+                let mut writer = WriteHasher::<D, _>::new(io::sink());
+                io::copy(&mut file, &mut writer)?;
+                Ok(writer.finalize())
+            });
+            let status = status::<D>(&entry.expected, result);
+            Ok(Report { entry, status })
+        })
+        .collect()
+}
+
+/// Async version of [`verify`], hashing up to `concurrency` files at a time (at least one)
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+pub async fn verify_async<D>(
+    contents: &[u8],
+    base: impl AsRef<Path>,
+    concurrency: usize,
+) -> Vec<Result<Report, Malformed>>
+where
+    D: MinDigest + Default,
+    D::Output: OutputBytes,
+{
+    use core::future::Future;
+    use core::pin::Pin;
+    use core::task::Poll;
+
+    type Hashing<'a, O> = Pin<Box<dyn Future<Output = io::Result<O>> + 'a>>;
+
+    async fn hash<D: MinDigest + Default>(path: PathBuf) -> io::Result<D::Output> {
+        let mut file = tokio::fs::File::open(path).await?;
+        let mut writer = WriteHasher::<D, _>::new(tokio::io::sink());
+        tokio::io::copy(&mut file, &mut writer).await?;
+        Ok(writer.finalize())
+    }
+
+    let base = base.as_ref();
+    let mut pending: Vec<(usize, Hashing<'_, D::Output>)> = Vec::new();
+    let mut entries = parse(contents).into_iter().enumerate();
+    let mut slots: Vec<Option<Result<Entry, Malformed>>> = Vec::new();
+    let mut statuses: Vec<Option<Status>> = Vec::new();
+
+    core::future::poll_fn(|cx| loop {
+        while pending.len() < concurrency.max(1) {
+            let Some((i, entry)) = entries.next() else {
+                break;
+            };
+            if let Ok(entry) = &entry {
+                pending.push((i, Box::pin(hash::<D>(base.join(&entry.name)))));
+            }
+            slots.push(Some(entry));
+            statuses.push(None);
+        }
+        let before = pending.len();
+        pending.retain_mut(|(i, hashing)| match hashing.as_mut().poll(cx) {
+            Poll::Ready(result) => {
+                let expected = match &slots[*i] {
+                    Some(Ok(entry)) => entry.expected.clone(),
+                    _ => unreachable!("only entries are hashed"),
+                };
+                statuses[*i] = Some(status::<D>(&expected, result));
+                false
+            }
+            Poll::Pending => true,
+        });
+        if pending.is_empty() && entries.len() == 0 {
+            return Poll::Ready(());
+        }
+        if pending.len() == before {
+            return Poll::Pending;
+        }
+    })
+    .await;
+
+    slots
+        .into_iter()
+        .zip(statuses)
+        .map(|(slot, status)| {
+            let entry = slot.expect("every line is visited once")?;
+            let status = status.expect("every entry is hashed");
+            Ok(Report { entry, status })
+        })
+        .collect()
+}
@@ -0,0 +1,716 @@
+//! Adapters that combine several readers into one hashed stream, and hashers into other hashers
+//!
+//! The hasher adapters are wrapper types rather than [`MinDigest`] impls for `Option`, tuples,
+//! `Arc<Mutex<_>>` or `&mut _`: with the `digest` feature every `digest::Digest` implements
+//! [`MinDigest`] through a blanket impl, which any impl for a foreign generic type would overlap.
+
+#[cfg(feature = "tokio")]
+use core::{pin::Pin, task::Poll};
+
+#[cfg(any(feature = "stdio", feature = "tokio"))]
+use crate::ReadHasher;
+use crate::{MinDigest, MinDigestReset};
+
+/// A reader that reads each reader from an iterator in order until it hits EOF, so several
+/// readers can be treated as one logical stream
+#[cfg(any(feature = "stdio", feature = "tokio"))]
+pub struct ReadChain<I: Iterator> {
+    readers: I,
+    current: Option<I::Item>,
+}
+
+#[cfg(any(feature = "stdio", feature = "tokio"))]
+impl<I: Iterator> ReadChain<I> {
+    pub fn new(readers: impl IntoIterator<IntoIter = I>) -> Self {
+        let mut readers = readers.into_iter();
+        let current = readers.next();
+        Self { readers, current }
+    }
+}
+
+#[cfg(feature = "stdio")]
+impl<I: Iterator> std::io::Read for ReadChain<I>
+where
+    I::Item: std::io::Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while let Some(current) = &mut self.current {
+            match current.read(buf)? {
+                0 if !buf.is_empty() => self.current = self.readers.next(),
+                n => return Ok(n),
+            }
+        }
+        Ok(0)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+impl<I: Iterator + std::marker::Unpin> tokio::io::AsyncRead for ReadChain<I>
+where
+    I::Item: tokio::io::AsyncRead + std::marker::Unpin,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        let this = &mut *self;
+        while let Some(current) = &mut this.current {
+            let before = buf.filled().len();
+            core::task::ready!(Pin::new(current).poll_read(cx, buf))?;
+            if buf.filled().len() == before && buf.remaining() > 0 {
+                this.current = this.readers.next();
+            } else {
+                return Poll::Ready(Ok(()));
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// A [`ReadHasher`] over several readers read one after the other
+#[cfg(any(feature = "stdio", feature = "tokio"))]
+pub type ChainedReadHasher<D, I> = ReadHasher<D, ReadChain<I>>;
+
+/// Hash the concatenation of all the readers in order.
+///
+/// An error from any of the readers aborts hashing and is returned as is.
+#[cfg(feature = "stdio")]
+pub fn hash_chain<D: MinDigest + Default, R: std::io::Read>(
+    readers: impl IntoIterator<Item = R>,
+) -> std::io::Result<D::Output> {
+    ChainedReadHasher::<D, _>::new(ReadChain::new(readers)).finalize_ensure_eof()
+}
+
+/// Async version of [`hash_chain`]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+pub async fn hash_chain_async<D, R, I>(readers: I) -> std::io::Result<D::Output>
+where
+    D: MinDigest + Default,
+    R: tokio::io::AsyncRead + std::marker::Unpin,
+    I: IntoIterator<Item = R>,
+    I::IntoIter: std::marker::Unpin,
+{
+    ChainedReadHasher::<D, _>::new(ReadChain::new(readers))
+        .finalize_ensure_eof_async()
+        .await
+}
+
+/// A hasher that calls `inspect` with every chunk before hashing it, see
+/// [`WriteHasher::with_inspect`](crate::WriteHasher::with_inspect)
+pub struct Inspect<D, F> {
+    hasher: D,
+    inspect: F,
+}
+
+impl<D, F: FnMut(&[u8])> Inspect<D, F> {
+    pub fn new(hasher: D, inspect: F) -> Self {
+        Self { hasher, inspect }
+    }
+
+    pub fn into_inner(self) -> D {
+        self.hasher
+    }
+}
+
+impl<D: MinDigest, F: FnMut(&[u8])> MinDigest for Inspect<D, F> {
+    type Output = D::Output;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        let data = data.as_ref();
+        (self.inspect)(data);
+        self.hasher.update(data)
+    }
+    fn finalize(self) -> Self::Output {
+        self.hasher.finalize()
+    }
+}
+
+/// A hasher that calls `checkpoint` with the offset and the digest so far every `interval`
+/// bytes, see [`WriteHasher::with_checkpoint`](crate::WriteHasher::with_checkpoint)
+pub struct Checkpoint<D, F> {
+    hasher: D,
+    interval: u64,
+    pub(crate) hashed: u64,
+    checkpoint: F,
+}
+
+impl<D: MinDigest + Clone, F: FnMut(u64, D::Output)> Checkpoint<D, F> {
+    /// # Panics
+    /// Panics if `interval` is 0
+    pub fn new(hasher: D, interval: u64, checkpoint: F) -> Self {
+        assert!(interval > 0, "checkpoint interval must be positive");
+        Self {
+            hasher,
+            interval,
+            hashed: 0,
+            checkpoint,
+        }
+    }
+
+    pub fn into_inner(self) -> D {
+        self.hasher
+    }
+}
+
+impl<D: MinDigest + Clone, F: FnMut(u64, D::Output)> MinDigest for Checkpoint<D, F> {
+    type Output = D::Output;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        let mut data = data.as_ref();
+        while !data.is_empty() {
+            // split the chunk at the next boundary so the checkpoint covers exactly up to it
+            let until_boundary = self.interval - self.hashed % self.interval;
+            let len = until_boundary.min(data.len() as u64) as usize;
+            self.hasher.update(&data[..len]);
+            self.hashed += len as u64;
+            data = &data[len..];
+            if self.hashed.is_multiple_of(self.interval) {
+                (self.checkpoint)(self.hashed, self.hasher.clone().finalize());
+            }
+        }
+    }
+    fn finalize(self) -> Self::Output {
+        self.hasher.finalize()
+    }
+}
+
+/// A hasher whose output is transformed by `map` at finalize, see [`MinDigest::map`]
+pub struct MappedDigest<D, F, O> {
+    hasher: D,
+    map: F,
+    output: core::marker::PhantomData<fn() -> O>,
+}
+
+impl<D: MinDigest, F: FnOnce(D::Output) -> O, O> MappedDigest<D, F, O> {
+    pub fn new(hasher: D, map: F) -> Self {
+        Self {
+            hasher,
+            map,
+            output: core::marker::PhantomData,
+        }
+    }
+
+    pub fn into_inner(self) -> D {
+        self.hasher
+    }
+}
+
+impl<D: Clone, F: Clone, O> Clone for MappedDigest<D, F, O> {
+    fn clone(&self) -> Self {
+        Self {
+            hasher: self.hasher.clone(),
+            map: self.map.clone(),
+            output: core::marker::PhantomData,
+        }
+    }
+}
+
+impl<D: MinDigest, F: FnOnce(D::Output) -> O, O> MinDigest for MappedDigest<D, F, O> {
+    type Output = O;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        self.hasher.update(data)
+    }
+    fn finalize(self) -> O {
+        (self.map)(self.hasher.finalize())
+    }
+}
+
+/// A hasher lent out by [`MinDigest::borrowed`], so it can be used again once the borrow ends
+///
+/// Updates go straight to the borrowed hasher. Finalizing gives the digest of a clone of it,
+/// the hasher itself is left as it was.
+#[derive(Debug)]
+pub struct BorrowedDigest<'a, D> {
+    hasher: &'a mut D,
+}
+
+impl<'a, D> BorrowedDigest<'a, D> {
+    pub fn new(hasher: &'a mut D) -> Self {
+        Self { hasher }
+    }
+}
+
+impl<D: MinDigest + Clone> MinDigest for BorrowedDigest<'_, D> {
+    type Output = D::Output;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        self.hasher.update(data)
+    }
+    fn finalize(self) -> Self::Output {
+        self.hasher.clone().finalize()
+    }
+}
+
+/// Feeds the data to two hashers, the output is both of their outputs
+///
+/// ```rust
+/// # #[cfg(all(feature = "stdio", feature = "digest"))] {
+/// extern crate sha2;
+/// use std::io::Write;
+/// use write_hasher::{DelimitedDigest, MinDigest, TeeDigest, WriteHasher};
+/// // the digest of the whole stream and of each line
+/// let hasher = TeeDigest::new(
+///     sha2::Sha256::default(),
+///     DelimitedDigest::<sha2::Sha256>::new(b'\n', true),
+/// );
+/// let mut writer = WriteHasher::new_with_hasher(std::io::sink(), hasher);
+/// writer.write_all(b"{\"id\":1}\n{\"id\":").unwrap();
+/// writer.write_all(b"2}\n").unwrap();
+/// let (whole, records) = writer.finalize();
+/// assert_eq!(records.len(), 2);
+/// assert_eq!(records[1], write_hasher::hash_bytes::<sha2::Sha256>(b"{\"id\":2}\n"));
+/// assert_eq!(whole, write_hasher::hash_bytes::<sha2::Sha256>(b"{\"id\":1}\n{\"id\":2}\n"));
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TeeDigest<A, B> {
+    a: A,
+    b: B,
+}
+
+impl<A, B> TeeDigest<A, B> {
+    pub fn new(a: A, b: B) -> Self {
+        Self { a, b }
+    }
+}
+
+impl<A: MinDigest, B: MinDigest> MinDigest for TeeDigest<A, B> {
+    type Output = (A::Output, B::Output);
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        let data = data.as_ref();
+        self.a.update(data);
+        self.b.update(data);
+    }
+    fn finalize(self) -> Self::Output {
+        (self.a.finalize(), self.b.finalize())
+    }
+}
+
+/// Hashing that can be turned off at runtime while keeping the same hasher type, e.g. from a
+/// config flag. Without a hasher updates are skipped and the output is `None`.
+///
+/// Its `Default` hashes (unlike `Option`'s), so
+/// `WriteHasher::<OptionalDigest<D>, _>::new` behaves like `WriteHasher::<D, _>::new`, use
+/// [`OptionalDigest::new`] or [`OptionalDigest::disabled`] to turn it off.
+///
+/// ```rust
+/// # #[cfg(all(feature = "stdio", feature = "digest"))] {
+/// extern crate sha2;
+/// use std::io::Write;
+/// use write_hasher::{MinDigest, OptionalDigest, WriteHasher};
+/// let checksums = false;
+/// let hasher = OptionalDigest::<sha2::Sha256>::new(checksums);
+/// let mut writer = WriteHasher::new_with_hasher(Vec::new(), hasher);
+/// writer.write_all(b"payload").unwrap();
+/// let (digest, written) = writer.finalize_into_parts();
+/// assert_eq!(digest, None);
+/// assert_eq!(written, b"payload");
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct OptionalDigest<D>(pub Option<D>);
+
+impl<D: Default> OptionalDigest<D> {
+    /// Hash with `D::default()` if `enabled`
+    pub fn new(enabled: bool) -> Self {
+        Self(enabled.then(D::default))
+    }
+}
+
+impl<D> OptionalDigest<D> {
+    pub fn disabled() -> Self {
+        Self(None)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.0.is_some()
+    }
+}
+
+impl<D: Default> Default for OptionalDigest<D> {
+    fn default() -> Self {
+        Self(Some(D::default()))
+    }
+}
+
+impl<D> From<Option<D>> for OptionalDigest<D> {
+    fn from(hasher: Option<D>) -> Self {
+        Self(hasher)
+    }
+}
+
+impl<D: MinDigest> MinDigest for OptionalDigest<D> {
+    type Output = Option<D::Output>;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        if let Some(hasher) = &mut self.0 {
+            hasher.update(data)
+        }
+    }
+    fn finalize(self) -> Self::Output {
+        self.0.map(MinDigest::finalize)
+    }
+}
+
+impl<D: MinDigestReset> MinDigestReset for OptionalDigest<D> {
+    fn finalize_reset(&mut self) -> Self::Output {
+        self.0.as_mut().map(MinDigestReset::finalize_reset)
+    }
+}
+
+/// Feeds the data to every hasher of a tuple of up to four, the output is the tuple of their
+/// outputs.
+///
+/// ```rust
+/// # #[cfg(all(feature = "stdio", feature = "digest", feature = "crc32c"))] {
+/// extern crate sha2;
+/// use std::io::Write;
+/// use write_hasher::{crc32c::Crc32c, hash_bytes, MinDigest, MultiDigest, WriteHasher};
+/// let mut writer = WriteHasher::<MultiDigest<(sha2::Sha256, Crc32c)>, _>::new(std::io::sink());
+/// writer.write_all(b"123456789").unwrap();
+/// let (sha, crc) = writer.finalize();
+/// assert_eq!(sha, hash_bytes::<sha2::Sha256>(b"123456789"));
+/// assert_eq!(crc, 0xe3069283);
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MultiDigest<T>(pub T);
+
+impl<T> MultiDigest<T> {
+    pub fn new(hashers: T) -> Self {
+        Self(hashers)
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+macro_rules! multi_digest {
+    ($($name:ident $index:tt),+) => {
+        impl<$($name: MinDigest),+> MinDigest for MultiDigest<($($name,)+)> {
+            type Output = ($($name::Output,)+);
+            fn update(&mut self, data: impl AsRef<[u8]>) {
+                let data = data.as_ref();
+                $(self.0.$index.update(data);)+
+            }
+            fn finalize(self) -> Self::Output {
+                ($(self.0.$index.finalize(),)+)
+            }
+        }
+    };
+}
+
+multi_digest!(A 0, B 1);
+multi_digest!(A 0, B 1, C 2);
+multi_digest!(A 0, B 1, C 2, D 3);
+
+/// [`MultiDigest`] updating the hashers on separate threads with rayon, for when hashing with
+/// several expensive algorithms on one core is the bottleneck.
+///
+/// Updates shorter than [`min_parallel_len`](Self::with_min_parallel_len) (16 KiB by default)
+/// are hashed sequentially since handing them to other threads costs more than it saves.
+#[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+#[cfg(feature = "rayon")]
+#[derive(Debug, Clone)]
+pub struct ParallelMultiHasher<T> {
+    hashers: T,
+    min_parallel_len: usize,
+}
+
+#[cfg(feature = "rayon")]
+impl<T> ParallelMultiHasher<T> {
+    pub fn new(hashers: T) -> Self {
+        Self {
+            hashers,
+            min_parallel_len: 16 * 1024,
+        }
+    }
+
+    /// Hash updates of at least `len` bytes in parallel
+    pub fn with_min_parallel_len(mut self, len: usize) -> Self {
+        self.min_parallel_len = len;
+        self
+    }
+
+    pub fn into_inner(self) -> T {
+        self.hashers
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T: Default> Default for ParallelMultiHasher<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+#[cfg(feature = "rayon")]
+macro_rules! parallel_multi_hasher {
+    (($($name:ident $var:ident),+), $data:ident => $parallel:expr) => {
+        impl<$($name: MinDigest + Send),+> MinDigest for ParallelMultiHasher<($($name,)+)> {
+            type Output = ($($name::Output,)+);
+            fn update(&mut self, data: impl AsRef<[u8]>) {
+                let $data = data.as_ref();
+                let ($($var,)+) = &mut self.hashers;
+                if $data.len() < self.min_parallel_len {
+                    $($var.update($data);)+
+                } else {
+                    $parallel;
+                }
+            }
+            fn finalize(self) -> Self::Output {
+                let ($($var,)+) = self.hashers;
+                ($($var.finalize(),)+)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "rayon")]
+parallel_multi_hasher!((A a, B b), data => rayon::join(|| a.update(data), || b.update(data)));
+#[cfg(feature = "rayon")]
+parallel_multi_hasher!((A a, B b, C c), data => rayon::join(
+    || a.update(data),
+    || rayon::join(|| b.update(data), || c.update(data)),
+));
+#[cfg(feature = "rayon")]
+parallel_multi_hasher!((A a, B b, C c, D d), data => rayon::join(
+    || rayon::join(|| a.update(data), || b.update(data)),
+    || rayon::join(|| c.update(data), || d.update(data)),
+));
+
+/// Hashes each record of a stream split on a delimiter byte separately, the output is the digest
+/// of every record in order.
+///
+/// Records can span any number of updates. A final record without a trailing delimiter is still
+/// a record, while a delimiter at the very end doesn't start an empty one.
+pub struct DelimitedDigest<D: MinDigest> {
+    current: D,
+    delimiter: u8,
+    include_delimiter: bool,
+    in_record: bool,
+    index: usize,
+    records: Vec<D::Output>,
+    #[allow(clippy::type_complexity)]
+    on_record: Option<Box<dyn FnMut(usize, D::Output) + Send>>,
+}
+
+impl<D: MinDigest + Default> DelimitedDigest<D> {
+    /// `include_delimiter` decides whether the delimiter ending a record is part of its digest
+    pub fn new(delimiter: u8, include_delimiter: bool) -> Self {
+        Self {
+            current: D::default(),
+            delimiter,
+            include_delimiter,
+            in_record: false,
+            index: 0,
+            records: Vec::new(),
+            on_record: None,
+        }
+    }
+
+    /// Pass each record's index and digest to `on_record` as soon as the record ends instead of
+    /// collecting them, the output is empty then
+    pub fn with_on_record(
+        mut self,
+        on_record: impl FnMut(usize, D::Output) + Send + 'static,
+    ) -> Self {
+        self.on_record = Some(Box::new(on_record));
+        self
+    }
+
+    fn end_record(&mut self) {
+        let digest = core::mem::take(&mut self.current).finalize();
+        match &mut self.on_record {
+            Some(on_record) => on_record(self.index, digest),
+            None => self.records.push(digest),
+        }
+        self.index += 1;
+        self.in_record = false;
+    }
+}
+
+impl<D: MinDigest + Default> MinDigest for DelimitedDigest<D> {
+    type Output = Vec<D::Output>;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        let mut data = data.as_ref();
+        while let Some(end) = data.iter().position(|&b| b == self.delimiter) {
+            let record = if self.include_delimiter {
+                &data[..=end]
+            } else {
+                &data[..end]
+            };
+            self.current.update(record);
+            self.end_record();
+            data = &data[end + 1..];
+        }
+        if !data.is_empty() {
+            self.current.update(data);
+            self.in_record = true;
+        }
+    }
+    fn finalize(mut self) -> Self::Output {
+        if self.in_record {
+            self.end_record();
+        }
+        self.records
+    }
+}
+
+/// A cloneable handle to one hasher, so several writers or readers can feed a single digest
+///
+/// **Ordering is up to the caller**: every handle updates the same state as soon as its data
+/// arrives, so the digest is only over the logical concatenation if the writers run one after
+/// the other (writer A fully, then writer B). Concurrent writes interleave in whatever order
+/// the lock is taken and produce a digest of that interleaving.
+///
+/// Finalizing a handle while other handles are alive yields `None`, only the last one gets the
+/// digest. Use [`SharedDigest::try_finalize`] to get the handle back instead.
+///
+/// ```rust
+/// # #[cfg(all(feature = "stdio", feature = "crc32c"))] {
+/// use std::io::Write;
+/// use write_hasher::{crc32c::Crc32c, MinDigest, SharedDigest, WriteHasher};
+/// let shared = SharedDigest::new(Crc32c::default());
+/// let mut a = WriteHasher::new_with_hasher(std::io::sink(), shared.clone());
+/// let mut b = WriteHasher::new_with_hasher(std::io::sink(), shared);
+/// a.write_all(b"1234").unwrap();
+/// assert_eq!(a.finalize(), None);
+/// b.write_all(b"56789").unwrap();
+/// assert_eq!(b.finalize(), Some(0xe3069283));
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct SharedDigest<D> {
+    hasher: std::sync::Arc<std::sync::Mutex<D>>,
+}
+
+impl<D> Clone for SharedDigest<D> {
+    fn clone(&self) -> Self {
+        Self {
+            hasher: self.hasher.clone(),
+        }
+    }
+}
+
+impl<D> SharedDigest<D> {
+    pub fn new(hasher: D) -> Self {
+        Self {
+            hasher: std::sync::Arc::new(std::sync::Mutex::new(hasher)),
+        }
+    }
+
+    /// The number of live handles sharing this digest, including this one
+    pub fn handles(&self) -> usize {
+        std::sync::Arc::strong_count(&self.hasher)
+    }
+}
+
+impl<D: MinDigest> SharedDigest<D> {
+    /// Finalize the digest if this is the last handle, otherwise return the handle back
+    pub fn try_finalize(self) -> Result<D::Output, Self> {
+        std::sync::Arc::try_unwrap(self.hasher)
+            .map(|hasher| {
+                hasher
+                    .into_inner()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner)
+                    .finalize()
+            })
+            .map_err(|hasher| Self { hasher })
+    }
+}
+
+impl<D: MinDigest> MinDigest for SharedDigest<D> {
+    type Output = Option<D::Output>;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        self.hasher
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .update(data);
+    }
+    fn finalize(self) -> Self::Output {
+        self.try_finalize().ok()
+    }
+}
+
+/// Any handle can take the digest of everything written through all of them so far, which
+/// starts the shared hasher over
+impl<D: MinDigestReset> MinDigestReset for SharedDigest<D> {
+    fn finalize_reset(&mut self) -> Self::Output {
+        let mut hasher = self
+            .hasher
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        Some(hasher.finalize_reset())
+    }
+}
+
+/// [`SharedDigest`] for a single thread, the handles share the hasher through an `Rc<RefCell<_>>`
+/// instead of an `Arc<Mutex<_>>`
+///
+/// ```rust
+/// # #[cfg(all(feature = "stdio", feature = "digest"))] {
+/// extern crate sha2;
+/// use std::io::Write;
+/// use write_hasher::{hash_bytes, LocalSharedDigest, MinDigest, WriteHasher};
+/// let shared = LocalSharedDigest::new(sha2::Sha256::default());
+/// let mut file = WriteHasher::new_with_hasher(Vec::new(), shared.clone());
+/// let mut network = WriteHasher::new_with_hasher(Vec::new(), shared);
+/// file.write_all(b"header,").unwrap();
+/// network.write_all(b"body").unwrap();
+/// assert_eq!(file.finalize(), None);
+/// assert_eq!(network.finalize(), Some(hash_bytes::<sha2::Sha256>(b"header,body")));
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct LocalSharedDigest<D> {
+    hasher: std::rc::Rc<core::cell::RefCell<D>>,
+}
+
+impl<D> Clone for LocalSharedDigest<D> {
+    fn clone(&self) -> Self {
+        Self {
+            hasher: self.hasher.clone(),
+        }
+    }
+}
+
+impl<D> LocalSharedDigest<D> {
+    pub fn new(hasher: D) -> Self {
+        Self {
+            hasher: std::rc::Rc::new(core::cell::RefCell::new(hasher)),
+        }
+    }
+
+    /// The number of live handles sharing this digest, including this one
+    pub fn handles(&self) -> usize {
+        std::rc::Rc::strong_count(&self.hasher)
+    }
+}
+
+impl<D: MinDigest> LocalSharedDigest<D> {
+    /// Finalize the digest if this is the last handle, otherwise return the handle back
+    pub fn try_finalize(self) -> Result<D::Output, Self> {
+        std::rc::Rc::try_unwrap(self.hasher)
+            .map(|hasher| hasher.into_inner().finalize())
+            .map_err(|hasher| Self { hasher })
+    }
+}
+
+impl<D: MinDigest> MinDigest for LocalSharedDigest<D> {
+    type Output = Option<D::Output>;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        self.hasher.borrow_mut().update(data);
+    }
+    fn finalize(self) -> Self::Output {
+        self.try_finalize().ok()
+    }
+}
+
+/// Any handle can take the digest of everything written through all of them so far, which
+/// starts the shared hasher over
+impl<D: MinDigestReset> MinDigestReset for LocalSharedDigest<D> {
+    fn finalize_reset(&mut self) -> Self::Output {
+        Some(self.hasher.borrow_mut().finalize_reset())
+    }
+}
@@ -0,0 +1,63 @@
+//! Adler-32 as used by [zlib](https://www.rfc-editor.org/rfc/rfc1950#section-8)
+
+use super::{MinDigest, MinDigestReset};
+
+const MOD: u32 = 65521;
+/// The most bytes that can be summed before `b` could overflow a `u32`
+const NMAX: usize = 5552;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Adler32 {
+    a: u32,
+    b: u32,
+}
+
+impl Adler32 {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Continue from `adler`, the checksum of the data before what will be hashed
+    pub fn new_with_initial(adler: u32) -> Self {
+        Self {
+            a: adler & 0xFFFF,
+            b: adler >> 16,
+        }
+    }
+}
+
+impl Default for Adler32 {
+    fn default() -> Self {
+        Self::new_with_initial(1)
+    }
+}
+
+impl MinDigest for Adler32 {
+    type Output = u32;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        for chunk in data.as_ref().chunks(NMAX) {
+            for &byte in chunk {
+                self.a += u32::from(byte);
+                self.b += self.a;
+            }
+            self.a %= MOD;
+            self.b %= MOD;
+        }
+    }
+    fn finalize(self) -> Self::Output {
+        (self.b << 16) | self.a
+    }
+}
+
+impl MinDigestReset for Adler32 {
+    fn finalize_reset(&mut self) -> Self::Output {
+        core::mem::take(self).finalize()
+    }
+}
+
+impl<T> crate::WriteHasher<Adler32, T> {
+    /// Continue the Adler-32 checksum `adler` with what's written
+    pub fn new_with_initial(inner: T, adler: u32) -> Self {
+        Self::new_with_hasher(inner, Adler32::new_with_initial(adler))
+    }
+}
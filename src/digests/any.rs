@@ -0,0 +1,154 @@
+//! [`AnyHasher`], one concrete type over every built-in algorithm enabled by the features
+
+use super::MinDigest;
+use crate::{HashWriterError, OutputBytes};
+
+/// The output of an [`AnyHasher`]: the bytes of the algorithm's output and its name
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AnyDigest {
+    /// The canonical name of the algorithm, see [`AnyHasher::name`]
+    pub algorithm: &'static str,
+    pub bytes: Vec<u8>,
+}
+
+impl OutputBytes for AnyDigest {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
+        std::borrow::Cow::Borrowed(&self.bytes)
+    }
+}
+
+/// Lowercase and without the separators `-`, `_` and `/`, so e.g. "SHA-256" matches "sha256"
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| !matches!(c, '-' | '_' | '/'))
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+macro_rules! any_hasher {
+    ($($(#[$attr:meta])* $variant:ident($hasher:ty) = $make:expr, [$name:literal $(, $alias:literal)*];)*) => {
+        /// A hasher for any of the built-in algorithms, picked at runtime without a type
+        /// parameter or a trait object. Each variant is only available when the feature of its
+        /// algorithm is enabled.
+        ///
+        /// Algorithms are parsed from their names, ignoring case and `-` / `_` / `/`, e.g.
+        /// "SHA-256" or "sha512/224". There's no `Default` as no algorithm is a sensible one.
+        ///
+        /// ```rust
+        /// # #[cfg(all(feature = "stdio", feature = "crc32c"))] {
+        /// use std::io::Write;
+        /// use write_hasher::{AnyHasher, MinDigest, WriteHasher};
+        /// let hasher: AnyHasher = "CRC-32C".parse().unwrap();
+        /// let mut writer = WriteHasher::new_with_hasher(std::io::sink(), hasher);
+        /// writer.write_all(b"123456789").unwrap();
+        /// let digest = writer.finalize();
+        /// assert_eq!(digest.algorithm, "crc32c");
+        /// assert_eq!(digest.bytes, 0xe3069283u32.to_be_bytes());
+        /// # }
+        /// ```
+        // the hasher states are stored inline like they would be in a `WriteHasher`
+        #[allow(clippy::large_enum_variant)]
+        #[derive(Clone)]
+        #[non_exhaustive]
+        pub enum AnyHasher {
+            $(
+                $(#[$attr])*
+                $variant($hasher),
+            )*
+        }
+
+        impl AnyHasher {
+            /// The canonical name of the algorithm, lowercase as accepted by the `FromStr` impl
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(
+                        $(#[$attr])*
+                        Self::$variant(_) => $name,
+                    )*
+                }
+            }
+        }
+
+        impl core::str::FromStr for AnyHasher {
+            type Err = HashWriterError;
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let name = normalize(s);
+                $(
+                    $(#[$attr])*
+                    if [$name $(, $alias)*].iter().any(|n| normalize(n) == name) {
+                        return Ok(Self::$variant($make));
+                    }
+                )*
+                Err(HashWriterError::Parse(format!("unknown or disabled algorithm {:?}", s)))
+            }
+        }
+
+        impl MinDigest for AnyHasher {
+            type Output = AnyDigest;
+            fn update(&mut self, data: impl AsRef<[u8]>) {
+                match self {
+                    $(
+                        $(#[$attr])*
+                        Self::$variant(hasher) => MinDigest::update(hasher, data),
+                    )*
+                }
+            }
+            fn finalize(self) -> AnyDigest {
+                let algorithm = self.name();
+                let bytes = match self {
+                    $(
+                        $(#[$attr])*
+                        Self::$variant(hasher) => hasher.finalize_bytes(),
+                    )*
+                };
+                AnyDigest { algorithm, bytes }
+            }
+        }
+    };
+}
+
+any_hasher! {
+    #[cfg(feature = "sha1")]
+    Sha1(sha1::Sha1) = Default::default(), ["sha1"];
+    #[cfg(feature = "sha2")]
+    Sha224(sha2::Sha224) = Default::default(), ["sha224"];
+    #[cfg(feature = "sha2")]
+    Sha256(sha2::Sha256) = Default::default(), ["sha256"];
+    #[cfg(feature = "sha2")]
+    Sha384(sha2::Sha384) = Default::default(), ["sha384"];
+    #[cfg(feature = "sha2")]
+    Sha512(sha2::Sha512) = Default::default(), ["sha512"];
+    #[cfg(feature = "sha2")]
+    Sha512_224(sha2::Sha512_224) = Default::default(), ["sha512/224"];
+    #[cfg(feature = "sha2")]
+    Sha512_256(sha2::Sha512_256) = Default::default(), ["sha512/256"];
+    #[cfg(feature = "md2")]
+    Md2(md2::Md2) = Default::default(), ["md2"];
+    #[cfg(feature = "md4")]
+    Md4(md4::Md4) = Default::default(), ["md4"];
+    #[cfg(feature = "md5")]
+    Md5(md5::Context) = md5::Context::new(), ["md5"];
+    #[cfg(feature = "blake2")]
+    Blake2b512(blake2::Blake2b512) = Default::default(), ["blake2b512", "blake2b"];
+    #[cfg(feature = "blake2")]
+    Blake2s256(blake2::Blake2s256) = Default::default(), ["blake2s256", "blake2s"];
+    #[cfg(feature = "blake3")]
+    Blake3(blake3::Hasher) = blake3::Hasher::new(), ["blake3"];
+    #[cfg(feature = "crc32fast")]
+    Crc32(crc32fast::Hasher) = crc32fast::Hasher::new(), ["crc32"];
+    #[cfg(feature = "crc32c")]
+    Crc32c(super::crc32c::Crc32c) = Default::default(), ["crc32c"];
+    Crc24(super::crc24::Crc24) = Default::default(), ["crc24"];
+    #[cfg(feature = "adler")]
+    Adler32(super::adler32::Adler32) = Default::default(), ["adler32"];
+    #[cfg(feature = "crc64")]
+    Crc64Xz(super::crc64::Crc64Xz) = Default::default(), ["crc64xz", "crc64"];
+    #[cfg(feature = "crc64")]
+    Crc64Nvme(super::crc64::Crc64Nvme) = Default::default(), ["crc64nvme"];
+    #[cfg(feature = "xxh3")]
+    Xxh3_64(super::xxh3::Xxh3_64) = Default::default(), ["xxh3_64", "xxh3"];
+    #[cfg(feature = "xxh3")]
+    Xxh3_128(super::xxh3::Xxh3_128) = Default::default(), ["xxh3_128"];
+    #[cfg(feature = "xxh64")]
+    Xxh64(super::xxh64::Xxh64) = Default::default(), ["xxh64"];
+}
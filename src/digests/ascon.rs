@@ -0,0 +1,13 @@
+use super::{MinDigest, MinDigestReset};
+delegate_digest_mindigest!(ascon_hash::AsconHash256);
+
+/// The XOF is finalized into its reader so any length of output can be read from it
+impl MinDigest for ascon_hash::AsconXof128 {
+    type Output = ascon_hash::AsconXof128Reader;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        <Self as digest::Update>::update(self, data.as_ref())
+    }
+    fn finalize(self) -> Self::Output {
+        <Self as digest::ExtendableOutput>::finalize_xof(self)
+    }
+}
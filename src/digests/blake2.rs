@@ -0,0 +1,77 @@
+use super::{MinDigest, MinDigestReset};
+use blake2::digest::{InvalidLength, InvalidOutputSize, KeyInit, Mac, MacError};
+use blake2::digest::{Update, VariableOutput};
+// use digest::consts::*;
+// use digest::typenum::*;
+
+// delegate_digest_mindigest!(blake2::Blake2b);
+delegate_digest_mindigest!(blake2::Blake2b512);
+// delegate_digest_mindigest!(blake2::Blake2bCore);
+// delegate_digest_mindigest!(blake2::Blake2s);
+delegate_digest_mindigest!(blake2::Blake2s256);
+// delegate_digest_mindigest!(blake2::Blake2sCore);
+
+/// Keyed BLAKE2 only implements `Mac`, the output is the tag
+macro_rules! blake2_mac {
+    ($($mac:ty, $key_size:literal);*) => {
+        $(
+            impl MinDigest for $mac {
+                type Output = digest::Output<$mac>;
+                fn update(&mut self, data: impl AsRef<[u8]>) {
+                    Mac::update(self, data.as_ref())
+                }
+                fn finalize(self) -> Self::Output {
+                    Mac::finalize(self).into_bytes()
+                }
+            }
+
+            impl<T> crate::WriteHasher<$mac, T> {
+                #[doc = concat!("Keyed BLAKE2, errors if `key` is longer than ", $key_size, " bytes")]
+                pub fn new_keyed(inner: T, key: &[u8]) -> Result<Self, InvalidLength> {
+                    <$mac as KeyInit>::new_from_slice(key).map(|hasher| Self::new_with_hasher(inner, hasher))
+                }
+
+                /// Check the tag of everything written against `expected` in constant time
+                pub fn finalize_verify_tag(self, expected: &[u8]) -> Result<(), MacError> {
+                    self.hasher.verify_slice(expected)
+                }
+            }
+        )*
+    };
+}
+
+blake2_mac!(blake2::Blake2bMac512, 64; blake2::Blake2sMac256, 32);
+
+/// The variable output variants pick their output size at runtime, the output is a `Vec` of
+/// that many bytes
+macro_rules! blake2_var {
+    ($($var:ty, $max_size:literal);*) => {
+        $(
+            impl MinDigest for $var {
+                type Output = Vec<u8>;
+                fn update(&mut self, data: impl AsRef<[u8]>) {
+                    Update::update(self, data.as_ref())
+                }
+                fn finalize(self) -> Self::Output {
+                    let mut output = vec![0; self.output_size()];
+                    self.finalize_variable(&mut output)
+                        .expect("output is sized to output_size");
+                    output
+                }
+            }
+
+            impl<T> crate::WriteHasher<$var, T> {
+                #[doc = concat!("BLAKE2 with an output of `len` bytes, errors unless it's between 1 and ", $max_size)]
+                pub fn new_with_output_size(inner: T, len: usize) -> Result<Self, InvalidOutputSize> {
+                    // BLAKE2 has no empty output, but the blake2 crate accepts 0
+                    if len == 0 {
+                        return Err(InvalidOutputSize);
+                    }
+                    <$var>::new(len).map(|hasher| Self::new_with_hasher(inner, hasher))
+                }
+            }
+        )*
+    };
+}
+
+blake2_var!(blake2::Blake2bVar, 64; blake2::Blake2sVar, 32);
@@ -0,0 +1,93 @@
+use super::MinDigest;
+impl MinDigest for blake3::Hasher {
+    type Output = blake3::Hash;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        self.update(data.as_ref());
+    }
+    fn finalize(self) -> Self::Output {
+        blake3::Hasher::finalize(&self)
+    }
+}
+
+impl<T> crate::WriteHasher<blake3::Hasher, T> {
+    /// BLAKE3 in keyed hashing mode, a MAC with a 32 byte key
+    pub fn new_keyed(inner: T, key: &[u8; 32]) -> Self {
+        Self::new_with_hasher(inner, blake3::Hasher::new_keyed(key))
+    }
+
+    /// BLAKE3 in key derivation mode, deriving a key from what's written for `context`, which
+    /// should be a hardcoded, globally unique and application specific string
+    pub fn new_derive_key(inner: T, context: &str) -> Self {
+        Self::new_with_hasher(inner, blake3::Hasher::new_derive_key(context))
+    }
+
+    /// Finalize into a reader of BLAKE3's extendable output, the first 32 bytes of which are
+    /// the regular hash
+    pub fn finalize_xof(self) -> blake3::OutputReader {
+        self.hasher.finalize_xof()
+    }
+
+    /// Fill `out` with BLAKE3's extendable output
+    pub fn finalize_xof_into(self, out: &mut [u8]) {
+        self.finalize_xof().fill(out)
+    }
+
+    /// Same as [`finalize_xof`](Self::finalize_xof) but also hands back the inner writer
+    pub fn finalize_xof_into_parts(self) -> (blake3::OutputReader, T) {
+        (self.hasher.finalize_xof(), self.inner)
+    }
+}
+
+/// [`blake3::Hasher`] using `update_rayon` for updates of at least `min_parallel_len` bytes, so
+/// large writes are hashed on several threads
+#[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+#[cfg(feature = "rayon")]
+#[derive(Debug, Clone)]
+pub struct ParallelBlake3 {
+    hasher: blake3::Hasher,
+    min_parallel_len: usize,
+}
+
+#[cfg(feature = "rayon")]
+impl ParallelBlake3 {
+    /// Wrap `hasher`, which can be keyed or deriving a key
+    pub fn new(hasher: blake3::Hasher) -> Self {
+        Self {
+            hasher,
+            min_parallel_len: 128 * 1024,
+        }
+    }
+
+    /// Hash updates of at least `len` bytes in parallel
+    pub fn with_min_parallel_len(mut self, len: usize) -> Self {
+        self.min_parallel_len = len;
+        self
+    }
+
+    pub fn into_inner(self) -> blake3::Hasher {
+        self.hasher
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl Default for ParallelBlake3 {
+    fn default() -> Self {
+        Self::new(blake3::Hasher::new())
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl MinDigest for ParallelBlake3 {
+    type Output = blake3::Hash;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        let data = data.as_ref();
+        if data.len() < self.min_parallel_len {
+            self.hasher.update(data);
+        } else {
+            self.hasher.update_rayon(data);
+        }
+    }
+    fn finalize(self) -> Self::Output {
+        self.hasher.finalize()
+    }
+}
@@ -0,0 +1,69 @@
+//! Checksums whose values over adjacent pieces of data can be merged without the data
+
+use super::MinDigest;
+
+/// A checksum that can compute the value of a concatenation from the values of its parts, so
+/// pieces of a stream can be hashed independently (in parallel or by different parties) and
+/// combined afterwards.
+pub trait CombineDigest: MinDigest {
+    /// The output over `lhs` data immediately followed by `rhs_len` bytes of `rhs` data
+    fn combine(lhs: Self::Output, rhs: Self::Output, rhs_len: u64) -> Self::Output;
+}
+
+/// Combine the outputs of consecutive parts, each given with its length in bytes, into the output
+/// of the whole stream
+///
+/// ```rust
+/// # #[cfg(feature = "crc32c")] {
+/// use write_hasher::{combine_all, crc32c::Crc32c, hash_bytes};
+/// let parts = [
+///     (hash_bytes::<Crc32c>(b"1234"), 4),
+///     (hash_bytes::<Crc32c>(b""), 0),
+///     (hash_bytes::<Crc32c>(b"56789"), 5),
+/// ];
+/// assert_eq!(combine_all::<Crc32c>(&parts), 0xe3069283);
+/// # }
+/// ```
+pub fn combine_all<D: CombineDigest + Default>(parts: &[(D::Output, u64)]) -> D::Output
+where
+    D::Output: Clone,
+{
+    parts
+        .iter()
+        .cloned()
+        .fold(D::default().finalize(), |lhs, (rhs, rhs_len)| {
+            D::combine(lhs, rhs, rhs_len)
+        })
+}
+
+/// Multiply two polynomials modulo the bit-reflected 32 bit `poly`
+fn multiply_reflected(a: u32, mut b: u32, poly: u32) -> u32 {
+    let mut product = 0;
+    let mut bit = 1 << 31;
+    while bit != 0 {
+        if a & bit != 0 {
+            product ^= b;
+        }
+        b = if b & 1 != 0 { (b >> 1) ^ poly } else { b >> 1 };
+        bit >>= 1;
+    }
+    product
+}
+
+/// Combine two reflected CRC-32 values with matching init and final xor (`0xFFFFFFFF` or `0`),
+/// by multiplying `lhs` with `x^(8 * rhs_len)` the same way zlib's `crc32_combine` does
+#[cfg_attr(not(feature = "crc32fast"), allow(dead_code))]
+pub(super) fn combine_reflected32(poly: u32, lhs: u32, rhs: u32, rhs_len: u64) -> u32 {
+    // x^0 and x^8 in the reflected bit order
+    let mut shift = 1 << 31;
+    let mut square = 1 << 23;
+    let mut len = rhs_len;
+    while len != 0 {
+        if len & 1 != 0 {
+            shift = multiply_reflected(square, shift, poly);
+        }
+        square = multiply_reflected(square, square, poly);
+        len >>= 1;
+    }
+    multiply_reflected(shift, lhs, poly) ^ rhs
+}
@@ -0,0 +1,99 @@
+//! CRC-24 as used by [OpenPGP](https://www.rfc-editor.org/rfc/rfc4880#section-6.1) armor
+
+use super::{CombineDigest, MinDigest};
+
+const INIT: u32 = 0xB704CE;
+const POLY: u32 = 0x864CFB;
+
+const TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = (i as u32) << 16;
+        let mut bit = 0;
+        while bit < 8 {
+            crc <<= 1;
+            if crc & 0x1000000 != 0 {
+                crc ^= POLY;
+            }
+            bit += 1;
+        }
+        table[i] = crc & 0xFFFFFF;
+        i += 1;
+    }
+    table
+};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Crc24(u32);
+
+impl Crc24 {
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl Default for Crc24 {
+    fn default() -> Self {
+        Self(INIT)
+    }
+}
+
+impl MinDigest for Crc24 {
+    /// Only the low 24 bits are used
+    type Output = u32;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        for byte in data.as_ref() {
+            let index = ((self.0 >> 16) as u8 ^ byte) as usize;
+            self.0 = ((self.0 << 8) ^ TABLE[index]) & 0xFFFFFF;
+        }
+    }
+    fn finalize(self) -> Self::Output {
+        self.0
+    }
+}
+
+/// Multiply two polynomials modulo [`POLY`]
+fn multiply(a: u32, b: u32) -> u32 {
+    let mut product = 0;
+    for bit in (0..24).rev() {
+        product <<= 1;
+        if product & 0x1000000 != 0 {
+            product ^= 0x1000000 | POLY;
+        }
+        if a & (1 << bit) != 0 {
+            product ^= b;
+        }
+    }
+    product
+}
+
+impl CombineDigest for Crc24 {
+    fn combine(lhs: u32, rhs: u32, rhs_len: u64) -> u32 {
+        // without a final xor both values carry the init shifted by their length, so the one in
+        // `lhs` has to be cancelled out before shifting it past `rhs`
+        let mut shift = 1;
+        let mut square = 1 << 8;
+        let mut len = rhs_len;
+        while len != 0 {
+            if len & 1 != 0 {
+                shift = multiply(square, shift);
+            }
+            square = multiply(square, square);
+            len >>= 1;
+        }
+        multiply(shift, lhs ^ INIT) ^ rhs
+    }
+}
+
+/// Format a checksum as the `=XXXX` line of an armor footer, i.e. the base64 encoding of its
+/// three big-endian bytes
+pub fn to_armor_string(crc: u32) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut armor = String::with_capacity(5);
+    armor.push('=');
+    for shift in [18, 12, 6, 0] {
+        armor.push(ALPHABET[(crc >> shift) as usize & 0x3F] as char);
+    }
+    armor
+}
@@ -0,0 +1,65 @@
+use super::{combine::combine_reflected32, CombineDigest, MinDigest, MinDigestReset};
+#[repr(transparent)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Crc32c(u32);
+
+impl Crc32c {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Continue from `crc`, the checksum of the data before what will be hashed
+    pub fn new_with_initial(crc: u32) -> Self {
+        Self(crc)
+    }
+
+    /// The checksum of the data hashed by `self` immediately followed by the `other_len` bytes
+    /// hashed by `other`, e.g. to merge segments of a file hashed in parallel
+    pub fn combine(self, other: Crc32c, other_len: u64) -> Crc32c {
+        Self(<Self as CombineDigest>::combine(self.0, other.0, other_len))
+    }
+}
+
+impl<T> crate::WriteHasher<Crc32c, T> {
+    /// Continue the CRC-32C `crc` with what's written
+    pub fn new_with_initial(inner: T, crc: u32) -> Self {
+        Self::new_with_hasher(inner, Crc32c::new_with_initial(crc))
+    }
+}
+
+impl MinDigest for Crc32c {
+    type Output = u32;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        self.0 = crc32c::crc32c_append(self.0, data.as_ref())
+    }
+    fn finalize(self) -> Self::Output {
+        self.0
+    }
+}
+
+impl MinDigestReset for Crc32c {
+    fn finalize_reset(&mut self) -> Self::Output {
+        core::mem::take(&mut self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl crate::state::ResumableDigest for Crc32c {
+    const ALGORITHM: &'static str = "crc32c";
+    fn save_state(&self) -> Vec<u8> {
+        self.0.to_be_bytes().to_vec()
+    }
+    fn restore_state(state: &[u8]) -> Option<Self> {
+        state
+            .try_into()
+            .ok()
+            .map(|crc| Self(u32::from_be_bytes(crc)))
+    }
+}
+
+impl CombineDigest for Crc32c {
+    fn combine(lhs: u32, rhs: u32, rhs_len: u64) -> u32 {
+        // the bit-reflected Castagnoli polynomial
+        combine_reflected32(0x82F63B78, lhs, rhs, rhs_len)
+    }
+}
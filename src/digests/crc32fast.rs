@@ -0,0 +1,37 @@
+use super::{combine::combine_reflected32, CombineDigest, MinDigest, MinDigestReset};
+impl MinDigest for crc32fast::Hasher {
+    type Output = u32;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        self.update(data.as_ref())
+    }
+    fn finalize(self) -> Self::Output {
+        self.finalize()
+    }
+}
+
+impl MinDigestReset for crc32fast::Hasher {
+    fn finalize_reset(&mut self) -> Self::Output {
+        let crc = self.clone().finalize();
+        self.reset();
+        crc
+    }
+}
+
+#[cfg(feature = "serde")]
+impl crate::state::ResumableDigest for crc32fast::Hasher {
+    const ALGORITHM: &'static str = "crc32";
+    fn save_state(&self) -> Vec<u8> {
+        self.clone().finalize().to_be_bytes().to_vec()
+    }
+    fn restore_state(state: &[u8]) -> Option<Self> {
+        let crc = u32::from_be_bytes(state.try_into().ok()?);
+        Some(crc32fast::Hasher::new_with_initial(crc))
+    }
+}
+
+impl CombineDigest for crc32fast::Hasher {
+    fn combine(lhs: u32, rhs: u32, rhs_len: u64) -> u32 {
+        // the bit-reflected IEEE polynomial
+        combine_reflected32(0xEDB88320, lhs, rhs, rhs_len)
+    }
+}
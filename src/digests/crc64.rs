@@ -0,0 +1,77 @@
+//! CRC-64 with the polynomials of [XZ](https://tukaani.org/xz/xz-file-format.txt) (ECMA-182) and
+//! NVMe end-to-end data protection, both bit-reflected with an all ones initial value and final
+//! xor
+
+use super::{MinDigest, MinDigestReset};
+
+const fn table(poly: u64) -> [u64; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u64;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ poly
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+macro_rules! crc64 {
+    ($(#[$doc:meta])* $name:ident, $poly:literal) => {
+        $(#[$doc])*
+        #[derive(Debug, Default, Clone, Copy)]
+        pub struct $name(u64);
+
+        impl $name {
+            const TABLE: [u64; 256] = table($poly);
+
+            pub fn new() -> Self {
+                Default::default()
+            }
+
+            /// Continue from `crc`, the checksum of the data before what will be hashed
+            pub fn new_with_initial(crc: u64) -> Self {
+                Self(crc)
+            }
+        }
+
+        impl MinDigest for $name {
+            type Output = u64;
+            fn update(&mut self, data: impl AsRef<[u8]>) {
+                let mut crc = !self.0;
+                for byte in data.as_ref() {
+                    crc = (crc >> 8) ^ Self::TABLE[(crc as u8 ^ byte) as usize];
+                }
+                self.0 = !crc;
+            }
+            fn finalize(self) -> Self::Output {
+                self.0
+            }
+        }
+
+        impl MinDigestReset for $name {
+            fn finalize_reset(&mut self) -> Self::Output {
+                core::mem::take(&mut self.0)
+            }
+        }
+    };
+}
+
+crc64!(
+    /// CRC-64/XZ, also known as CRC-64/GO-ECMA
+    Crc64Xz,
+    0xC96C5795D7870F42
+);
+crc64!(
+    /// CRC-64/NVME
+    Crc64Nvme,
+    0x9A6C9329AC4BC9B5
+);
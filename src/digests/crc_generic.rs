@@ -0,0 +1,128 @@
+//! Any CRC of the [`crc`] crate's catalogue, or a custom one, of up to 128 bits
+//!
+//! [`CrcHasher`] borrows the [`crc::Crc`] holding the lookup table, usually a `static` so the
+//! table is computed at compile time. [`CrcHasher::from_algorithm`] makes the table from just a
+//! `&'static` [`crc::Algorithm`] instead, once per algorithm for the life of the process.
+//!
+//! ```rust
+//! use write_hasher::{CrcHasher, MinDigest, WriteHasher};
+//!
+//! static MODBUS: crc::Crc<u16> = crc::Crc::<u16>::new(&crc::CRC_16_MODBUS);
+//!
+//! let mut hasher = WriteHasher::new_with_hasher(Vec::<u8>::new(), CrcHasher::new(&MODBUS));
+//! hasher.update(b"123456789");
+//! assert_eq!(hasher.finalize(), 0x4b37);
+//!
+//! let mut hasher = WriteHasher::new_crc(Vec::<u8>::new(), &crc::CRC_32_BZIP2);
+//! hasher.update(b"123456789");
+//! assert_eq!(hasher.finalize(), 0xfc891918);
+//! ```
+
+use std::sync::Mutex;
+
+use ::crc::{Algorithm, Crc, Digest, Width};
+
+use super::MinDigest;
+
+/// A running CRC over a [`crc::Crc`]
+#[derive(Clone)]
+pub struct CrcHasher<'a, W: CrcWidth> {
+    digest: Digest<'a, W>,
+}
+
+/// The integer types [`CrcHasher`] works with, `u8` to `u128`
+pub trait CrcWidth: Width + Copy {
+    #[doc(hidden)]
+    fn digest(crc: &Crc<Self>, initial: Option<Self>) -> Digest<'_, Self>;
+    #[doc(hidden)]
+    fn update(digest: &mut Digest<'_, Self>, data: &[u8]);
+    #[doc(hidden)]
+    fn finalize(digest: Digest<'_, Self>) -> Self;
+    /// The table of `algorithm`, made on first use
+    #[doc(hidden)]
+    fn table(algorithm: &'static Algorithm<Self>) -> &'static Crc<Self>;
+}
+
+macro_rules! crc_width {
+    ($($width:ty),*) => {
+        $(
+            impl CrcWidth for $width {
+                fn digest(crc: &Crc<Self>, initial: Option<Self>) -> Digest<'_, Self> {
+                    match initial {
+                        Some(initial) => crc.digest_with_initial(initial),
+                        None => crc.digest(),
+                    }
+                }
+                fn update(digest: &mut Digest<'_, Self>, data: &[u8]) {
+                    digest.update(data)
+                }
+                fn finalize(digest: Digest<'_, Self>) -> Self {
+                    digest.finalize()
+                }
+                fn table(algorithm: &'static Algorithm<Self>) -> &'static Crc<Self> {
+                    static TABLES: Mutex<Vec<&'static Crc<$width>>> = Mutex::new(Vec::new());
+                    let mut tables = TABLES.lock().unwrap_or_else(|e| e.into_inner());
+                    let cached = tables
+                        .iter()
+                        .find(|crc| core::ptr::eq(crc.algorithm, algorithm));
+                    match cached {
+                        Some(crc) => crc,
+                        None => {
+                            let crc = Box::leak(Box::new(Crc::<$width>::new(algorithm)));
+                            tables.push(crc);
+                            crc
+                        }
+                    }
+                }
+            }
+        )*
+    };
+}
+
+crc_width!(u8, u16, u32, u64, u128);
+
+impl<'a, W: CrcWidth> CrcHasher<'a, W> {
+    pub fn new(crc: &'a Crc<W>) -> Self {
+        Self {
+            digest: W::digest(crc, None),
+        }
+    }
+
+    /// Start from `initial` rather than the algorithm's initial value
+    pub fn with_initial(crc: &'a Crc<W>, initial: W) -> Self {
+        Self {
+            digest: W::digest(crc, Some(initial)),
+        }
+    }
+}
+
+impl<W: CrcWidth> CrcHasher<'static, W> {
+    /// Hash with `algorithm`, its lookup table is made the first time it's used and kept for
+    /// later hashers
+    pub fn from_algorithm(algorithm: &'static Algorithm<W>) -> Self {
+        Self::new(W::table(algorithm))
+    }
+}
+
+impl<'a, W: CrcWidth> From<Digest<'a, W>> for CrcHasher<'a, W> {
+    fn from(digest: Digest<'a, W>) -> Self {
+        Self { digest }
+    }
+}
+
+impl<W: CrcWidth> MinDigest for CrcHasher<'_, W> {
+    type Output = W;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        W::update(&mut self.digest, data.as_ref())
+    }
+    fn finalize(self) -> Self::Output {
+        W::finalize(self.digest)
+    }
+}
+
+impl<W: CrcWidth, T> crate::WriteHasher<CrcHasher<'static, W>, T> {
+    /// Hash what's written with `algorithm`, see [`CrcHasher::from_algorithm`]
+    pub fn new_crc(inner: T, algorithm: &'static Algorithm<W>) -> Self {
+        Self::new_with_hasher(inner, CrcHasher::from_algorithm(algorithm))
+    }
+}
@@ -0,0 +1,122 @@
+//! [`DynMinDigest`], an object safe [`MinDigest`] for picking the algorithm at runtime
+
+use super::MinDigest;
+use crate::OutputBytes;
+
+/// An object safe version of [`MinDigest`], implemented for every `MinDigest` whose output can be
+/// viewed as bytes, so hashers can be picked at runtime as `Box<dyn DynMinDigest>`, which
+/// implements `MinDigest` in turn with the bytes of the output as its output.
+///
+/// ```rust
+/// # #[cfg(all(feature = "stdio", feature = "crc32c"))] {
+/// use std::io::Write;
+/// use write_hasher::{crc32c::Crc32c, DynMinDigest, MinDigest, WriteHasher};
+/// let hasher: Box<dyn DynMinDigest> = Box::new(Crc32c::new());
+/// let mut writer = WriteHasher::new_with_hasher(std::io::sink(), hasher);
+/// writer.write_all(b"123456789").unwrap();
+/// assert_eq!(writer.finalize(), 0xe3069283u32.to_be_bytes());
+/// # }
+/// ```
+pub trait DynMinDigest {
+    fn update_dyn(&mut self, data: &[u8]);
+    fn finalize_boxed(self: Box<Self>) -> Vec<u8>;
+}
+
+impl<D> DynMinDigest for D
+where
+    D: MinDigest,
+    D::Output: OutputBytes,
+{
+    fn update_dyn(&mut self, data: &[u8]) {
+        MinDigest::update(self, data)
+    }
+    fn finalize_boxed(self: Box<Self>) -> Vec<u8> {
+        (*self).finalize_bytes()
+    }
+}
+
+impl MinDigest for Box<dyn DynMinDigest> {
+    type Output = Vec<u8>;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        // through the trait object, `self` would pick up the blanket impl and recurse
+        (**self).update_dyn(data.as_ref())
+    }
+    fn finalize(self) -> Vec<u8> {
+        self.finalize_boxed()
+    }
+}
+
+impl MinDigest for Box<dyn DynMinDigest + Send> {
+    type Output = Vec<u8>;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        (**self).update_dyn(data.as_ref())
+    }
+    fn finalize(self) -> Vec<u8> {
+        self.finalize_boxed()
+    }
+}
+
+/// A [`digest::DynDigest`] trait object as a [`MinDigest`], for RustCrypto hashers picked at
+/// runtime. The trait can't be implemented for `Box<dyn DynDigest>` itself as it would overlap
+/// with the impl for every [`Digest`](digest::Digest).
+///
+/// Construct the writer with [`WriteHasher::new_with_hasher`](crate::WriteHasher::new_with_hasher)
+///
+/// ```rust
+/// # #[cfg(feature = "stdio")] {
+/// extern crate sha2;
+/// use digest::DynDigest;
+/// use write_hasher::{BoxedDynDigest, MinDigest, WriteHasher};
+/// fn by_name(name: &str) -> Option<Box<dyn DynDigest>> {
+///     match name {
+///         "sha256" => Some(Box::new(sha2::Sha256::default())),
+///         "sha512" => Some(Box::new(sha2::Sha512::default())),
+///         _ => None,
+///     }
+/// }
+/// let mut src = std::fs::File::open("LICENSE").unwrap();
+/// let hasher = BoxedDynDigest::new(by_name("sha256").unwrap());
+/// let mut writer = WriteHasher::new_with_hasher(std::io::sink(), hasher);
+/// std::io::copy(&mut src, &mut writer).unwrap();
+/// assert_eq!(writer.finalize().len(), 32);
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "digest")))]
+#[cfg(feature = "digest")]
+pub struct BoxedDynDigest(pub Box<dyn digest::DynDigest>);
+
+#[cfg(feature = "digest")]
+impl BoxedDynDigest {
+    pub fn new(hasher: Box<dyn digest::DynDigest>) -> Self {
+        Self(hasher)
+    }
+
+    pub fn into_inner(self) -> Box<dyn digest::DynDigest> {
+        self.0
+    }
+}
+
+#[cfg(feature = "digest")]
+impl From<Box<dyn digest::DynDigest>> for BoxedDynDigest {
+    fn from(hasher: Box<dyn digest::DynDigest>) -> Self {
+        Self(hasher)
+    }
+}
+
+#[cfg(feature = "digest")]
+impl Clone for BoxedDynDigest {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+#[cfg(feature = "digest")]
+impl MinDigest for BoxedDynDigest {
+    type Output = Box<[u8]>;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        digest::DynDigest::update(&mut *self.0, data.as_ref())
+    }
+    fn finalize(self) -> Box<[u8]> {
+        <dyn digest::DynDigest>::finalize(self.0)
+    }
+}
@@ -0,0 +1,71 @@
+//! Key derivation with [HKDF](https://www.rfc-editor.org/rfc/rfc5869) from streamed content
+
+use super::MinDigest;
+use digest::core_api::BlockSizeUser;
+use digest::Digest;
+pub use hkdf::InvalidLength;
+
+impl<D: Digest + BlockSizeUser + Clone, T> crate::WriteHasher<D, T> {
+    /// Fill `okm` with key material derived with HKDF over `D`.
+    ///
+    /// The hasher only keeps the digest of what was written so that digest is used as the
+    /// input key material, use [`DerivingDigest`] to use the content itself.
+    ///
+    /// ```rust
+    /// extern crate sha2;
+    /// use write_hasher::WriteHasher;
+    /// let mut src = std::fs::File::open("LICENSE").unwrap();
+    /// let mut hasher = WriteHasher::<sha2::Sha256, _>::new(std::io::sink());
+    /// std::io::copy(&mut src, &mut hasher).unwrap();
+    /// let mut key = [0; 64];
+    /// hasher
+    ///     .finalize_hkdf(Some(b"salt"), b"file key", &mut key)
+    ///     .unwrap();
+    /// ```
+    pub fn finalize_hkdf(
+        self,
+        salt: Option<&[u8]>,
+        info: &[u8],
+        okm: &mut [u8],
+    ) -> Result<(), InvalidLength> {
+        let ikm = Digest::finalize(self.hasher);
+        hkdf::SimpleHkdf::<D>::new(salt, &ikm).expand(info, okm)
+    }
+}
+
+/// Runs HKDF-Extract over everything written to it, finalizing runs HKDF-Expand and returns
+/// the output key material.
+pub struct DerivingDigest<D: Digest + BlockSizeUser + Clone> {
+    extract: hkdf::SimpleHkdfExtract<D>,
+    info: Vec<u8>,
+    okm_len: usize,
+}
+
+impl<D: Digest + BlockSizeUser + Clone> DerivingDigest<D> {
+    /// Errors if `okm_len` is more than HKDF can produce for `D`, i.e. 255 times its output
+    /// size
+    pub fn new(salt: Option<&[u8]>, info: &[u8], okm_len: usize) -> Result<Self, InvalidLength> {
+        if okm_len > 255 * <D as Digest>::output_size() {
+            return Err(InvalidLength);
+        }
+        Ok(Self {
+            extract: hkdf::SimpleHkdfExtract::new(salt),
+            info: info.to_vec(),
+            okm_len,
+        })
+    }
+}
+
+impl<D: Digest + BlockSizeUser + Clone> MinDigest for DerivingDigest<D> {
+    type Output = Vec<u8>;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        self.extract.input_ikm(data.as_ref())
+    }
+    fn finalize(self) -> Self::Output {
+        let (_, hkdf) = self.extract.finalize();
+        let mut okm = vec![0; self.okm_len];
+        hkdf.expand(&self.info, &mut okm)
+            .expect("okm length is checked on construction");
+        okm
+    }
+}
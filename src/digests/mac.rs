@@ -0,0 +1,66 @@
+//! Message authentication codes implementing [`digest::Mac`], e.g. `hmac::Hmac<Sha256>`
+
+use super::MinDigest;
+pub use digest::{InvalidLength, MacError};
+use digest::{KeyInit, Mac};
+
+/// Any [`Mac`] as a [`MinDigest`], finalizing to the tag bytes
+///
+/// There is no `Default` as a MAC needs a key, construct the hasher with
+/// [`WriteHasher::new_with_key`](crate::WriteHasher::new_with_key) or
+/// [`WriteHasher::new_with_hasher`](crate::WriteHasher::new_with_hasher). Compare tags with
+/// [`WriteHasher::finalize_verify_tag`](crate::WriteHasher::finalize_verify_tag) rather than
+/// `==` on the output, which isn't constant time.
+#[derive(Clone)]
+pub struct MacHasher<M: Mac>(pub M);
+
+impl<M: Mac + KeyInit> MacHasher<M> {
+    /// Errors if `M` doesn't accept keys of `key.len()` bytes
+    pub fn new_from_slice(key: &[u8]) -> Result<Self, InvalidLength> {
+        <M as Mac>::new_from_slice(key).map(Self)
+    }
+}
+
+impl<M: Mac> From<M> for MacHasher<M> {
+    fn from(mac: M) -> Self {
+        Self(mac)
+    }
+}
+
+impl<M: Mac> MinDigest for MacHasher<M> {
+    type Output = digest::Output<M>;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        self.0.update(data.as_ref())
+    }
+    fn finalize(self) -> Self::Output {
+        self.0.finalize().into_bytes()
+    }
+}
+
+impl<M: Mac + KeyInit, T> crate::WriteHasher<MacHasher<M>, T> {
+    /// Authenticate with `M` keyed with `key`, errors if `M` doesn't accept keys of `key.len()`
+    /// bytes
+    ///
+    /// ```rust
+    /// extern crate hmac;
+    /// extern crate sha2;
+    /// use write_hasher::{mac::MacHasher, WriteHasher};
+    /// type HmacSha256 = hmac::Hmac<sha2::Sha256>;
+    ///
+    /// let mut src = std::fs::File::open("LICENSE").unwrap();
+    /// let mut hasher =
+    ///     WriteHasher::<MacHasher<HmacSha256>, _>::new_with_key(std::io::sink(), b"key").unwrap();
+    /// std::io::copy(&mut src, &mut hasher).unwrap();
+    /// let tag = write_hasher::MinDigest::finalize(hasher);
+    /// ```
+    pub fn new_with_key(inner: T, key: &[u8]) -> Result<Self, InvalidLength> {
+        MacHasher::new_from_slice(key).map(|hasher| Self::new_with_hasher(inner, hasher))
+    }
+}
+
+impl<M: Mac, T> crate::WriteHasher<MacHasher<M>, T> {
+    /// Check the tag of everything written against `expected` in constant time
+    pub fn finalize_verify_tag(self, expected: &[u8]) -> Result<(), MacError> {
+        self.hasher.0.verify_slice(expected)
+    }
+}
@@ -0,0 +1,2 @@
+use super::{MinDigest, MinDigestReset};
+delegate_digest_mindigest!(md4::Md4);
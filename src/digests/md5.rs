@@ -0,0 +1,34 @@
+use super::{MinDigest, MinDigestReset};
+impl MinDigest for md5::Context {
+    type Output = md5::Digest;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        self.consume(data)
+    }
+    fn finalize(self) -> Self::Output {
+        self.compute()
+    }
+}
+
+impl MinDigestReset for md5::Context {
+    fn finalize_reset(&mut self) -> Self::Output {
+        core::mem::replace(self, md5::Context::new()).compute()
+    }
+}
+
+impl<T> crate::WriteHasher<md5::Context, T> {
+    /// `md5::Context` doesn't implement `Default`, so it doesn't get
+    /// [`new`](crate::WriteHasher::new)
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "stdio")] {
+    /// use std::io::Write;
+    /// use write_hasher::{MinDigest, WriteHasher};
+    /// let mut writer = WriteHasher::<md5::Context, _>::new_md5(std::io::sink());
+    /// writer.write_all(b"abc").unwrap();
+    /// assert_eq!(format!("{:x}", writer.finalize()), "900150983cd24fb0d6963f7d28e17f72");
+    /// # }
+    /// ```
+    pub fn new_md5(inner: T) -> Self {
+        Self::new_with_hasher(inner, md5::Context::new())
+    }
+}
@@ -0,0 +1,4 @@
+//! RustCrypto's MD5, see the md5 module for the `md5` crate's `Context`
+
+use super::{MinDigest, MinDigestReset};
+delegate_digest_mindigest!(md_5::Md5);
@@ -0,0 +1,256 @@
+//! The [`MinDigest`] trait and its implementations for the supported hash functions
+
+#[cfg(feature = "digest")]
+use digest::Digest;
+
+use crate::combinators::{BorrowedDigest, MappedDigest};
+use crate::OutputBytes;
+
+/// A minimal version of [`Digest`][digest::digest] trait that is used to implement the WriteHasher
+/// and all implementations of the Digest trait.
+pub trait MinDigest {
+    type Output;
+    fn update(&mut self, data: impl AsRef<[u8]>);
+    fn finalize(self) -> Self::Output;
+
+    /// Finalize into the bytes of the output, see [`OutputBytes`] for how each output is viewed
+    fn finalize_bytes(self) -> Vec<u8>
+    where
+        Self: Sized,
+        Self::Output: OutputBytes,
+    {
+        self.finalize().to_bytes().into_owned()
+    }
+
+    /// Transform the output with `f` at finalize, e.g. to truncate it or wrap it in an ID type
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "digest")] {
+    /// extern crate sha2;
+    /// use write_hasher::MinDigest;
+    /// // the first 8 bytes of the SHA-256 as a cache key
+    /// let mut hasher = sha2::Sha256::default().map(|digest| u64::from_be_bytes(digest[..8].try_into().unwrap()));
+    /// MinDigest::update(&mut hasher, b"abc");
+    /// assert_eq!(MinDigest::finalize(hasher), 0xba7816bf8f01cfea);
+    /// # }
+    /// ```
+    fn map<O, F: FnOnce(Self::Output) -> O>(self, f: F) -> MappedDigest<Self, F, O>
+    where
+        Self: Sized,
+    {
+        MappedDigest::new(self, f)
+    }
+
+    /// Lend the hasher out, e.g. to a [`WriteHasher`](crate::WriteHasher) for one copy, and keep
+    /// using it afterwards. See [`BorrowedDigest`].
+    ///
+    /// ```rust
+    /// # #[cfg(all(feature = "stdio", feature = "crc32c"))] {
+    /// use write_hasher::{crc32c::Crc32c, MinDigest, WriteHasher};
+    /// let mut crc = Crc32c::default();
+    /// let mut writer = WriteHasher::new_with_hasher(std::io::sink(), crc.borrowed());
+    /// std::io::copy(&mut &b"1234"[..], &mut writer).unwrap();
+    /// drop(writer);
+    /// crc.update(b"56789");
+    /// assert_eq!(crc.finalize(), 0xe3069283);
+    /// # }
+    /// ```
+    fn borrowed(&mut self) -> BorrowedDigest<'_, Self>
+    where
+        Self: Sized,
+    {
+        BorrowedDigest::new(self)
+    }
+}
+
+/// A [`MinDigest`] that can finalize and start over in one step, keeping e.g. its key
+pub trait MinDigestReset: MinDigest {
+    /// Finalize the hash of the data so far and reset to the initial state
+    fn finalize_reset(&mut self) -> Self::Output;
+}
+
+/// Hash each of the chunks in order, as if they were written to a [`WriteHasher`](crate::WriteHasher)
+///
+/// ```rust
+/// # #[cfg(feature = "digest")] {
+/// extern crate sha2;
+/// let (header, body, footer) = ("header", vec![1u8, 2, 3], b"footer");
+/// let digest = write_hasher::hash_iter::<sha2::Sha256>([
+///     header.as_bytes(),
+///     body.as_slice(),
+///     footer,
+/// ]);
+/// assert_eq!(digest, write_hasher::hash_bytes::<sha2::Sha256>(b"header\x01\x02\x03footer"));
+/// # }
+/// ```
+pub fn hash_iter<D: MinDigest + Default>(
+    chunks: impl IntoIterator<Item = impl AsRef<[u8]>>,
+) -> D::Output {
+    let mut hasher = D::default();
+    for chunk in chunks {
+        hasher.update(chunk);
+    }
+    hasher.finalize()
+}
+
+/// Hash a single slice
+///
+/// ```rust
+/// # #[cfg(feature = "crc32c")] {
+/// use write_hasher::{crc32c::Crc32c, hash_bytes};
+/// assert_eq!(hash_bytes::<Crc32c>(b"123456789"), 0xe3069283);
+/// # }
+/// ```
+pub fn hash_bytes<D: MinDigest + Default>(data: impl AsRef<[u8]>) -> D::Output {
+    hash_iter::<D>([data])
+}
+
+#[cfg(feature = "digest")]
+impl<T: Digest> MinDigest for T {
+    type Output = digest::Output<T>;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        <T as Digest>::update(self, data)
+    }
+    fn finalize(self) -> Self::Output {
+        <T as Digest>::finalize(self)
+    }
+}
+
+#[cfg(feature = "digest")]
+impl<T: Digest + digest::FixedOutputReset> MinDigestReset for T {
+    fn finalize_reset(&mut self) -> Self::Output {
+        <T as Digest>::finalize_reset(self)
+    }
+}
+
+#[cfg(any(
+    feature = "sha2",
+    feature = "sha1",
+    feature = "md2",
+    feature = "md4",
+    feature = "md-5",
+    feature = "blake2",
+    feature = "ascon",
+    feature = "skein",
+    feature = "ripemd",
+    all(feature = "sha3", not(feature = "digest"))
+))]
+macro_rules! delegate_digest_mindigest {
+    (impl<$($g:ident),*> $x:ty where $($bounds:tt)*) => {
+        impl<$($g),*> MinDigest for $x where $($bounds)* {
+            type Output = digest::Output<$x>;
+            fn update(&mut self, data: impl AsRef<[u8]>) {
+                <Self as digest::Digest>::update(self, data)
+            }
+            fn finalize(self) -> Self::Output {
+                <Self as digest::Digest>::finalize(self)
+            }
+        }
+        impl<$($g),*> MinDigestReset for $x where $($bounds)* {
+            fn finalize_reset(&mut self) -> Self::Output {
+                <Self as digest::Digest>::finalize_reset(self)
+            }
+        }
+    };
+    ($($x:ty),*) => {
+        $(
+            impl MinDigest for $x {
+                type Output = digest::Output<$x>;
+                fn update(&mut self, data: impl AsRef<[u8]>) {
+                    <Self as digest::Digest>::update(self, data)
+                }
+                fn finalize(self) -> Self::Output {
+                    <Self as digest::Digest>::finalize(self)
+                }
+            }
+            impl MinDigestReset for $x {
+                fn finalize_reset(&mut self) -> Self::Output {
+                    <Self as digest::Digest>::finalize_reset(self)
+                }
+            }
+        )*
+    };
+}
+
+mod any;
+pub use any::{AnyDigest, AnyHasher};
+mod combine;
+pub use combine::{combine_all, CombineDigest};
+mod dynamic;
+#[cfg_attr(docsrs, doc(cfg(feature = "digest")))]
+#[cfg(feature = "digest")]
+pub use dynamic::BoxedDynDigest;
+pub use dynamic::DynMinDigest;
+
+#[cfg(feature = "ascon")]
+mod ascon;
+#[cfg(feature = "blake2")]
+mod blake2;
+#[cfg(feature = "blake3")]
+mod blake3;
+#[cfg_attr(docsrs, doc(cfg(all(feature = "blake3", feature = "rayon"))))]
+#[cfg(all(feature = "blake3", feature = "rayon"))]
+pub use self::blake3::ParallelBlake3;
+#[cfg(feature = "crc32fast")]
+mod crc32fast;
+#[cfg(feature = "md2")]
+mod md2;
+#[cfg(feature = "md4")]
+mod md4;
+#[cfg(feature = "md5")]
+mod md5;
+#[cfg(feature = "md-5")]
+mod md_5;
+#[cfg(feature = "ripemd")]
+mod ripemd;
+#[cfg(feature = "sha1")]
+mod sha1;
+#[cfg(feature = "sha2")]
+mod sha2;
+// with the digest feature these are covered by the blanket impl
+#[cfg(all(feature = "sha3", not(feature = "digest")))]
+mod sha3;
+#[cfg(feature = "skein")]
+mod skein;
+mod std_hasher;
+pub use std_hasher::StdHasher;
+#[cfg_attr(docsrs, doc(cfg(feature = "adler")))]
+#[cfg(feature = "adler")]
+pub mod adler32;
+pub mod crc24;
+#[cfg_attr(docsrs, doc(cfg(feature = "crc32c")))]
+#[cfg(feature = "crc32c")]
+pub mod crc32c;
+#[cfg_attr(docsrs, doc(cfg(feature = "crc64")))]
+#[cfg(feature = "crc64")]
+pub mod crc64;
+#[cfg_attr(docsrs, doc(cfg(feature = "crc")))]
+#[cfg(feature = "crc")]
+pub mod crc_generic;
+#[cfg_attr(docsrs, doc(cfg(feature = "hkdf")))]
+#[cfg(feature = "hkdf")]
+pub mod kdf;
+#[cfg_attr(docsrs, doc(cfg(feature = "mac")))]
+#[cfg(feature = "mac")]
+pub mod mac;
+#[cfg_attr(docsrs, doc(cfg(feature = "poly1305")))]
+#[cfg(feature = "poly1305")]
+pub mod poly1305;
+#[cfg_attr(docsrs, doc(cfg(feature = "sha1cd")))]
+#[cfg(feature = "sha1cd")]
+pub mod sha1cd;
+#[cfg_attr(docsrs, doc(cfg(feature = "sha3")))]
+#[cfg(feature = "sha3")]
+pub mod sp800_185;
+#[cfg_attr(docsrs, doc(cfg(feature = "uuid")))]
+#[cfg(feature = "uuid")]
+pub mod uuids;
+#[cfg_attr(docsrs, doc(cfg(feature = "sha3")))]
+#[cfg(feature = "sha3")]
+pub mod xof;
+#[cfg_attr(docsrs, doc(cfg(feature = "xxh3")))]
+#[cfg(feature = "xxh3")]
+pub mod xxh3;
+#[cfg_attr(docsrs, doc(cfg(feature = "xxh64")))]
+#[cfg(feature = "xxh64")]
+pub mod xxh64;
@@ -0,0 +1,60 @@
+//! The [Poly1305](https://www.rfc-editor.org/rfc/rfc8439#section-2.5) one-time authenticator
+//!
+//! **A key must only ever be used to authenticate a single message**, anyone who sees two tags
+//! made with the same key can forge tags for other messages. Poly1305 keys are usually derived
+//! per message, e.g. from a stream cipher's keystream as in ChaCha20-Poly1305.
+
+use super::MinDigest;
+use poly1305::universal_hash::{KeyInit, UniversalHash};
+pub use poly1305::{BLOCK_SIZE, KEY_SIZE};
+
+/// [`poly1305::Poly1305`] only accepts whole blocks, this buffers the trailing partial block
+/// between updates so data can be written in any chunk size.
+///
+/// It is intentionally neither `Clone` nor `Default` to make reusing a key harder to do by
+/// accident.
+pub struct Poly1305 {
+    mac: poly1305::Poly1305,
+    block: [u8; BLOCK_SIZE],
+    len: usize,
+}
+
+impl Poly1305 {
+    /// `key` must not be used for any other message
+    pub fn new(key: &[u8; KEY_SIZE]) -> Self {
+        Self {
+            mac: poly1305::Poly1305::new(key.into()),
+            block: [0; BLOCK_SIZE],
+            len: 0,
+        }
+    }
+}
+
+impl MinDigest for Poly1305 {
+    /// The 16 byte tag
+    type Output = [u8; BLOCK_SIZE];
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        let mut data = data.as_ref();
+        if self.len > 0 {
+            let take = (BLOCK_SIZE - self.len).min(data.len());
+            self.block[self.len..self.len + take].copy_from_slice(&data[..take]);
+            self.len += take;
+            data = &data[take..];
+            if self.len < BLOCK_SIZE {
+                return;
+            }
+            self.mac.update(&[self.block.into()]);
+            self.len = 0;
+        }
+        let mut blocks = data.chunks_exact(BLOCK_SIZE);
+        for block in &mut blocks {
+            self.mac.update(&[*poly1305::Block::from_slice(block)]);
+        }
+        let rest = blocks.remainder();
+        self.block[..rest.len()].copy_from_slice(rest);
+        self.len = rest.len();
+    }
+    fn finalize(self) -> Self::Output {
+        self.mac.compute_unpadded(&self.block[..self.len]).into()
+    }
+}
@@ -0,0 +1,7 @@
+use super::{MinDigest, MinDigestReset};
+delegate_digest_mindigest!(
+    ripemd::Ripemd128,
+    ripemd::Ripemd160,
+    ripemd::Ripemd256,
+    ripemd::Ripemd320
+);
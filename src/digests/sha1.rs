@@ -0,0 +1,2 @@
+use super::{MinDigest, MinDigestReset};
+delegate_digest_mindigest!(sha1::Sha1);
@@ -0,0 +1,56 @@
+//! SHA-1 with collision detection, the hardened SHA-1 used by Git.
+//!
+//! [`Sha1Cd`] detects the known collision attacks (SHAttered, Shambles) while hashing. By default
+//! it also mitigates them, hashing the near-collision blocks differently so the digest of a
+//! detected attack no longer collides, and reports the detection in
+//! [`Sha1CdOutput::collision_detected`].
+
+use super::MinDigest;
+
+pub use sha1collisiondetection::{Builder, Collision};
+
+/// Collision detecting SHA-1, see the [module docs](self)
+///
+/// Build one with non default settings from a configured [`Builder`]:
+/// `Sha1Cd::from(Builder::default().safe_hash(false).build())`
+#[derive(Debug, Clone, Default)]
+pub struct Sha1Cd(sha1collisiondetection::Sha1CD);
+
+impl Sha1Cd {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// The digest, or an error if a collision attack was detected
+    pub fn try_finalize(self) -> Result<[u8; 20], Collision> {
+        self.0.finalize_cd().map(Into::into)
+    }
+}
+
+impl From<sha1collisiondetection::Sha1CD> for Sha1Cd {
+    fn from(hasher: sha1collisiondetection::Sha1CD) -> Self {
+        Self(hasher)
+    }
+}
+
+/// The digest of a [`Sha1Cd`] and whether a collision attack was detected in the data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sha1CdOutput {
+    pub digest: [u8; 20],
+    pub collision_detected: bool,
+}
+
+impl MinDigest for Sha1Cd {
+    type Output = Sha1CdOutput;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        self.0.update(data)
+    }
+    fn finalize(mut self) -> Sha1CdOutput {
+        let mut digest = sha1collisiondetection::Output::default();
+        let collision_detected = self.0.finalize_into_dirty_cd(&mut digest).is_err();
+        Sha1CdOutput {
+            digest: digest.into(),
+            collision_detected,
+        }
+    }
+}
@@ -0,0 +1,9 @@
+use super::{MinDigest, MinDigestReset};
+delegate_digest_mindigest!(
+    sha2::Sha224,
+    sha2::Sha256,
+    sha2::Sha384,
+    sha2::Sha512,
+    sha2::Sha512_224,
+    sha2::Sha512_256
+);
@@ -0,0 +1,11 @@
+use super::{MinDigest, MinDigestReset};
+delegate_digest_mindigest!(
+    sha3::Sha3_224,
+    sha3::Sha3_256,
+    sha3::Sha3_384,
+    sha3::Sha3_512,
+    sha3::Keccak224,
+    sha3::Keccak256,
+    sha3::Keccak384,
+    sha3::Keccak512
+);
@@ -0,0 +1,6 @@
+use super::{MinDigest, MinDigestReset};
+use digest::generic_array::ArrayLength;
+// The output size defaults to the state size, i.e. Skein256 == Skein256<U32>
+delegate_digest_mindigest!(impl<N> skein::Skein256<N> where N: ArrayLength<u8> + 'static);
+delegate_digest_mindigest!(impl<N> skein::Skein512<N> where N: ArrayLength<u8> + 'static);
+delegate_digest_mindigest!(impl<N> skein::Skein1024<N> where N: ArrayLength<u8> + 'static);
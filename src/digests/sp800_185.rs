@@ -0,0 +1,256 @@
+//! SHA-3 derived functions from [NIST SP 800-185](https://doi.org/10.6028/NIST.SP.800-185)
+//!
+//! These are extendable output functions, the output length is picked when constructing them
+//! and [`MinDigest::finalize`] returns that many bytes.
+
+use super::MinDigest;
+use digest::{ExtendableOutput, Update};
+
+macro_rules! cshake {
+    ($name:ident, $core:ident) => {
+        #[derive(Clone)]
+        pub struct $name {
+            hasher: sha3::$name,
+            output_len: usize,
+        }
+
+        impl $name {
+            /// With an empty customization string this is equivalent to SHAKE
+            pub fn new(customization: &[u8], output_len: usize) -> Self {
+                Self::new_with_function_name(&[], customization, output_len)
+            }
+
+            /// `function_name` is reserved for functions defined by NIST, use
+            /// [`new`](Self::new) unless implementing one of those.
+            pub fn new_with_function_name(
+                function_name: &[u8],
+                customization: &[u8],
+                output_len: usize,
+            ) -> Self {
+                Self {
+                    hasher: sha3::$name::from_core(sha3::$core::new_with_function_name(
+                        function_name,
+                        customization,
+                    )),
+                    output_len,
+                }
+            }
+        }
+
+        impl MinDigest for $name {
+            type Output = Vec<u8>;
+            fn update(&mut self, data: impl AsRef<[u8]>) {
+                Update::update(&mut self.hasher, data.as_ref())
+            }
+            fn finalize(self) -> Self::Output {
+                let mut output = vec![0; self.output_len];
+                self.hasher.finalize_xof_into(&mut output);
+                output
+            }
+        }
+    };
+}
+
+cshake!(CShake128, CShake128Core);
+cshake!(CShake256, CShake256Core);
+
+fn encode(x: u64) -> Vec<u8> {
+    let bytes = x.to_be_bytes();
+    let zeros = bytes.iter().take_while(|b| **b == 0).count().min(7);
+    bytes[zeros..].to_vec()
+}
+
+fn left_encode(x: u64) -> Vec<u8> {
+    let mut encoded = encode(x);
+    encoded.insert(0, encoded.len() as u8);
+    encoded
+}
+
+fn right_encode(x: u64) -> Vec<u8> {
+    let mut encoded = encode(x);
+    encoded.push(encoded.len() as u8);
+    encoded
+}
+
+fn bits(bytes: usize) -> u64 {
+    bytes as u64 * 8
+}
+
+macro_rules! tuple_hash {
+    ($name:ident, $cshake:ident, $core:ident) => {
+        /// Hashes a tuple of byte strings such that the boundaries between them are part of
+        /// the hash.
+        ///
+        /// Each call to [`update_field`](Self::update_field) adds one element to the tuple.
+        /// Data written with [`MinDigest::update`] (e.g. through a [`WriteHasher`](crate::WriteHasher))
+        /// is appended to a single element which is closed by
+        /// [`finish_field`](Self::finish_field), `update_field` or finalizing. Since the
+        /// elements are length prefixed that element is buffered in memory until it's closed.
+        #[derive(Clone)]
+        pub struct $name {
+            hasher: sha3::$cshake,
+            output_len: usize,
+            field: Option<Vec<u8>>,
+        }
+
+        impl $name {
+            pub fn new(customization: &[u8], output_len: usize) -> Self {
+                Self {
+                    hasher: sha3::$cshake::from_core(sha3::$core::new_with_function_name(
+                        b"TupleHash",
+                        customization,
+                    )),
+                    output_len,
+                    field: None,
+                }
+            }
+
+            /// Add `field` as the next element of the tuple
+            pub fn update_field(&mut self, field: &[u8]) {
+                self.finish_field();
+                self.absorb_field(field);
+            }
+
+            /// Close the element being written with [`MinDigest::update`], if any
+            pub fn finish_field(&mut self) {
+                if let Some(field) = self.field.take() {
+                    self.absorb_field(&field);
+                }
+            }
+
+            fn absorb_field(&mut self, field: &[u8]) {
+                Update::update(&mut self.hasher, &left_encode(bits(field.len())));
+                Update::update(&mut self.hasher, field);
+            }
+        }
+
+        impl MinDigest for $name {
+            type Output = Vec<u8>;
+            fn update(&mut self, data: impl AsRef<[u8]>) {
+                self.field
+                    .get_or_insert_with(Vec::new)
+                    .extend_from_slice(data.as_ref())
+            }
+            fn finalize(mut self) -> Self::Output {
+                self.finish_field();
+                Update::update(&mut self.hasher, &right_encode(bits(self.output_len)));
+                let mut output = vec![0; self.output_len];
+                self.hasher.finalize_xof_into(&mut output);
+                output
+            }
+        }
+    };
+}
+
+tuple_hash!(TupleHash128, CShake128, CShake128Core);
+tuple_hash!(TupleHash256, CShake256, CShake256Core);
+
+macro_rules! parallel_hash {
+    ($name:ident, $cshake:ident, $core:ident, $shake:ident, $leaf_len:literal) => {
+        /// Splits the data into blocks of a fixed size which are hashed independently, with
+        /// the `rayon` feature the blocks in a single update are hashed in parallel.
+        #[derive(Clone)]
+        pub struct $name {
+            hasher: sha3::$cshake,
+            output_len: usize,
+            block_size: usize,
+            block: Vec<u8>,
+            blocks: u64,
+            #[cfg(feature = "rayon")]
+            parallel: bool,
+        }
+
+        impl $name {
+            /// # Panics
+            /// Panics if `block_size` is 0
+            pub fn new(block_size: usize, customization: &[u8], output_len: usize) -> Self {
+                assert!(block_size > 0, "block size must not be 0");
+                let mut hasher = sha3::$cshake::from_core(sha3::$core::new_with_function_name(
+                    b"ParallelHash",
+                    customization,
+                ));
+                Update::update(&mut hasher, &left_encode(block_size as u64));
+                Self {
+                    hasher,
+                    output_len,
+                    block_size,
+                    block: Vec::with_capacity(block_size),
+                    blocks: 0,
+                    #[cfg(feature = "rayon")]
+                    parallel: true,
+                }
+            }
+
+            /// Whether to hash blocks in parallel with rayon, on by default
+            #[cfg(feature = "rayon")]
+            pub fn with_parallel(mut self, parallel: bool) -> Self {
+                self.parallel = parallel;
+                self
+            }
+
+            fn leaf(block: &[u8]) -> [u8; $leaf_len] {
+                let mut shake = sha3::$shake::default();
+                Update::update(&mut shake, block);
+                let mut leaf = [0; $leaf_len];
+                shake.finalize_xof_into(&mut leaf);
+                leaf
+            }
+
+            /// `blocks` has to be a multiple of the block size
+            fn absorb_blocks(&mut self, blocks: &[u8]) {
+                #[cfg(feature = "rayon")]
+                if self.parallel && blocks.len() > self.block_size {
+                    use rayon::prelude::*;
+                    let leaves = blocks
+                        .par_chunks(self.block_size)
+                        .map(Self::leaf)
+                        .collect::<Vec<_>>();
+                    for leaf in leaves {
+                        Update::update(&mut self.hasher, &leaf);
+                    }
+                    self.blocks += (blocks.len() / self.block_size) as u64;
+                    return;
+                }
+                for block in blocks.chunks(self.block_size) {
+                    Update::update(&mut self.hasher, &Self::leaf(block));
+                    self.blocks += 1;
+                }
+            }
+        }
+
+        impl MinDigest for $name {
+            type Output = Vec<u8>;
+            fn update(&mut self, data: impl AsRef<[u8]>) {
+                let mut data = data.as_ref();
+                if !self.block.is_empty() {
+                    let take = (self.block_size - self.block.len()).min(data.len());
+                    self.block.extend_from_slice(&data[..take]);
+                    data = &data[take..];
+                    if self.block.len() == self.block_size {
+                        let block = core::mem::take(&mut self.block);
+                        self.absorb_blocks(&block);
+                        self.block = block;
+                        self.block.clear();
+                    }
+                }
+                let full = data.len() - data.len() % self.block_size;
+                self.absorb_blocks(&data[..full]);
+                self.block.extend_from_slice(&data[full..]);
+            }
+            fn finalize(mut self) -> Self::Output {
+                if !self.block.is_empty() {
+                    let block = core::mem::take(&mut self.block);
+                    self.absorb_blocks(&block);
+                }
+                Update::update(&mut self.hasher, &right_encode(self.blocks));
+                Update::update(&mut self.hasher, &right_encode(bits(self.output_len)));
+                let mut output = vec![0; self.output_len];
+                self.hasher.finalize_xof_into(&mut output);
+                output
+            }
+        }
+    };
+}
+
+parallel_hash!(ParallelHash128, CShake128, CShake128Core, Shake128, 32);
+parallel_hash!(ParallelHash256, CShake256, CShake256Core, Shake256, 64);
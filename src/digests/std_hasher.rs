@@ -0,0 +1,48 @@
+//! Using [`std::hash::Hasher`] implementations as a [`MinDigest`]
+
+use super::MinDigest;
+
+/// Any [`std::hash::Hasher`] as a [`MinDigest`], for cheap non-cryptographic keys (e.g. to dedup
+/// files) with hashers like SeaHash, FxHash, aHash or std's [`DefaultHasher`]
+///
+/// ```rust
+/// # #[cfg(feature = "stdio")] {
+/// use std::collections::hash_map::DefaultHasher;
+/// use std::hash::Hasher;
+/// use std::io::Write;
+/// use write_hasher::{MinDigest, StdHasher, WriteHasher};
+///
+/// let mut hasher = WriteHasher::<StdHasher<DefaultHasher>, _>::new(std::io::sink());
+/// hasher.write_all(b"hello world").unwrap();
+///
+/// let mut direct = DefaultHasher::new();
+/// direct.write(b"hello world");
+/// assert_eq!(hasher.finalize(), direct.finish());
+/// # }
+/// ```
+///
+/// `Hasher::write` isn't required to be prefix stable: a hasher may return a different value for
+/// `write(b"ab"); write(b"c")` than for `write(b"abc")`. Writers and readers pass data along in
+/// whatever pieces they get it in, so only use hashers which are stream oriented, like SipHash
+/// ([`DefaultHasher`]) or SeaHash. Hashers meant for `HashMap` keys, such as FxHash or aHash, mix
+/// every call separately and will give results depending on the buffer sizes in play.
+///
+/// [`DefaultHasher`]: std::collections::hash_map::DefaultHasher
+#[derive(Debug, Clone, Default)]
+pub struct StdHasher<H: std::hash::Hasher>(pub H);
+
+impl<H: std::hash::Hasher> MinDigest for StdHasher<H> {
+    type Output = u64;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        self.0.write(data.as_ref())
+    }
+    fn finalize(self) -> Self::Output {
+        self.0.finish()
+    }
+}
+
+impl<H: std::hash::Hasher> From<H> for StdHasher<H> {
+    fn from(hasher: H) -> Self {
+        Self(hasher)
+    }
+}
@@ -0,0 +1,65 @@
+//! Name-based UUIDs ([RFC 4122](https://www.rfc-editor.org/rfc/rfc4122#section-4.3) versions 3
+//! and 5) where the name is the streamed content.
+//!
+//! The namespace is hashed before the name, so it's given to the hasher's constructor and
+//! everything written afterwards is the name:
+//!
+//! ```rust
+//! use std::io::Write;
+//! use write_hasher::{uuids::UuidV5, MinDigest, WriteHasher};
+//! let mut hasher = WriteHasher::new_with_hasher(std::io::sink(), UuidV5::new(uuid::Uuid::NAMESPACE_URL));
+//! hasher.write_all(b"https://example.com/").unwrap();
+//! hasher.write_all(b"file.tar").unwrap();
+//! assert_eq!(
+//!     hasher.finalize().to_string(),
+//!     "5cafd50a-e36e-5c04-ae9c-e3b748d493cd"
+//! );
+//! ```
+
+use super::MinDigest;
+
+/// A version 5 (SHA-1) UUID of the namespace and the data
+#[derive(Clone)]
+pub struct UuidV5(sha1::Sha1);
+
+impl UuidV5 {
+    pub fn new(namespace: uuid::Uuid) -> Self {
+        let mut hasher = <sha1::Sha1 as digest::Digest>::new();
+        digest::Digest::update(&mut hasher, namespace.as_bytes());
+        Self(hasher)
+    }
+}
+
+impl MinDigest for UuidV5 {
+    type Output = uuid::Uuid;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        digest::Digest::update(&mut self.0, data)
+    }
+    fn finalize(self) -> uuid::Uuid {
+        let digest = digest::Digest::finalize(self.0);
+        let bytes = digest[..16].try_into().expect("SHA-1 is 20 bytes");
+        uuid::Builder::from_sha1_bytes(bytes).into_uuid()
+    }
+}
+
+/// A version 3 (MD5) UUID of the namespace and the data
+#[derive(Clone)]
+pub struct UuidV3(md5::Context);
+
+impl UuidV3 {
+    pub fn new(namespace: uuid::Uuid) -> Self {
+        let mut hasher = md5::Context::new();
+        hasher.consume(namespace.as_bytes());
+        Self(hasher)
+    }
+}
+
+impl MinDigest for UuidV3 {
+    type Output = uuid::Uuid;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        self.0.consume(data)
+    }
+    fn finalize(self) -> uuid::Uuid {
+        uuid::Builder::from_md5_bytes(self.0.compute().0).into_uuid()
+    }
+}
@@ -0,0 +1,72 @@
+//! Extendable output functions such as SHAKE128 / SHAKE256
+//!
+//! XOFs don't implement `Digest`, [`XofHasher`] wraps any of them. [`MinDigest::finalize`]
+//! returns as many bytes as it was constructed with, or the length can be picked when finalizing
+//! with [`WriteHasher::finalize_xof_into`](crate::WriteHasher::finalize_xof_into).
+
+use super::MinDigest;
+use digest::{ExtendableOutput, Update};
+
+/// An XOF with the length of the output [`MinDigest::finalize`] produces
+#[derive(Clone)]
+pub struct XofHasher<X> {
+    hasher: X,
+    output_len: usize,
+}
+
+impl<X: Default> XofHasher<X> {
+    pub fn new(output_len: usize) -> Self {
+        Self::new_with_xof(X::default(), output_len)
+    }
+}
+
+impl<X> XofHasher<X> {
+    pub fn new_with_xof(hasher: X, output_len: usize) -> Self {
+        Self { hasher, output_len }
+    }
+}
+
+impl<X: Update + ExtendableOutput> MinDigest for XofHasher<X> {
+    type Output = Vec<u8>;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        self.hasher.update(data.as_ref())
+    }
+    fn finalize(self) -> Self::Output {
+        let mut output = vec![0; self.output_len];
+        self.hasher.finalize_xof_into(&mut output);
+        output
+    }
+}
+
+/// SHAKE128, [`XofHasher::new`] takes the output length
+pub type Shake128 = XofHasher<sha3::Shake128>;
+/// SHAKE256, [`XofHasher::new`] takes the output length
+pub type Shake256 = XofHasher<sha3::Shake256>;
+
+impl<X: Default, T> crate::WriteHasher<XofHasher<X>, T> {
+    /// Hash with `X`, [`finalize`](MinDigest::finalize) returning `output_len` bytes
+    pub fn new_with_output_len(inner: T, output_len: usize) -> Self {
+        Self::new_with_hasher(inner, XofHasher::new(output_len))
+    }
+}
+
+impl<X: ExtendableOutput, T> crate::WriteHasher<XofHasher<X>, T> {
+    /// Finalize into a reader of the extendable output, ignoring the configured output length
+    pub fn finalize_xof(self) -> X::Reader {
+        self.hasher.hasher.finalize_xof()
+    }
+
+    /// Fill `out` with the extendable output, ignoring the configured output length
+    ///
+    /// ```rust
+    /// use write_hasher::{xof::Shake256, WriteHasher};
+    /// let mut src = std::fs::File::open("LICENSE").unwrap();
+    /// let mut hasher = WriteHasher::<Shake256, _>::new_with_output_len(std::io::sink(), 64);
+    /// std::io::copy(&mut src, &mut hasher).unwrap();
+    /// let mut out = [0; 64];
+    /// hasher.finalize_xof_into(&mut out);
+    /// ```
+    pub fn finalize_xof_into(self, out: &mut [u8]) {
+        self.hasher.hasher.finalize_xof_into(out)
+    }
+}
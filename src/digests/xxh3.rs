@@ -0,0 +1,50 @@
+//! XXH3 with either a 64 or 128 bit output.
+//!
+//! [`xxhash_rust::xxh3::Xxh3`] can produce both, so it's wrapped in [`Xxh3_64`] and [`Xxh3_128`]
+//! to make the [`MinDigest::Output`] unambiguous.
+
+use super::MinDigest;
+
+/// Size of the custom secret accepted by `with_secret`
+pub const SECRET_SIZE: usize = 192;
+
+macro_rules! xxh3_newtype {
+    ($name:ident, $output:ty, $digest:ident) => {
+        #[derive(Clone, Default)]
+        pub struct $name(xxhash_rust::xxh3::Xxh3);
+
+        impl $name {
+            pub fn new() -> Self {
+                Default::default()
+            }
+
+            pub fn with_seed(seed: u64) -> Self {
+                Self(xxhash_rust::xxh3::Xxh3::with_seed(seed))
+            }
+
+            pub fn with_secret(secret: [u8; SECRET_SIZE]) -> Self {
+                Self(xxhash_rust::xxh3::Xxh3::with_secret(secret))
+            }
+        }
+
+        impl MinDigest for $name {
+            type Output = $output;
+            fn update(&mut self, data: impl AsRef<[u8]>) {
+                self.0.update(data.as_ref())
+            }
+            fn finalize(self) -> Self::Output {
+                self.0.$digest()
+            }
+        }
+
+        impl<T> crate::WriteHasher<$name, T> {
+            #[doc = concat!("Hash with [`", stringify!($name), "::with_seed`]")]
+            pub fn new_with_seed(inner: T, seed: u64) -> Self {
+                Self::new_with_hasher(inner, $name::with_seed(seed))
+            }
+        }
+    };
+}
+
+xxh3_newtype!(Xxh3_64, u64, digest);
+xxh3_newtype!(Xxh3_128, u128, digest128);
@@ -0,0 +1,33 @@
+//! XXH64, xxHash's original 64 bit variant
+
+use super::MinDigest;
+
+#[derive(Clone, Default)]
+pub struct Xxh64(xxhash_rust::xxh64::Xxh64);
+
+impl Xxh64 {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self(xxhash_rust::xxh64::Xxh64::new(seed))
+    }
+}
+
+impl MinDigest for Xxh64 {
+    type Output = u64;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        self.0.update(data.as_ref())
+    }
+    fn finalize(self) -> Self::Output {
+        self.0.digest()
+    }
+}
+
+impl<T> crate::WriteHasher<Xxh64, T> {
+    /// Hash with XXH64 seeded with `seed`
+    pub fn new_with_seed(inner: T, seed: u64) -> Self {
+        Self::new_with_hasher(inner, Xxh64::with_seed(seed))
+    }
+}
@@ -0,0 +1,280 @@
+//! Hashing a whole directory tree into a single digest, e.g. for cache keys
+//!
+//! The framing is part of the digest and won't change. Entries are sorted by their path relative
+//! to the root (components joined with `/`, compared bytewise) and for each of them the hasher is
+//! fed
+//!
+//! - `f` for a regular file or `l` for a symlink
+//! - the relative path followed by a NUL byte
+//! - the length of the contents as a big-endian `u64`
+//! - the contents, the bytes of the file or the target of the symlink as stored in it
+//!
+//! Directories are only walked, an empty directory doesn't change the digest. Other file types
+//! (sockets, FIFOs, devices) are skipped.
+
+use std::ffi::OsStr;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::MinDigest;
+#[cfg(feature = "stdio")]
+use crate::WriteHasher;
+
+/// What [`hash_dir_with_options`] (and its async counterpart) include in the digest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HashDirOptions {
+    /// Include files and directories whose name starts with a `.`
+    pub include_hidden: bool,
+    /// Hash what symlinks point to, as if it was in their place, rather than their target. A
+    /// dangling symlink or one pointing back up the tree is an error.
+    pub follow_symlinks: bool,
+}
+
+impl Default for HashDirOptions {
+    fn default() -> Self {
+        Self {
+            include_hidden: true,
+            follow_symlinks: false,
+        }
+    }
+}
+
+enum Kind {
+    File,
+    Symlink(Vec<u8>),
+}
+
+struct Entry {
+    relative: Vec<u8>,
+    path: PathBuf,
+    kind: Kind,
+}
+
+fn join(parent: &[u8], name: &OsStr) -> Vec<u8> {
+    let mut relative = parent.to_vec();
+    if !relative.is_empty() {
+        relative.push(b'/');
+    }
+    relative.extend_from_slice(name.as_encoded_bytes());
+    relative
+}
+
+fn is_hidden(name: &OsStr) -> bool {
+    name.as_encoded_bytes().starts_with(b".")
+}
+
+fn header(tag: u8, relative: &[u8], len: u64) -> Vec<u8> {
+    let mut header = Vec::with_capacity(relative.len() + 10);
+    header.push(tag);
+    header.extend_from_slice(relative);
+    header.push(0);
+    header.extend_from_slice(&len.to_be_bytes());
+    header
+}
+
+fn symlink_loop(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("{} links to a directory containing it", path.display()),
+    )
+}
+
+fn changed(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        format!("{} got shorter while it was hashed", path.display()),
+    )
+}
+
+const BUFFER_SIZE: usize = 64 * 1024;
+
+/// Hash the directory tree at `path` with [`HashDirOptions::default`]
+///
+/// ```rust,no_run
+/// # #[cfg(feature = "digest")] {
+/// let key = write_hasher::hash_dir::<sha2::Sha256>("src")?;
+/// println!("{key:x}");
+/// # }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[cfg(feature = "stdio")]
+pub fn hash_dir<D: MinDigest + Default>(path: impl AsRef<Path>) -> io::Result<D::Output> {
+    hash_dir_with_options::<D>(path, &HashDirOptions::default())
+}
+
+/// Hash the directory tree at `path` as described in the [module docs](self)
+#[cfg(feature = "stdio")]
+pub fn hash_dir_with_options<D: MinDigest + Default>(
+    path: impl AsRef<Path>,
+    options: &HashDirOptions,
+) -> io::Result<D::Output> {
+    use std::io::{Read, Write};
+    let mut hasher = WriteHasher::<D, _>::new(io::sink());
+    for entry in walk(path.as_ref(), options)? {
+        match entry.kind {
+            Kind::Symlink(target) => {
+                hasher.write_all(&header(b'l', &entry.relative, target.len() as u64))?;
+                hasher.write_all(&target)?;
+            }
+            Kind::File => {
+                let file = std::fs::File::open(&entry.path)?;
+                let len = file.metadata()?.len();
+                hasher.write_all(&header(b'f', &entry.relative, len))?;
+                let mut file = io::BufReader::with_capacity(BUFFER_SIZE, file.take(len));
+                if io::copy(&mut file, &mut hasher)? != len {
+                    return Err(changed(&entry.path));
+                }
+            }
+        }
+    }
+    Ok(hasher.finalize())
+}
+
+#[cfg(feature = "stdio")]
+fn walk(root: &Path, options: &HashDirOptions) -> io::Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+    let ancestors = match options.follow_symlinks {
+        true => vec![root.canonicalize()?],
+        false => Vec::new(),
+    };
+    let mut dirs = vec![(root.to_path_buf(), Vec::new(), ancestors)];
+    while let Some((dir, parent, ancestors)) = dirs.pop() {
+        for child in std::fs::read_dir(&dir)? {
+            let child = child?;
+            let name = child.file_name();
+            if !options.include_hidden && is_hidden(&name) {
+                continue;
+            }
+            let path = child.path();
+            let relative = join(&parent, &name);
+            let mut file_type = child.file_type()?;
+            if file_type.is_symlink() {
+                if !options.follow_symlinks {
+                    let target = std::fs::read_link(&path)?.into_os_string();
+                    let kind = Kind::Symlink(target.into_encoded_bytes());
+                    entries.push(Entry {
+                        relative,
+                        path,
+                        kind,
+                    });
+                    continue;
+                }
+                file_type = std::fs::metadata(&path)?.file_type();
+            }
+            if file_type.is_dir() {
+                let mut ancestors = ancestors.clone();
+                if options.follow_symlinks {
+                    let canonical = path.canonicalize()?;
+                    if ancestors.contains(&canonical) {
+                        return Err(symlink_loop(&path));
+                    }
+                    ancestors.push(canonical);
+                }
+                dirs.push((path, relative, ancestors));
+            } else if file_type.is_file() {
+                entries.push(Entry {
+                    relative,
+                    path,
+                    kind: Kind::File,
+                });
+            }
+        }
+    }
+    entries.sort_unstable_by(|a, b| a.relative.cmp(&b.relative));
+    Ok(entries)
+}
+
+/// Async version of [`hash_dir`]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+pub async fn hash_dir_async<D: MinDigest + Default>(
+    path: impl AsRef<Path>,
+) -> io::Result<D::Output> {
+    hash_dir_with_options_async::<D>(path, &HashDirOptions::default()).await
+}
+
+/// Async version of [`hash_dir_with_options`]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+pub async fn hash_dir_with_options_async<D: MinDigest + Default>(
+    path: impl AsRef<Path>,
+    options: &HashDirOptions,
+) -> io::Result<D::Output> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    let mut hasher = crate::WriteHasher::<D, _>::new(tokio::io::sink());
+    for entry in walk_async(path.as_ref(), options).await? {
+        match entry.kind {
+            Kind::Symlink(target) => {
+                let header = header(b'l', &entry.relative, target.len() as u64);
+                hasher.write_all(&header).await?;
+                hasher.write_all(&target).await?;
+            }
+            Kind::File => {
+                let file = tokio::fs::File::open(&entry.path).await?;
+                let len = file.metadata().await?.len();
+                hasher
+                    .write_all(&header(b'f', &entry.relative, len))
+                    .await?;
+                let mut file = tokio::io::BufReader::with_capacity(BUFFER_SIZE, file.take(len));
+                if tokio::io::copy_buf(&mut file, &mut hasher).await? != len {
+                    return Err(changed(&entry.path));
+                }
+            }
+        }
+    }
+    Ok(MinDigest::finalize(hasher))
+}
+
+#[cfg(feature = "tokio")]
+async fn walk_async(root: &Path, options: &HashDirOptions) -> io::Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+    let ancestors = match options.follow_symlinks {
+        true => vec![tokio::fs::canonicalize(root).await?],
+        false => Vec::new(),
+    };
+    let mut dirs = vec![(root.to_path_buf(), Vec::new(), ancestors)];
+    while let Some((dir, parent, ancestors)) = dirs.pop() {
+        let mut children = tokio::fs::read_dir(&dir).await?;
+        while let Some(child) = children.next_entry().await? {
+            let name = child.file_name();
+            if !options.include_hidden && is_hidden(&name) {
+                continue;
+            }
+            let path = child.path();
+            let relative = join(&parent, &name);
+            let mut file_type = child.file_type().await?;
+            if file_type.is_symlink() {
+                if !options.follow_symlinks {
+                    let target = tokio::fs::read_link(&path).await?.into_os_string();
+                    let kind = Kind::Symlink(target.into_encoded_bytes());
+                    entries.push(Entry {
+                        relative,
+                        path,
+                        kind,
+                    });
+                    continue;
+                }
+                file_type = tokio::fs::metadata(&path).await?.file_type();
+            }
+            if file_type.is_dir() {
+                let mut ancestors = ancestors.clone();
+                if options.follow_symlinks {
+                    let canonical = tokio::fs::canonicalize(&path).await?;
+                    if ancestors.contains(&canonical) {
+                        return Err(symlink_loop(&path));
+                    }
+                    ancestors.push(canonical);
+                }
+                dirs.push((path, relative, ancestors));
+            } else if file_type.is_file() {
+                entries.push(Entry {
+                    relative,
+                    path,
+                    kind: Kind::File,
+                });
+            }
+        }
+    }
+    entries.sort_unstable_by(|a, b| a.relative.cmp(&b.relative));
+    Ok(entries)
+}
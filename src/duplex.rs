@@ -0,0 +1,222 @@
+//! [`DuplexHasher`], hashing what is read from and what is written to a stream separately
+
+#[cfg(any(feature = "futures", feature = "tokio"))]
+use core::{pin::Pin, task::Poll};
+
+#[cfg(any(feature = "stdio", feature = "tokio", feature = "futures"))]
+use crate::writer::update_vectored;
+use crate::MinDigest;
+
+/// A wrapper over a Read + Write / AsyncRead + AsyncWrite stream that hashes the data read from
+/// it with `DR` and the data written to it with `DW`, e.g. for both sides of a protocol
+/// transcript
+///
+/// ```
+/// # #[cfg(all(feature = "stdio", feature = "crc32c"))] {
+/// # use std::io::{Read, Write};
+/// # use write_hasher::{DuplexHasher, crc32c::Crc32c};
+/// let mut stream = DuplexHasher::<Crc32c, Crc32c, _>::new(std::io::Cursor::new(b"reply".to_vec()));
+/// let mut reply = Vec::new();
+/// stream.read_to_end(&mut reply).unwrap();
+/// stream.write_all(b"request").unwrap();
+/// let (read, written) = stream.finalize_both();
+/// assert_eq!(read, write_hasher::hash_bytes::<Crc32c>(b"reply"));
+/// assert_eq!(written, write_hasher::hash_bytes::<Crc32c>(b"request"));
+/// # }
+/// ```
+#[cfg_attr(any(feature = "futures", feature = "tokio"), pin_project::pin_project)]
+#[derive(Default)]
+pub struct DuplexHasher<DR, DW, T> {
+    read_hasher: DR,
+    write_hasher: DW,
+    #[cfg_attr(any(feature = "futures", feature = "tokio"), pin)]
+    inner: T,
+}
+
+impl<DR, DW, T> DuplexHasher<DR, DW, T> {
+    pub fn new_with_hashers(inner: T, read_hasher: DR, write_hasher: DW) -> Self {
+        Self {
+            read_hasher,
+            write_hasher,
+            inner,
+        }
+    }
+
+    pub fn new(inner: T) -> Self
+    where
+        DR: Default,
+        DW: Default,
+    {
+        Self::new_with_hashers(inner, DR::default(), DW::default())
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Reading from / writing to the inner stream directly bypasses the hashers.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<DR: MinDigest, DW: MinDigest, T> DuplexHasher<DR, DW, T> {
+    /// Finalize the hashes of the data read and the data written so far
+    pub fn finalize_both(self) -> (DR::Output, DW::Output) {
+        (self.read_hasher.finalize(), self.write_hasher.finalize())
+    }
+}
+
+#[cfg(feature = "stdio")]
+impl<DR: MinDigest, DW, T: std::io::Read> std::io::Read for DuplexHasher<DR, DW, T> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read_hasher.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+#[cfg(feature = "stdio")]
+impl<DR, DW: MinDigest, T: std::io::Write> std::io::Write for DuplexHasher<DR, DW, T> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.write_hasher.update(&buf[..n]);
+        Ok(n)
+    }
+    fn write_vectored(&mut self, bufs: &[std::io::IoSlice<'_>]) -> std::io::Result<usize> {
+        let n = self.inner.write_vectored(bufs)?;
+        update_vectored(&mut self.write_hasher, bufs, n);
+        Ok(n)
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+impl<DR: MinDigest, DW, T: tokio::io::AsyncRead> tokio::io::AsyncRead for DuplexHasher<DR, DW, T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        let ah = self.project();
+        let before = buf.filled().len();
+        let r = ah.inner.poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = r {
+            ah.read_hasher.update(&buf.filled()[before..]);
+        }
+        r
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+impl<DR, DW: MinDigest, T: tokio::io::AsyncWrite> tokio::io::AsyncWrite
+    for DuplexHasher<DR, DW, T>
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        let ah = self.project();
+        let r = ah.inner.poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = r {
+            ah.write_hasher.update(&buf[..n]);
+        }
+        r
+    }
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        bufs: &[std::io::IoSlice<'_>],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        let ah = self.project();
+        let r = ah.inner.poll_write_vectored(cx, bufs);
+        if let Poll::Ready(Ok(n)) = r {
+            update_vectored(ah.write_hasher, bufs, n);
+        }
+        r
+    }
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+    fn poll_shutdown(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        self.project().inner.poll_shutdown(cx)
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<DR: MinDigest, DW, T: futures::io::AsyncRead> futures::io::AsyncRead
+    for DuplexHasher<DR, DW, T>
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut [u8],
+    ) -> std::task::Poll<futures::io::Result<usize>> {
+        let ah = self.project();
+        let r = ah.inner.poll_read(cx, buf);
+        if let Poll::Ready(Ok(n)) = r {
+            ah.read_hasher.update(&buf[..n]);
+        }
+        r
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<DR, DW: MinDigest, T: futures::io::AsyncWrite> futures::io::AsyncWrite
+    for DuplexHasher<DR, DW, T>
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<futures::io::Result<usize>> {
+        let ah = self.project();
+        let r = ah.inner.poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = r {
+            ah.write_hasher.update(&buf[..n]);
+        }
+        r
+    }
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        bufs: &[std::io::IoSlice<'_>],
+    ) -> std::task::Poll<futures::io::Result<usize>> {
+        let ah = self.project();
+        let r = ah.inner.poll_write_vectored(cx, bufs);
+        if let Poll::Ready(Ok(n)) = r {
+            update_vectored(ah.write_hasher, bufs, n);
+        }
+        r
+    }
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<futures::io::Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+    fn poll_close(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<futures::io::Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
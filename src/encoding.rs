@@ -0,0 +1,431 @@
+//! Viewing digest outputs as bytes and encoding them as text
+
+use std::borrow::Cow;
+
+use crate::helpers::decode_hex;
+
+use crate::{MinDigest, ReadHasher, WriteHasher};
+
+/// A digest output that can be viewed as bytes, so generic code over [`MinDigest`](crate::MinDigest)
+/// can use the output of any of them (see [`MinDigest::finalize_bytes`](crate::MinDigest::finalize_bytes)).
+///
+/// The integer checksums (e.g. crc32's `u32`) are viewed as their big-endian (network order)
+/// bytes, which is how they are conventionally printed, e.g. `0x1234u32` is `[0, 0, 0x12, 0x34]`.
+pub trait OutputBytes {
+    fn to_bytes(&self) -> Cow<'_, [u8]>;
+}
+
+#[cfg(any(
+    feature = "digest",
+    feature = "sha2",
+    feature = "sha1",
+    feature = "md2",
+    feature = "md4",
+    feature = "md5",
+    feature = "md-5",
+    feature = "blake2",
+    feature = "ascon",
+    feature = "skein",
+    feature = "ripemd",
+    feature = "sha3",
+    feature = "hkdf",
+    feature = "mac"
+))]
+impl<N: digest::generic_array::ArrayLength<u8>> OutputBytes
+    for digest::generic_array::GenericArray<u8, N>
+{
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self.as_slice())
+    }
+}
+
+impl<const N: usize> OutputBytes for [u8; N] {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl OutputBytes for Vec<u8> {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl OutputBytes for Box<[u8]> {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self)
+    }
+}
+
+macro_rules! output_bytes_be {
+    ($($int:ty),*) => {
+        $(
+            impl OutputBytes for $int {
+                fn to_bytes(&self) -> Cow<'_, [u8]> {
+                    Cow::Owned(self.to_be_bytes().to_vec())
+                }
+            }
+        )*
+    };
+}
+
+output_bytes_be!(u8, u16, u32, u64, u128);
+
+#[cfg(feature = "md5")]
+impl OutputBytes for md5::Digest {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(&self.0)
+    }
+}
+
+#[cfg(feature = "blake3")]
+impl OutputBytes for blake3::Hash {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self.as_bytes())
+    }
+}
+
+/// The inverse of [`OutputBytes`], rebuilding a digest output from the bytes it's viewed as,
+/// e.g. to read back a stored digest. `None` if `bytes` has the wrong length.
+pub trait FromOutputBytes: OutputBytes + Sized {
+    fn from_output_bytes(bytes: &[u8]) -> Option<Self>;
+}
+
+#[cfg(any(
+    feature = "digest",
+    feature = "sha2",
+    feature = "sha1",
+    feature = "md2",
+    feature = "md4",
+    feature = "md5",
+    feature = "md-5",
+    feature = "blake2",
+    feature = "ascon",
+    feature = "skein",
+    feature = "ripemd",
+    feature = "sha3",
+    feature = "hkdf",
+    feature = "mac"
+))]
+impl<N: digest::generic_array::ArrayLength<u8>> FromOutputBytes
+    for digest::generic_array::GenericArray<u8, N>
+{
+    fn from_output_bytes(bytes: &[u8]) -> Option<Self> {
+        (bytes.len() == N::USIZE).then(|| Self::clone_from_slice(bytes))
+    }
+}
+
+impl<const N: usize> FromOutputBytes for [u8; N] {
+    fn from_output_bytes(bytes: &[u8]) -> Option<Self> {
+        bytes.try_into().ok()
+    }
+}
+
+impl FromOutputBytes for Vec<u8> {
+    fn from_output_bytes(bytes: &[u8]) -> Option<Self> {
+        Some(bytes.to_vec())
+    }
+}
+
+impl FromOutputBytes for Box<[u8]> {
+    fn from_output_bytes(bytes: &[u8]) -> Option<Self> {
+        Some(bytes.into())
+    }
+}
+
+macro_rules! from_output_bytes_be {
+    ($($int:ty),*) => {
+        $(
+            impl FromOutputBytes for $int {
+                fn from_output_bytes(bytes: &[u8]) -> Option<Self> {
+                    bytes.try_into().ok().map(<$int>::from_be_bytes)
+                }
+            }
+        )*
+    };
+}
+
+from_output_bytes_be!(u8, u16, u32, u64, u128);
+
+#[cfg(feature = "md5")]
+impl FromOutputBytes for md5::Digest {
+    fn from_output_bytes(bytes: &[u8]) -> Option<Self> {
+        bytes.try_into().ok().map(md5::Digest)
+    }
+}
+
+#[cfg(feature = "blake3")]
+impl FromOutputBytes for blake3::Hash {
+    fn from_output_bytes(bytes: &[u8]) -> Option<Self> {
+        bytes.try_into().ok().map(blake3::Hash::from_bytes)
+    }
+}
+
+/// Compare a digest output with a hex string
+pub trait HexEq {
+    /// Whether `hex` (in any case, surrounding whitespace is ignored) encodes the same bytes.
+    ///
+    /// Malformed hex, including odd lengths and whitespace within the digits, is never equal.
+    /// The bytes are compared in constant time with the `subtle` feature.
+    fn eq_hex(&self, hex: &str) -> bool;
+}
+
+impl<O: OutputBytes + ?Sized> HexEq for O {
+    fn eq_hex(&self, hex: &str) -> bool {
+        match decode_hex(hex.trim()) {
+            Some(other) => bytes_eq(&self.to_bytes(), &other),
+            None => false,
+        }
+    }
+}
+
+/// Compare two digests, in constant time with the `subtle` feature (lengths are not secret)
+pub(crate) fn bytes_eq(a: &[u8], b: &[u8]) -> bool {
+    #[cfg(feature = "subtle")]
+    {
+        subtle::ConstantTimeEq::ct_eq(a, b).into()
+    }
+    #[cfg(not(feature = "subtle"))]
+    {
+        a == b
+    }
+}
+
+#[cfg(feature = "base32")]
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+#[cfg(feature = "base32")]
+const CROCKFORD_ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+#[cfg(feature = "base32")]
+fn encode_base32_with(bytes: &[u8], alphabet: &[u8; 32], padding: bool) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    for chunk in bytes.chunks(5) {
+        let mut block = [0u8; 5];
+        block[..chunk.len()].copy_from_slice(chunk);
+        let bits = block.iter().fold(0u64, |bits, &b| bits << 8 | b as u64);
+        let chars = (chunk.len() * 8).div_ceil(5);
+        for i in 0..chars {
+            encoded.push(alphabet[(bits >> (35 - i * 5)) as usize & 0x1F] as char);
+        }
+        if padding {
+            encoded.extend(core::iter::repeat_n('=', 8 - chars));
+        }
+    }
+    encoded
+}
+
+#[cfg(feature = "base32")]
+fn decode_base32_with(symbols: impl Iterator<Item = Option<u8>>) -> Option<Vec<u8>> {
+    let mut decoded = Vec::new();
+    let (mut bits, mut len) = (0u32, 0u32);
+    for symbol in symbols {
+        bits = bits << 5 | symbol? as u32;
+        len += 5;
+        if len >= 8 {
+            len -= 8;
+            decoded.push((bits >> len) as u8);
+            bits &= (1 << len) - 1;
+        }
+    }
+    // leftover bits must be zero padding of the last byte
+    (len < 5 && bits == 0).then_some(decoded)
+}
+
+/// Encode `bytes` with the [RFC 4648](https://www.rfc-editor.org/rfc/rfc4648#section-6) base32
+/// alphabet, optionally padded with `=` to a multiple of 8 characters
+#[cfg_attr(docsrs, doc(cfg(feature = "base32")))]
+#[cfg(feature = "base32")]
+pub fn encode_base32(bytes: &[u8], padding: bool) -> String {
+    encode_base32_with(bytes, BASE32_ALPHABET, padding)
+}
+
+/// Decode RFC 4648 base32, with or without padding, returns `None` if it isn't valid base32
+#[cfg_attr(docsrs, doc(cfg(feature = "base32")))]
+#[cfg(feature = "base32")]
+pub fn decode_base32(encoded: &str) -> Option<Vec<u8>> {
+    let unpadded = encoded.trim_end_matches('=');
+    if encoded.len() != unpadded.len() && !encoded.len().is_multiple_of(8) {
+        return None;
+    }
+    decode_base32_with(unpadded.bytes().map(|c| {
+        BASE32_ALPHABET
+            .iter()
+            .position(|&a| a == c)
+            .map(|i| i as u8)
+    }))
+}
+
+/// Encode `bytes` with [Crockford's base32](https://www.crockford.com/base32.html) alphabet,
+/// which has no padding and avoids the easily confused `I`, `L`, `O` and `U`
+#[cfg_attr(docsrs, doc(cfg(feature = "base32")))]
+#[cfg(feature = "base32")]
+pub fn encode_base32_crockford(bytes: &[u8]) -> String {
+    encode_base32_with(bytes, CROCKFORD_ALPHABET, false)
+}
+
+/// Decode Crockford's base32, returns `None` if it isn't valid.
+///
+/// Decoding is case insensitive, `I` / `L` are read as `1`, `O` as `0` and hyphens are ignored.
+#[cfg_attr(docsrs, doc(cfg(feature = "base32")))]
+#[cfg(feature = "base32")]
+pub fn decode_base32_crockford(encoded: &str) -> Option<Vec<u8>> {
+    decode_base32_with(encoded.bytes().filter(|&c| c != b'-').map(|c| {
+        let c = match c.to_ascii_uppercase() {
+            b'I' | b'L' => b'1',
+            b'O' => b'0',
+            c => c,
+        };
+        CROCKFORD_ALPHABET
+            .iter()
+            .position(|&a| a == c)
+            .map(|i| i as u8)
+    }))
+}
+
+macro_rules! finalize_base32 {
+    ($hasher:ident) => {
+        #[cfg_attr(docsrs, doc(cfg(feature = "base32")))]
+        #[cfg(feature = "base32")]
+        impl<D: MinDigest, T> $hasher<D, T>
+        where
+            D::Output: OutputBytes,
+        {
+            /// Finalize the digest as padded RFC 4648 base32
+            pub fn finalize_base32(self) -> String {
+                encode_base32(&self.hasher.finalize_bytes(), true)
+            }
+
+            /// Finalize the digest as RFC 4648 base32 without padding
+            pub fn finalize_base32_unpadded(self) -> String {
+                encode_base32(&self.hasher.finalize_bytes(), false)
+            }
+
+            /// Finalize the digest as Crockford's base32
+            pub fn finalize_base32_crockford(self) -> String {
+                encode_base32_crockford(&self.hasher.finalize_bytes())
+            }
+        }
+    };
+}
+
+finalize_base32!(WriteHasher);
+finalize_base32!(ReadHasher);
+
+#[cfg(feature = "base64")]
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+#[cfg(feature = "base64")]
+const BASE64_URL_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+#[cfg(feature = "base64")]
+fn encode_base64_with(bytes: &[u8], alphabet: &[u8; 64], padding: bool) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let mut block = [0u8; 3];
+        block[..chunk.len()].copy_from_slice(chunk);
+        let bits = block.iter().fold(0u32, |bits, &b| bits << 8 | b as u32);
+        let chars = (chunk.len() * 8).div_ceil(6);
+        for i in 0..chars {
+            encoded.push(alphabet[(bits >> (18 - i * 6)) as usize & 0x3F] as char);
+        }
+        if padding {
+            encoded.extend(core::iter::repeat_n('=', 4 - chars));
+        }
+    }
+    encoded
+}
+
+#[cfg(feature = "base64")]
+fn decode_base64_with(encoded: &str, alphabet: &[u8; 64]) -> Option<Vec<u8>> {
+    let unpadded = encoded.trim_end_matches('=');
+    if encoded.len() != unpadded.len() && !encoded.len().is_multiple_of(4) {
+        return None;
+    }
+    // a single character left over can't encode a whole byte
+    if unpadded.len() % 4 == 1 {
+        return None;
+    }
+    let mut decoded = Vec::with_capacity(unpadded.len() * 3 / 4);
+    let (mut bits, mut len) = (0u32, 0u32);
+    for c in unpadded.bytes() {
+        let symbol = alphabet.iter().position(|&a| a == c)?;
+        bits = bits << 6 | symbol as u32;
+        len += 6;
+        if len >= 8 {
+            len -= 8;
+            decoded.push((bits >> len) as u8);
+            bits &= (1 << len) - 1;
+        }
+    }
+    // leftover bits must be zero padding of the last byte
+    (bits == 0).then_some(decoded)
+}
+
+/// Encode `bytes` with the standard [RFC 4648](https://www.rfc-editor.org/rfc/rfc4648#section-4)
+/// base64 alphabet, optionally padded with `=` to a multiple of 4 characters
+#[cfg_attr(docsrs, doc(cfg(feature = "base64")))]
+#[cfg(feature = "base64")]
+pub fn encode_base64(bytes: &[u8], padding: bool) -> String {
+    encode_base64_with(bytes, BASE64_ALPHABET, padding)
+}
+
+/// Decode standard base64, with or without padding, returns `None` if it isn't valid base64
+#[cfg_attr(docsrs, doc(cfg(feature = "base64")))]
+#[cfg(feature = "base64")]
+pub fn decode_base64(encoded: &str) -> Option<Vec<u8>> {
+    decode_base64_with(encoded, BASE64_ALPHABET)
+}
+
+/// Encode `bytes` with the URL and filename safe
+/// [RFC 4648](https://www.rfc-editor.org/rfc/rfc4648#section-5) base64 alphabet (`-` and `_`
+/// instead of `+` and `/`), optionally padded
+#[cfg_attr(docsrs, doc(cfg(feature = "base64")))]
+#[cfg(feature = "base64")]
+pub fn encode_base64_url(bytes: &[u8], padding: bool) -> String {
+    encode_base64_with(bytes, BASE64_URL_ALPHABET, padding)
+}
+
+/// Decode URL safe base64, with or without padding, returns `None` if it isn't valid
+#[cfg_attr(docsrs, doc(cfg(feature = "base64")))]
+#[cfg(feature = "base64")]
+pub fn decode_base64_url(encoded: &str) -> Option<Vec<u8>> {
+    decode_base64_with(encoded, BASE64_URL_ALPHABET)
+}
+
+macro_rules! finalize_text {
+    ($hasher:ident) => {
+        impl<D: MinDigest, T> $hasher<D, T>
+        where
+            D::Output: OutputBytes,
+        {
+            /// Finalize the digest as lower case hex of its [`OutputBytes`], so the integer
+            /// checksums are printed big-endian like the other digests
+            pub fn finalize_hex(self) -> String {
+                crate::helpers::encode_hex(&self.hasher.finalize_bytes())
+            }
+
+            /// Finalize the digest as upper case hex
+            pub fn finalize_hex_upper(self) -> String {
+                self.finalize_hex().to_ascii_uppercase()
+            }
+
+            /// Finalize the digest as padded standard base64
+            #[cfg_attr(docsrs, doc(cfg(feature = "base64")))]
+            #[cfg(feature = "base64")]
+            pub fn finalize_base64(self) -> String {
+                encode_base64(&self.hasher.finalize_bytes(), true)
+            }
+
+            /// Finalize the digest as URL safe base64 without padding
+            #[cfg_attr(docsrs, doc(cfg(feature = "base64")))]
+            #[cfg(feature = "base64")]
+            pub fn finalize_base64_url(self) -> String {
+                encode_base64_url(&self.hasher.finalize_bytes(), false)
+            }
+        }
+    };
+}
+
+finalize_text!(WriteHasher);
+finalize_text!(ReadHasher);
@@ -0,0 +1,85 @@
+//! [`HashWriterError`], the error of the verification and helper APIs
+
+use crate::helpers::encode_hex;
+
+/// Why verifying or processing hashed data failed
+///
+/// It converts into an [`std::io::Error`] (with the matching [`kind`](Self::kind) and itself as
+/// the inner error) for contexts that can only return those, e.g. `Read` implementations.
+#[derive(Debug)]
+pub enum HashWriterError {
+    /// The underlying reader / writer failed
+    Io(std::io::Error),
+    /// The digest of the data doesn't match the expected digest
+    Mismatch { expected: Vec<u8>, actual: Vec<u8> },
+    /// The number of bytes hashed doesn't match the expected length
+    Length { expected: u64, actual: u64 },
+    /// An expected digest (or the input it was taken from) couldn't be parsed
+    Parse(String),
+    /// The digest doesn't cover a single linear pass over the data since the stream was seeked,
+    /// see [`SeekPolicy`](crate::SeekPolicy) and
+    /// [`WriteHasher::is_digest_linear`](crate::WriteHasher::is_digest_linear)
+    Invalidated,
+    /// The operation was cancelled through a [`CancelHandle`](crate::CancelHandle)
+    Cancelled,
+}
+
+impl HashWriterError {
+    /// The [`ErrorKind`](std::io::ErrorKind) of the [`std::io::Error`] this converts into
+    pub fn kind(&self) -> std::io::ErrorKind {
+        match self {
+            Self::Io(err) => err.kind(),
+            Self::Mismatch { .. } | Self::Length { .. } | Self::Invalidated => {
+                std::io::ErrorKind::InvalidData
+            }
+            Self::Parse(_) => std::io::ErrorKind::InvalidInput,
+            Self::Cancelled => std::io::ErrorKind::Other,
+        }
+    }
+}
+
+impl core::fmt::Display for HashWriterError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Io(err) => err.fmt(f),
+            Self::Mismatch { expected, actual } => write!(
+                f,
+                "digest mismatch: expected {}, got {}",
+                encode_hex(expected),
+                encode_hex(actual)
+            ),
+            Self::Length { expected, actual } => write!(
+                f,
+                "length mismatch: expected {} bytes, got {}",
+                expected, actual
+            ),
+            Self::Parse(what) => write!(f, "parse error: {}", what),
+            Self::Invalidated => f.write_str("digest was invalidated by seeking"),
+            Self::Cancelled => f.write_str("operation was cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for HashWriterError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for HashWriterError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<HashWriterError> for std::io::Error {
+    fn from(err: HashWriterError) -> Self {
+        match err {
+            HashWriterError::Io(err) => err,
+            err => std::io::Error::new(err.kind(), err),
+        }
+    }
+}
@@ -0,0 +1,291 @@
+//! Gzip members verified against (or emitted with) the CRC-32 and length in their trailer.
+//!
+//! [`GzipVerifyingReader`] inflates the members itself with flate2's raw deflate and checks the
+//! trailer of every member with its own CRC-32 of the output instead of trusting the decoder.
+//! [`GzipHashingEncoder`] records the trailer it emitted, so it can be stored next to the file.
+
+use std::io::{self, BufRead, Read, Write};
+
+use flate2::{Compression, Decompress, FlushDecompress, Status};
+
+use crate::HashWriterError;
+
+const MAGIC: [u8; 2] = [0x1f, 0x8b];
+const DEFLATE: u8 = 8;
+
+const FHCRC: u8 = 1 << 1;
+const FEXTRA: u8 = 1 << 2;
+const FNAME: u8 = 1 << 3;
+const FCOMMENT: u8 = 1 << 4;
+const RESERVED: u8 = 0b1110_0000;
+
+/// The trailer of a gzip member
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GzipTrailer {
+    /// CRC-32 of the uncompressed data
+    pub crc32: u32,
+    /// Length of the uncompressed data modulo 2^32
+    pub isize: u32,
+}
+
+impl GzipTrailer {
+    fn from_bytes(bytes: [u8; 8]) -> Self {
+        let [c0, c1, c2, c3, s0, s1, s2, s3] = bytes;
+        Self {
+            crc32: u32::from_le_bytes([c0, c1, c2, c3]),
+            isize: u32::from_le_bytes([s0, s1, s2, s3]),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Header,
+    Body,
+    Done,
+}
+
+/// Inflates a (possibly multi-member) gzip stream, verifying the trailer of every member
+///
+/// A mismatching CRC-32 or length, a malformed header and corrupted compressed data all fail
+/// with [`ErrorKind::InvalidData`](io::ErrorKind::InvalidData), a truncated stream with
+/// [`ErrorKind::UnexpectedEof`](io::ErrorKind::UnexpectedEof). Data after the last member has
+/// to be another member.
+///
+/// ```
+/// use std::io::{Read, Write};
+/// use write_hasher::gzip::{GzipHashingEncoder, GzipVerifyingReader};
+///
+/// let mut encoder = GzipHashingEncoder::new(Vec::new(), Default::default());
+/// encoder.write_all(b"hello world").unwrap();
+/// let (compressed, trailer) = encoder.finish().unwrap();
+/// assert_eq!(trailer.isize, 11);
+///
+/// let mut reader = GzipVerifyingReader::new(&compressed[..]);
+/// let mut out = String::new();
+/// reader.read_to_string(&mut out).unwrap();
+/// assert_eq!(out, "hello world");
+/// assert_eq!(reader.trailers(), [trailer]);
+/// ```
+#[derive(Debug)]
+pub struct GzipVerifyingReader<R> {
+    inner: R,
+    decompress: Decompress,
+    crc: crc32fast::Hasher,
+    size: u32,
+    state: State,
+    trailers: Vec<GzipTrailer>,
+}
+
+impl<R: BufRead> GzipVerifyingReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            decompress: Decompress::new(false),
+            crc: crc32fast::Hasher::new(),
+            size: 0,
+            state: State::Header,
+            trailers: Vec::new(),
+        }
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// The trailers of the members verified so far
+    pub fn trailers(&self) -> &[GzipTrailer] {
+        &self.trailers
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn start_member(&mut self) -> io::Result<bool> {
+        if self.inner.fill_buf()?.is_empty() {
+            if self.trailers.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "empty gzip stream",
+                ));
+            }
+            return Ok(false);
+        }
+        read_header(&mut self.inner)?;
+        self.decompress.reset(false);
+        self.crc = crc32fast::Hasher::new();
+        self.size = 0;
+        Ok(true)
+    }
+
+    fn finish_member(&mut self) -> io::Result<()> {
+        let mut bytes = [0; 8];
+        self.inner.read_exact(&mut bytes)?;
+        let expected = GzipTrailer::from_bytes(bytes);
+        let actual = GzipTrailer {
+            crc32: core::mem::take(&mut self.crc).finalize(),
+            isize: self.size,
+        };
+        if expected.crc32 != actual.crc32 {
+            return Err(HashWriterError::Mismatch {
+                expected: expected.crc32.to_be_bytes().to_vec(),
+                actual: actual.crc32.to_be_bytes().to_vec(),
+            }
+            .into());
+        }
+        if expected.isize != actual.isize {
+            return Err(HashWriterError::Length {
+                expected: expected.isize.into(),
+                actual: actual.isize.into(),
+            }
+            .into());
+        }
+        self.trailers.push(actual);
+        Ok(())
+    }
+}
+
+impl<R: BufRead> Read for GzipVerifyingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            match self.state {
+                State::Done => return Ok(0),
+                State::Header => {
+                    self.state = if self.start_member()? {
+                        State::Body
+                    } else {
+                        State::Done
+                    };
+                }
+                State::Body => {
+                    let input = self.inner.fill_buf()?;
+                    let eof = input.is_empty();
+                    let (in_before, out_before) =
+                        (self.decompress.total_in(), self.decompress.total_out());
+                    let flush = if eof {
+                        FlushDecompress::Finish
+                    } else {
+                        FlushDecompress::None
+                    };
+                    let status = self
+                        .decompress
+                        .decompress(input, buf, flush)
+                        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+                    let consumed = (self.decompress.total_in() - in_before) as usize;
+                    let produced = (self.decompress.total_out() - out_before) as usize;
+                    self.inner.consume(consumed);
+                    self.crc.update(&buf[..produced]);
+                    self.size = self.size.wrapping_add(produced as u32);
+
+                    if status == Status::StreamEnd {
+                        self.finish_member()?;
+                        self.state = State::Header;
+                    } else if eof && produced == 0 {
+                        return Err(io::Error::new(
+                            io::ErrorKind::UnexpectedEof,
+                            "truncated gzip member",
+                        ));
+                    }
+                    if produced > 0 {
+                        return Ok(produced);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn read_header(reader: &mut impl BufRead) -> io::Result<()> {
+    let mut fixed = [0; 10];
+    reader.read_exact(&mut fixed)?;
+    let invalid = |what| io::Error::new(io::ErrorKind::InvalidData, what);
+    if fixed[..2] != MAGIC {
+        return Err(invalid("not a gzip member"));
+    }
+    if fixed[2] != DEFLATE {
+        return Err(invalid("unsupported gzip compression method"));
+    }
+    let flags = fixed[3];
+    if flags & RESERVED != 0 {
+        return Err(invalid("reserved gzip header flags set"));
+    }
+    if flags & FEXTRA != 0 {
+        let mut len = [0; 2];
+        reader.read_exact(&mut len)?;
+        let len = u16::from_le_bytes(len) as u64;
+        let skipped = io::copy(&mut reader.take(len), &mut io::sink())?;
+        if skipped != len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+    }
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            let mut field = Vec::new();
+            reader.read_until(0, &mut field)?;
+            if field.last() != Some(&0) {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+        }
+    }
+    if flags & FHCRC != 0 {
+        // the header CRC is optional and rarely written, it isn't checked
+        reader.read_exact(&mut [0; 2])?;
+    }
+    Ok(())
+}
+
+/// A gzip encoder that records the trailer it emits
+///
+/// The CRC-32 and length are computed from the data written to it, independently of the
+/// encoder.
+#[derive(Debug)]
+pub struct GzipHashingEncoder<W: Write> {
+    encoder: flate2::write::GzEncoder<W>,
+    crc: crc32fast::Hasher,
+    size: u32,
+}
+
+impl<W: Write> GzipHashingEncoder<W> {
+    pub fn new(inner: W, level: Compression) -> Self {
+        Self {
+            encoder: flate2::write::GzEncoder::new(inner, level),
+            crc: crc32fast::Hasher::new(),
+            size: 0,
+        }
+    }
+
+    pub fn get_ref(&self) -> &W {
+        self.encoder.get_ref()
+    }
+
+    /// The trailer of the data written so far
+    pub fn trailer(&self) -> GzipTrailer {
+        GzipTrailer {
+            crc32: self.crc.clone().finalize(),
+            isize: self.size,
+        }
+    }
+
+    /// Writes the trailer, returning the inner writer and the trailer written
+    pub fn finish(self) -> io::Result<(W, GzipTrailer)> {
+        let trailer = self.trailer();
+        Ok((self.encoder.finish()?, trailer))
+    }
+}
+
+impl<W: Write> Write for GzipHashingEncoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.encoder.write(buf)?;
+        self.crc.update(&buf[..n]);
+        self.size = self.size.wrapping_add(n as u32);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.encoder.flush()
+    }
+}
@@ -0,0 +1,834 @@
+//! Helpers for hashing whole files and the hex encoding used across the crate
+
+#[cfg(any(feature = "stdio", feature = "tokio"))]
+use crate::MinDigest;
+#[cfg(feature = "stdio")]
+use crate::WriteHasher;
+
+/// How [`hash_file_with_options`] (and its async counterpart) read the file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HashFileOptions {
+    /// Tell the kernel the file will be read sequentially so it reads ahead more aggressively
+    pub advise_sequential: bool,
+    /// Ask the kernel to drop the file from the page cache once it's hashed, so scanning a lot
+    /// of data doesn't evict everything else from the cache
+    pub drop_cache_after: bool,
+    /// Size of the buffer the file is read with, clamped to at least 1 byte
+    pub buffer_size: usize,
+}
+
+impl Default for HashFileOptions {
+    fn default() -> Self {
+        Self {
+            advise_sequential: true,
+            drop_cache_after: false,
+            buffer_size: 64 * 1024,
+        }
+    }
+}
+
+/// Access pattern hints, these are only acted on where `posix_fadvise` is available and are
+/// no-ops everywhere else
+#[cfg(any(feature = "stdio", feature = "tokio"))]
+#[derive(Debug, Clone, Copy)]
+enum Advice {
+    Sequential,
+    DontNeed,
+}
+
+#[cfg(any(feature = "stdio", feature = "tokio"))]
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+fn advise(file: &impl std::os::fd::AsRawFd, advice: Advice) {
+    let advice = match advice {
+        Advice::Sequential => libc::POSIX_FADV_SEQUENTIAL,
+        Advice::DontNeed => libc::POSIX_FADV_DONTNEED,
+    };
+    // Only a hint, failing (e.g. for pipes) doesn't affect the digest
+    // SAFETY: posix_fadvise doesn't touch memory, an invalid fd only results in an error
+    let _ = unsafe { libc::posix_fadvise(file.as_raw_fd(), 0, 0, advice) };
+}
+
+#[cfg(any(feature = "stdio", feature = "tokio"))]
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd")))]
+fn advise<F>(_file: &F, _advice: Advice) {}
+
+/// Hash everything `reader` yields until EOF
+///
+/// ```rust
+/// # #[cfg(feature = "digest")] {
+/// let digest = write_hasher::hash_reader::<sha2::Sha256>(&b"abc"[..])?;
+/// assert_eq!(format!("{digest:x}").len(), 64);
+/// # }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[cfg(feature = "stdio")]
+pub fn hash_reader<D: MinDigest + Default>(
+    reader: impl std::io::Read,
+) -> std::io::Result<D::Output> {
+    hash_reader_with_hasher(D::default(), reader)
+}
+
+/// [`hash_reader`] with a hasher that can't be (or shouldn't be) made with [`Default`], e.g. a
+/// keyed or seeded one
+#[cfg(feature = "stdio")]
+pub fn hash_reader_with_hasher<D: MinDigest>(
+    hasher: D,
+    reader: impl std::io::Read,
+) -> std::io::Result<D::Output> {
+    let mut reader =
+        std::io::BufReader::with_capacity(HashFileOptions::default().buffer_size, reader);
+    let mut hasher = WriteHasher::new_with_hasher(std::io::sink(), hasher);
+    std::io::copy(&mut reader, &mut hasher)?;
+    Ok(hasher.finalize())
+}
+
+/// Hash the file at `path` with [`HashFileOptions::default`]
+///
+/// ```rust,no_run
+/// # #[cfg(feature = "digest")] {
+/// let digest = write_hasher::hash_file::<sha2::Sha256>("Cargo.toml")?;
+/// println!("{digest:x}  Cargo.toml");
+/// # }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[cfg(feature = "stdio")]
+pub fn hash_file<D: MinDigest + Default>(
+    path: impl AsRef<std::path::Path>,
+) -> std::io::Result<D::Output> {
+    hash_file_with_options::<D>(path, &HashFileOptions::default())
+}
+
+/// [`hash_file`] with a hasher that can't be (or shouldn't be) made with [`Default`]
+#[cfg(feature = "stdio")]
+pub fn hash_file_with_hasher<D: MinDigest>(
+    hasher: D,
+    path: impl AsRef<std::path::Path>,
+) -> std::io::Result<D::Output> {
+    hash_open_file(
+        hasher,
+        &mut std::fs::File::open(path)?,
+        &HashFileOptions::default(),
+    )
+}
+
+/// Hash the file at `path`, with access pattern hints to the kernel as set in `options`
+#[cfg(feature = "stdio")]
+pub fn hash_file_with_options<D: MinDigest + Default>(
+    path: impl AsRef<std::path::Path>,
+    options: &HashFileOptions,
+) -> std::io::Result<D::Output> {
+    hash_open_file(D::default(), &mut std::fs::File::open(path)?, options)
+}
+
+#[cfg(feature = "stdio")]
+fn hash_open_file<D: MinDigest>(
+    hasher: D,
+    file: &mut std::fs::File,
+    options: &HashFileOptions,
+) -> std::io::Result<D::Output> {
+    let mut buf = vec![0; options.buffer_size.max(1)];
+    hash_open_file_with_buf(hasher, file, options, &mut buf)
+}
+
+/// [`hash_open_file`] reading into `buf` rather than a buffer of `options.buffer_size`
+#[cfg(feature = "stdio")]
+fn hash_open_file_with_buf<D: MinDigest>(
+    hasher: D,
+    file: &mut std::fs::File,
+    options: &HashFileOptions,
+    buf: &mut [u8],
+) -> std::io::Result<D::Output> {
+    use std::io::{Read, Write};
+    if options.advise_sequential {
+        advise(file, Advice::Sequential);
+    }
+    let mut hasher = WriteHasher::new_with_hasher(std::io::sink(), hasher);
+    loop {
+        match file.read(buf) {
+            Ok(0) => break,
+            Ok(n) => hasher.write_all(&buf[..n])?,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    if options.drop_cache_after {
+        advise(file, Advice::DontNeed);
+    }
+    Ok(hasher.finalize())
+}
+
+/// How much of the file [`hash_file_mmap`] maps at a time, bounding the address space it takes
+#[cfg(all(feature = "stdio", feature = "memmap2"))]
+const MMAP_WINDOW: usize = 64 << 20;
+
+/// Hash the file at `path` by mapping it into memory with [`memmap2`], a window of up to 64 MiB
+/// at a time, and passing each window to the hasher in one update, which skips copying through a
+/// read buffer.
+///
+/// Empty files, anything that isn't a regular file and files (or parts of them) that can't be
+/// mapped are read as with [`hash_file`] instead.
+///
+/// Truncating the file while it's mapped makes reading the pages past its new end raise
+/// `SIGBUS`, killing the process, so only use this on files nothing else truncates. Other
+/// concurrent writes can show up in the digest, as with reading the file.
+///
+/// ```rust,no_run
+/// # #[cfg(feature = "digest")] {
+/// let digest = write_hasher::hash_file_mmap::<sha2::Sha256>("large.iso")?;
+/// # }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "memmap2")))]
+#[cfg(all(feature = "stdio", feature = "memmap2"))]
+pub fn hash_file_mmap<D: MinDigest + Default>(
+    path: impl AsRef<std::path::Path>,
+) -> std::io::Result<D::Output> {
+    hash_file_mmap_windowed::<D>(path.as_ref(), MMAP_WINDOW)
+}
+
+#[cfg(all(feature = "stdio", feature = "memmap2"))]
+pub(crate) fn hash_file_mmap_windowed<D: MinDigest + Default>(
+    path: &std::path::Path,
+    window: usize,
+) -> std::io::Result<D::Output> {
+    use std::io::{Seek, SeekFrom};
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = D::default();
+    let metadata = file.metadata()?;
+    let len = match metadata.is_file() {
+        true => metadata.len(),
+        false => 0,
+    };
+    let mut offset = 0;
+    while offset < len {
+        let size = (len - offset).min(window as u64) as usize;
+        let mut options = memmap2::MmapOptions::new();
+        options.offset(offset).len(size);
+        // SAFETY: the map is only read here while the file is open, see the truncation caveat
+        // of hash_file_mmap
+        let Ok(map) = (unsafe { options.map(&file) }) else {
+            break;
+        };
+        hasher.update(&map[..]);
+        offset += size as u64;
+    }
+    if offset == len && len > 0 {
+        return Ok(hasher.finalize());
+    }
+    file.seek(SeekFrom::Start(offset))?;
+    hash_open_file(hasher, &mut file, &HashFileOptions::default())
+}
+
+/// Hash many files on the rayon pool, returning each path with its digest (or the error hashing
+/// it) in the order the paths came in. Every rayon job reuses one read buffer across the files it
+/// hashes.
+///
+/// ```rust,no_run
+/// # #[cfg(feature = "digest")] {
+/// use std::path::PathBuf;
+/// let paths = vec![PathBuf::from("Cargo.toml"), PathBuf::from("README.md")];
+/// for (path, digest) in write_hasher::hash_files::<sha2::Sha256>(paths) {
+///     println!("{:x}  {}", digest?, path.display());
+/// }
+/// # }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+#[cfg(all(feature = "stdio", feature = "rayon"))]
+pub fn hash_files<D>(
+    paths: impl rayon::iter::IntoParallelIterator<Item = std::path::PathBuf>,
+) -> Vec<(std::path::PathBuf, std::io::Result<D::Output>)>
+where
+    D: MinDigest + Default + Send,
+    D::Output: Send,
+{
+    use rayon::iter::ParallelIterator;
+    let options = HashFileOptions::default();
+    paths
+        .into_par_iter()
+        .map_init(
+            || vec![0; options.buffer_size],
+            |buf, path| {
+                let output = std::fs::File::open(&path).and_then(|mut file| {
+                    hash_open_file_with_buf(D::default(), &mut file, &options, buf)
+                });
+                (path, output)
+            },
+        )
+        .collect()
+}
+
+/// Whether [`hash_file_cached`] could use the digest cached with the file
+#[cfg_attr(docsrs, doc(cfg(all(feature = "xattr", unix))))]
+#[cfg(all(feature = "stdio", feature = "xattr", unix))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheStatus {
+    /// The cached digest was still valid, the file wasn't read
+    Hit,
+    /// The file was hashed and the digest cached (unless it changed while hashing it)
+    Miss,
+    /// The file was hashed, its filesystem doesn't support extended attributes
+    Unsupported,
+}
+
+/// How [`hash_file_cached`] names its cache and reads the file
+#[cfg_attr(docsrs, doc(cfg(all(feature = "xattr", unix))))]
+#[cfg(all(feature = "stdio", feature = "xattr", unix))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheOptions<'a> {
+    /// Names the attribute the digest is cached in, `user.write_hasher.<algorithm>`. Anything
+    /// that changes the digest (the algorithm, a key, an output length) needs its own name.
+    pub algorithm: &'a str,
+    /// How the file is read on a miss
+    pub hash: HashFileOptions,
+}
+
+#[cfg(all(feature = "stdio", feature = "xattr", unix))]
+impl<'a> CacheOptions<'a> {
+    pub fn new(algorithm: &'a str) -> Self {
+        Self {
+            algorithm,
+            hash: HashFileOptions::default(),
+        }
+    }
+}
+
+/// Hash the file at `path` like [`hash_file_with_options`], caching the digest in the
+/// `user.write_hasher.<algorithm>` extended attribute of the file.
+///
+/// The cached digest is used while the file's size, modification time (to the nanosecond) and
+/// inode number are the ones it was cached with, otherwise the file is hashed and the attribute
+/// rewritten. A digest isn't cached if the file changes while it's hashed. On filesystems
+/// without extended attributes the file is hashed every time and [`CacheStatus::Unsupported`]
+/// returned, if the attribute can't be written for other reasons (e.g. permissions) the digest
+/// is just not cached.
+///
+/// The change time (ctime) is not part of the stamp. Writing the attribute changes it, so the
+/// stamp can't hold the ctime the file ends up with, and rewriting the attribute to catch up
+/// changes it again (filesystems with fine grained timestamps give every change after a `stat`
+/// a new ctime). So like every cache keyed by modification times this trusts them: a write that
+/// restores the previous size and modification time, e.g. with `touch -d`, goes unnoticed and
+/// the stale digest is returned. Use [`hash_file_with_options`] for files that can be changed
+/// that way.
+#[cfg_attr(docsrs, doc(cfg(all(feature = "xattr", unix))))]
+#[cfg(all(feature = "stdio", feature = "xattr", unix))]
+pub fn hash_file_cached<D: MinDigest + Default>(
+    path: impl AsRef<std::path::Path>,
+    options: &CacheOptions<'_>,
+) -> std::io::Result<(D::Output, CacheStatus)>
+where
+    D::Output: crate::FromOutputBytes,
+{
+    use crate::{FromOutputBytes, OutputBytes};
+    use xattr::FileExt;
+
+    let name = format!("user.write_hasher.{}", options.algorithm);
+    let mut file = std::fs::File::open(path)?;
+    let stamp = CacheStamp::of(&file.metadata()?);
+    let unsupported = |e: &std::io::Error| matches!(e.raw_os_error(), Some(code) if code == libc::ENOTSUP || code == libc::EOPNOTSUPP);
+
+    if !xattr::SUPPORTED_PLATFORM {
+        let output = hash_open_file(D::default(), &mut file, &options.hash)?;
+        return Ok((output, CacheStatus::Unsupported));
+    }
+    match file.get_xattr(&name) {
+        Ok(Some(value)) => {
+            let cached = stamp
+                .strip_from(&value)
+                .and_then(|hex| decode_hex(std::str::from_utf8(hex).ok()?))
+                .and_then(|bytes| D::Output::from_output_bytes(&bytes));
+            if let Some(output) = cached {
+                return Ok((output, CacheStatus::Hit));
+            }
+        }
+        Ok(None) => {}
+        Err(e) if unsupported(&e) => {
+            let output = hash_open_file(D::default(), &mut file, &options.hash)?;
+            return Ok((output, CacheStatus::Unsupported));
+        }
+        Err(e) => return Err(e),
+    }
+
+    let output = hash_open_file(D::default(), &mut file, &options.hash)?;
+    if CacheStamp::of(&file.metadata()?) != stamp {
+        return Ok((output, CacheStatus::Miss));
+    }
+    let value = format!("{} {}", stamp, encode_hex(&output.to_bytes()));
+    match file.set_xattr(&name, value.as_bytes()) {
+        Err(e) if unsupported(&e) => Ok((output, CacheStatus::Unsupported)),
+        _ => Ok((output, CacheStatus::Miss)),
+    }
+}
+
+/// What a cached digest is valid for, stored in front of it as `<size> <secs>.<nanos> <inode>`
+#[cfg(all(feature = "stdio", feature = "xattr", unix))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CacheStamp {
+    size: u64,
+    mtime: i64,
+    mtime_nsec: i64,
+    ino: u64,
+}
+
+#[cfg(all(feature = "stdio", feature = "xattr", unix))]
+impl CacheStamp {
+    fn of(metadata: &std::fs::Metadata) -> Self {
+        use std::os::unix::fs::MetadataExt;
+        Self {
+            size: metadata.size(),
+            mtime: metadata.mtime(),
+            mtime_nsec: metadata.mtime_nsec(),
+            ino: metadata.ino(),
+        }
+    }
+
+    /// The rest of `value` after this stamp and a space, if it starts with this stamp
+    fn strip_from<'v>(&self, value: &'v [u8]) -> Option<&'v [u8]> {
+        value
+            .strip_prefix(self.to_string().as_bytes())?
+            .strip_prefix(b" ")
+    }
+}
+
+#[cfg(all(feature = "stdio", feature = "xattr", unix))]
+impl core::fmt::Display for CacheStamp {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{} {}.{:09} {}",
+            self.size, self.mtime, self.mtime_nsec, self.ino
+        )
+    }
+}
+
+/// Hash the file at `path` like [`hash_file`], but skip reading the holes of sparse files.
+///
+/// Data extents are found with `lseek`'s `SEEK_DATA` / `SEEK_HOLE` and read, holes are hashed as
+/// the zeros they read as without touching the disk, so the digest is the same as the one of a
+/// full read. Falls back to reading everything where the filesystem doesn't support those.
+#[cfg_attr(docsrs, doc(cfg(target_os = "linux")))]
+#[cfg(all(feature = "stdio", target_os = "linux"))]
+pub fn hash_file_sparse<D: MinDigest + Default>(
+    path: impl AsRef<std::path::Path>,
+) -> std::io::Result<D::Output> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = D::default();
+    hash_extents(&mut file, &mut hasher)?;
+    Ok(hasher.finalize())
+}
+
+/// Hash `file` from the start skipping its holes, returns the number of bytes actually read
+#[cfg(all(feature = "stdio", target_os = "linux"))]
+pub(crate) fn hash_extents<D: MinDigest>(
+    file: &mut std::fs::File,
+    hasher: &mut D,
+) -> std::io::Result<u64> {
+    use std::io::{Read, Seek};
+    use std::os::fd::AsRawFd;
+    static ZEROS: [u8; 64 * 1024] = [0; 64 * 1024];
+
+    let len = file.metadata()?.len();
+    let fd = file.as_raw_fd();
+    let seek = |offset: u64, whence| {
+        // SAFETY: lseek doesn't touch memory, an invalid fd only results in an error
+        match unsafe { libc::lseek(fd, offset as libc::off_t, whence) } {
+            -1 => Err(std::io::Error::last_os_error()),
+            offset => Ok(offset as u64),
+        }
+    };
+    let mut read = 0;
+    let mut pos = 0;
+    let mut buf = vec![0; 64 * 1024];
+    while pos < len {
+        let (data, hole) = match seek(pos, libc::SEEK_DATA) {
+            Ok(data) => (data.min(len), seek(data, libc::SEEK_HOLE)?.min(len)),
+            // only holes left
+            Err(e) if e.raw_os_error() == Some(libc::ENXIO) => (len, len),
+            // not supported, read the rest as data
+            Err(e) if e.raw_os_error() == Some(libc::EINVAL) => (pos, len),
+            Err(e) => return Err(e),
+        };
+        let mut zeros = data - pos;
+        while zeros > 0 {
+            let n = zeros.min(ZEROS.len() as u64) as usize;
+            hasher.update(&ZEROS[..n]);
+            zeros -= n as u64;
+        }
+        file.seek(std::io::SeekFrom::Start(data))?;
+        let mut extent = (&mut *file).take(hole - data);
+        loop {
+            match extent.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => {
+                    hasher.update(&buf[..n]);
+                    read += n as u64;
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        if extent.limit() > 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "the file was truncated while hashing it",
+            ));
+        }
+        pos = hole;
+    }
+    Ok(read)
+}
+
+/// Async version of [`hash_reader`], reading through a buffer of
+/// [`HashFileOptions::default`]'s size rather than [`tokio::io::copy`]'s 8 KiB
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+pub async fn hash_reader_async<D: MinDigest + Default>(
+    reader: impl tokio::io::AsyncRead + Unpin,
+) -> std::io::Result<D::Output> {
+    hash_reader_with_hasher_async(D::default(), reader).await
+}
+
+/// Async version of [`hash_reader_with_hasher`]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+pub async fn hash_reader_with_hasher_async<D: MinDigest>(
+    hasher: D,
+    reader: impl tokio::io::AsyncRead + Unpin,
+) -> std::io::Result<D::Output> {
+    let mut reader =
+        tokio::io::BufReader::with_capacity(HashFileOptions::default().buffer_size, reader);
+    let mut hasher = crate::WriteHasher::new_with_hasher(tokio::io::sink(), hasher);
+    tokio::io::copy_buf(&mut reader, &mut hasher).await?;
+    Ok(crate::MinDigest::finalize(hasher))
+}
+
+/// Async version of [`hash_file`]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+pub async fn hash_file_async<D: MinDigest + Default>(
+    path: impl AsRef<std::path::Path>,
+) -> std::io::Result<D::Output> {
+    hash_file_with_options_async::<D>(path, &HashFileOptions::default()).await
+}
+
+/// Async version of [`hash_file_with_hasher`]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+pub async fn hash_file_with_hasher_async<D: MinDigest>(
+    hasher: D,
+    path: impl AsRef<std::path::Path>,
+) -> std::io::Result<D::Output> {
+    hash_open_file_async(hasher, path, &HashFileOptions::default()).await
+}
+
+/// Async version of [`hash_file_with_options`]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+pub async fn hash_file_with_options_async<D: MinDigest + Default>(
+    path: impl AsRef<std::path::Path>,
+    options: &HashFileOptions,
+) -> std::io::Result<D::Output> {
+    hash_open_file_async(D::default(), path, options).await
+}
+
+#[cfg(feature = "tokio")]
+async fn hash_open_file_async<D: MinDigest>(
+    hasher: D,
+    path: impl AsRef<std::path::Path>,
+    options: &HashFileOptions,
+) -> std::io::Result<D::Output> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    let mut file = tokio::fs::File::open(path).await?;
+    if options.advise_sequential {
+        advise(&file, Advice::Sequential);
+    }
+    let mut hasher = crate::WriteHasher::new_with_hasher(tokio::io::sink(), hasher);
+    let mut buf = vec![0; options.buffer_size.max(1)];
+    loop {
+        match file.read(&mut buf).await {
+            Ok(0) => break,
+            Ok(n) => hasher.write_all(&buf[..n]).await?,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    if options.drop_cache_after {
+        advise(&file, Advice::DontNeed);
+    }
+    Ok(crate::MinDigest::finalize(hasher))
+}
+
+/// Check whether two streams have the same content by hashing each of them, so neither has to be
+/// buffered or read in lockstep with the other.
+///
+/// If both `lengths` are known up front and differ this returns `false` without reading.
+///
+/// Equal digests are taken as equal content. For a cryptographic hash the chance of a false
+/// positive is negligible, for a checksum like CRC-32 inputs can be crafted (or happen) to
+/// collide, so use one of those only where that's acceptable.
+#[cfg(feature = "stdio")]
+pub fn readers_equal_by_hash<D: MinDigest + Default>(
+    mut a: impl std::io::Read,
+    mut b: impl std::io::Read,
+    lengths: Option<(u64, u64)>,
+) -> std::io::Result<bool>
+where
+    D::Output: PartialEq,
+{
+    if matches!(lengths, Some((a, b)) if a != b) {
+        return Ok(false);
+    }
+    let mut a_hasher = WriteHasher::<D, _>::new(std::io::sink());
+    let mut b_hasher = WriteHasher::<D, _>::new(std::io::sink());
+    let a_len = std::io::copy(&mut a, &mut a_hasher)?;
+    let b_len = std::io::copy(&mut b, &mut b_hasher)?;
+    Ok(a_len == b_len && a_hasher.finalize() == b_hasher.finalize())
+}
+
+/// Async version of [`readers_equal_by_hash`], hashing both readers concurrently
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+pub async fn readers_equal_by_hash_async<D: MinDigest + Default>(
+    a: impl tokio::io::AsyncRead + std::marker::Unpin,
+    b: impl tokio::io::AsyncRead + std::marker::Unpin,
+    lengths: Option<(u64, u64)>,
+) -> std::io::Result<bool>
+where
+    D::Output: PartialEq,
+{
+    use core::future::Future;
+    if matches!(lengths, Some((a, b)) if a != b) {
+        return Ok(false);
+    }
+    async fn hash<D: MinDigest + Default>(
+        mut reader: impl tokio::io::AsyncRead + std::marker::Unpin,
+    ) -> std::io::Result<(u64, D::Output)> {
+        let mut hasher = crate::WriteHasher::<D, _>::new(tokio::io::sink());
+        let len = tokio::io::copy(&mut reader, &mut hasher).await?;
+        Ok((len, crate::MinDigest::finalize(hasher)))
+    }
+    let mut a = core::pin::pin!(hash::<D>(a));
+    let mut b = core::pin::pin!(hash::<D>(b));
+    let (mut a_done, mut b_done) = (None, None);
+    // poll both until they're done, stopping at the first error
+    core::future::poll_fn(|cx| {
+        if a_done.is_none() {
+            if let core::task::Poll::Ready(r) = a.as_mut().poll(cx) {
+                a_done = Some(r?);
+            }
+        }
+        if b_done.is_none() {
+            if let core::task::Poll::Ready(r) = b.as_mut().poll(cx) {
+                b_done = Some(r?);
+            }
+        }
+        match a_done.is_some() && b_done.is_some() {
+            true => core::task::Poll::Ready(Ok::<_, std::io::Error>(())),
+            false => core::task::Poll::Pending,
+        }
+    })
+    .await?;
+    Ok(a_done == b_done)
+}
+
+/// Spawn `cmd` with its stdin piped, stream `reader` into it and wait for it to exit, returning
+/// its status and the digest of exactly what the child accepted on its stdin.
+///
+/// If the child exits (or closes its stdin) before reading everything, the resulting broken pipe
+/// isn't an error: the status is returned with the digest of the part it did accept.
+#[cfg(feature = "stdio")]
+pub fn hash_pipe_to_command<D: MinDigest + Default>(
+    mut reader: impl std::io::Read,
+    cmd: &mut std::process::Command,
+) -> std::io::Result<(std::process::ExitStatus, D::Output)> {
+    let mut child = cmd.stdin(std::process::Stdio::piped()).spawn()?;
+    let stdin = child.stdin.take().expect("stdin is piped");
+    let mut hasher = WriteHasher::<D, _>::new(stdin);
+    let copied = std::io::copy(&mut reader, &mut hasher);
+    // closes the child's stdin so it sees EOF
+    let digest = hasher.finalize();
+    let status = child.wait()?;
+    match copied {
+        Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => Err(e),
+        _ => Ok((status, digest)),
+    }
+}
+
+/// Async version of [`hash_pipe_to_command`]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+pub async fn hash_pipe_to_command_async<D: MinDigest + Default>(
+    mut reader: impl tokio::io::AsyncRead + std::marker::Unpin,
+    cmd: &mut tokio::process::Command,
+) -> std::io::Result<(std::process::ExitStatus, D::Output)> {
+    let mut child = cmd.stdin(std::process::Stdio::piped()).spawn()?;
+    let stdin = child.stdin.take().expect("stdin is piped");
+    let mut hasher = crate::WriteHasher::<D, _>::new(stdin);
+    let copied = tokio::io::copy(&mut reader, &mut hasher).await;
+    // closes the child's stdin so it sees EOF
+    let digest = crate::MinDigest::finalize(hasher);
+    let status = child.wait().await?;
+    match copied {
+        Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => Err(e),
+        _ => Ok((status, digest)),
+    }
+}
+
+/// What a call to [`NonBlockingCopier::advance`] got to
+#[cfg(feature = "stdio")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CopyProgress<O> {
+    /// The reader or the writer returned `WouldBlock`, call `advance` again once it's ready
+    WouldBlock,
+    /// The reader is exhausted and everything it returned was accepted by the writer
+    Done(O),
+}
+
+/// A resumable copy from a reader to a writer that hashes everything the writer accepts, for
+/// non-blocking sockets where [`std::io::copy`] would give up on the first `WouldBlock`.
+///
+/// At most one chunk read but not yet accepted by the writer is buffered, and bytes are hashed
+/// exactly once as the writer accepts them, so the digest always covers exactly
+/// [`copied`](Self::copied) bytes.
+///
+/// ```rust
+/// # #[cfg(feature = "crc32c")] {
+/// use write_hasher::{crc32c::Crc32c, CopyProgress, NonBlockingCopier};
+/// let mut copier = NonBlockingCopier::<Crc32c>::new(Crc32c::default());
+/// let (mut reader, mut writer) = (&b"123456789"[..], Vec::new());
+/// // in a poll loop this would wait for readiness on `WouldBlock`
+/// let digest = loop {
+///     match copier.advance(&mut reader, &mut writer).unwrap() {
+///         CopyProgress::WouldBlock => continue,
+///         CopyProgress::Done(digest) => break digest,
+///     }
+/// };
+/// assert_eq!(digest, 0xe3069283);
+/// assert_eq!(writer, b"123456789");
+/// # }
+/// ```
+#[cfg(feature = "stdio")]
+pub struct NonBlockingCopier<D> {
+    hasher: Option<D>,
+    buf: Box<[u8]>,
+    start: usize,
+    end: usize,
+    eof: bool,
+    copied: u64,
+}
+
+#[cfg(feature = "stdio")]
+impl<D: MinDigest> NonBlockingCopier<D> {
+    /// A copier buffering chunks of up to 8 KiB, like [`std::io::copy`]
+    pub fn new(hasher: D) -> Self {
+        Self::with_capacity(hasher, 8 * 1024)
+    }
+
+    pub fn with_capacity(hasher: D, capacity: usize) -> Self {
+        Self {
+            hasher: Some(hasher),
+            buf: vec![0; capacity.max(1)].into_boxed_slice(),
+            start: 0,
+            end: 0,
+            eof: false,
+            copied: 0,
+        }
+    }
+
+    /// The number of bytes accepted by the writer (and hashed) so far
+    pub fn copied(&self) -> u64 {
+        self.copied
+    }
+
+    /// The bytes read but not yet accepted by the writer
+    pub fn pending(&self) -> &[u8] {
+        &self.buf[self.start..self.end]
+    }
+
+    /// Copy until the reader or the writer would block, or until the copy is done.
+    ///
+    /// `Interrupted` errors are retried, any other error is returned and the copy can be resumed
+    /// if the error was transient. Calling this again after it returned
+    /// [`Done`](CopyProgress::Done) is an error.
+    pub fn advance<R, W>(
+        &mut self,
+        reader: &mut R,
+        writer: &mut W,
+    ) -> std::io::Result<CopyProgress<D::Output>>
+    where
+        R: std::io::Read + ?Sized,
+        W: std::io::Write + ?Sized,
+    {
+        use std::io::ErrorKind;
+        let Some(hasher) = &mut self.hasher else {
+            return Err(std::io::Error::other("the copy is already done"));
+        };
+        loop {
+            if self.start < self.end {
+                match writer.write(&self.buf[self.start..self.end]) {
+                    Ok(0) => return Err(ErrorKind::WriteZero.into()),
+                    Ok(n) => {
+                        hasher.update(&self.buf[self.start..self.start + n]);
+                        self.start += n;
+                        self.copied += n as u64;
+                    }
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                        return Ok(CopyProgress::WouldBlock)
+                    }
+                    Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e),
+                }
+            } else if self.eof {
+                match writer.flush() {
+                    Ok(()) => break,
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                        return Ok(CopyProgress::WouldBlock)
+                    }
+                    Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e),
+                }
+            } else {
+                match reader.read(&mut self.buf) {
+                    Ok(0) => self.eof = true,
+                    Ok(n) => (self.start, self.end) = (0, n),
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                        return Ok(CopyProgress::WouldBlock)
+                    }
+                    Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+        let hasher = self.hasher.take().expect("checked at the start");
+        Ok(CopyProgress::Done(hasher.finalize()))
+    }
+}
+
+/// Decode a hex string (in any case) into bytes, returns `None` if it isn't valid hex
+pub(crate) fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    fn nibble(c: u8) -> Option<u8> {
+        match c {
+            b'0'..=b'9' => Some(c - b'0'),
+            b'a'..=b'f' => Some(c - b'a' + 10),
+            b'A'..=b'F' => Some(c - b'A' + 10),
+            _ => None,
+        }
+    }
+    let hex = hex.as_bytes();
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    hex.chunks(2)
+        .map(|pair| Some(nibble(pair[0])? << 4 | nibble(pair[1])?))
+        .collect()
+}
+
+/// Encode bytes as a lower case hex string
+pub(crate) fn encode_hex(bytes: &[u8]) -> String {
+    use core::fmt::Write;
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        let _ = write!(hex, "{:02x}", byte);
+    }
+    hex
+}
@@ -8,21 +8,35 @@
 //!  written to it.  
 //!
 //!
-//! The object should implement AsyncRead so that it can wrap some data and then read from that
-//! transparently while offloading the hashing to another thread.
+//! [`ReadHasher`] does the same for data read from a reader, and [`BackgroundWriteHasher`]
+//! offloads the hashing to another thread.
 //! ```rust
+//! # #[cfg(all(feature = "stdio", any(feature = "sha2", feature = "digest")))] {
 //! extern crate sha2;
 //! use write_hasher::{WriteHasher, MinDigest};
-//! let mut src = std::fs::File::open(".gitignore").unwrap();
+//! let mut src = std::fs::File::open("LICENSE").unwrap();
 //! let sink = std::io::sink();
 //! let mut hasher = WriteHasher::<sha2::Sha256, _>::new(sink);
 //! std::io::copy(&mut src, &mut hasher).unwrap();
 //! let x = hasher.finalize();
 //! let x = format!("{:x}", x);
 //! assert_eq!(
-//!     "c1e953ee360e77de57f7b02f1b7880bd6a3dc22d1a69e953c2ac2c52cc52d247",
+//!     "f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f",
 //!     x
 //! );
+//! # }
+//! ```
+//!
+//! Every built-in algorithm also has [aliases] for its writer and reader, which is the easiest way
+//! to name them, e.g. `Sha256Writer<T>` with the `sha2` feature or `Crc32cWriter<T>` with `crc32c`
+//! ```rust
+//! # #[cfg(all(feature = "stdio", feature = "crc32c"))] {
+//! use write_hasher::{Crc32cWriter, MinDigest};
+//! let mut src = std::fs::File::open("LICENSE").unwrap();
+//! let mut hasher = Crc32cWriter::new(std::io::sink());
+//! std::io::copy(&mut src, &mut hasher).unwrap();
+//! let _crc = hasher.finalize();
+//! # }
 //! ```
 
 #[cfg(all(
@@ -35,321 +49,252 @@
         feature = "md4",
         feature = "md5",
         feature = "blake2",
-        feature = "crc32fast"
+        feature = "crc32fast",
+        feature = "ascon",
+        feature = "skein",
+        feature = "blake3",
+        feature = "ripemd",
+        feature = "md-5"
     )
 ))]
 compile_error!("Please either use digest feature (for generic impls) or
-               concrete_impls (sha1, sha2, md2, md4, md5, blake2, crc32fast) features (for concrete impls),
+               concrete_impls (sha1, sha2, md2, md4, md5, blake2, crc32fast, ascon, skein, blake3, ripemd, md-5) features (for concrete impls),
                but not both");
 
-#[cfg(any(feature = "futures", feature = "tokio"))]
-use core::{pin::Pin, task::Poll};
-#[cfg(feature = "digest")]
-use digest::Digest;
-
-/// A hasher that will be a wrapper over any Write / AsyncWrite object and transparently calculate
-/// hash for any data written to it
-#[cfg_attr(any(feature = "futures", feature = "tokio"), pin_project::pin_project)]
-#[derive(Default)]
-pub struct WriteHasher<D, T> {
-    hasher: D,
-    #[cfg_attr(any(feature = "futures", feature = "tokio"), pin)]
-    inner: T,
-}
-
-impl<D, T> WriteHasher<D, T> {
-    pub fn new_with_hasher(inner: T, hasher: D) -> Self {
-        Self { hasher, inner }
-    }
-
-    pub fn new(inner: T) -> Self
-    where
-        D: Default,
-    {
-        Self {
-            hasher: Default::default(),
-            inner,
-        }
-    }
-}
-
-// #[cfg(feature = "digest")]
-// impl<D: Digest, T> WriteHasher<D, T> {
-//     pub fn new(inner: T) -> Self {
-//         Self {
-//             hasher: D::new(),
-//             inner,
-//         }
-//     }
-// }
+pub mod aliases;
+#[cfg_attr(docsrs, doc(cfg(feature = "async-digest")))]
+#[cfg(feature = "async-digest")]
+pub mod async_digest;
+#[cfg(any(feature = "stdio", feature = "tokio"))]
+pub mod background;
+#[cfg(feature = "stdio")]
+pub mod checksum_file;
+pub mod combinators;
+pub mod digests;
+#[cfg(any(feature = "stdio", feature = "tokio"))]
+pub mod dir;
+pub mod duplex;
+pub mod encoding;
+pub mod error;
+#[cfg_attr(docsrs, doc(cfg(feature = "flate2")))]
+#[cfg(feature = "flate2")]
+pub mod gzip;
+pub mod helpers;
+#[cfg_attr(docsrs, doc(cfg(feature = "multihash")))]
+#[cfg(feature = "multihash")]
+pub mod multihash;
+pub mod normalize;
+pub mod reader;
+#[cfg(feature = "stdio")]
+pub mod self_test;
+#[cfg_attr(docsrs, doc(cfg(feature = "sri")))]
+#[cfg(feature = "sri")]
+pub mod sri;
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(feature = "serde")]
+pub mod state;
+pub mod verifying;
+pub mod writer;
 
+pub use aliases::*;
+#[cfg_attr(docsrs, doc(cfg(feature = "async-digest")))]
+#[cfg(feature = "async-digest")]
+pub use async_digest::{AsyncBackendWriteHasher, AsyncMinDigest, DynAsyncMinDigest};
+#[cfg(any(feature = "stdio", feature = "tokio"))]
+pub use background::BackgroundWriteHasher;
+#[cfg(feature = "stdio")]
+pub use combinators::hash_chain;
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+pub use combinators::hash_chain_async;
+#[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+#[cfg(feature = "rayon")]
+pub use combinators::ParallelMultiHasher;
+pub use combinators::{
+    BorrowedDigest, Checkpoint, DelimitedDigest, Inspect, LocalSharedDigest, MappedDigest,
+    MultiDigest, OptionalDigest, SharedDigest, TeeDigest,
+};
+#[cfg(any(feature = "stdio", feature = "tokio"))]
+pub use combinators::{ChainedReadHasher, ReadChain};
+#[cfg_attr(docsrs, doc(cfg(feature = "adler")))]
+#[cfg(feature = "adler")]
+pub use digests::adler32;
+#[cfg_attr(docsrs, doc(cfg(feature = "crc32c")))]
+#[cfg(feature = "crc32c")]
+pub use digests::crc32c;
+#[cfg_attr(docsrs, doc(cfg(feature = "crc64")))]
+#[cfg(feature = "crc64")]
+pub use digests::crc64;
+#[cfg_attr(docsrs, doc(cfg(feature = "crc")))]
+#[cfg(feature = "crc")]
+pub use digests::crc_generic::{self, CrcHasher};
+#[cfg_attr(docsrs, doc(cfg(feature = "hkdf")))]
+#[cfg(feature = "hkdf")]
+pub use digests::kdf;
+#[cfg_attr(docsrs, doc(cfg(feature = "mac")))]
+#[cfg(feature = "mac")]
+pub use digests::mac::{self, MacHasher};
+#[cfg_attr(docsrs, doc(cfg(feature = "poly1305")))]
+#[cfg(feature = "poly1305")]
+pub use digests::poly1305;
+#[cfg_attr(docsrs, doc(cfg(feature = "sha1cd")))]
+#[cfg(feature = "sha1cd")]
+pub use digests::sha1cd;
+#[cfg_attr(docsrs, doc(cfg(feature = "sha3")))]
+#[cfg(feature = "sha3")]
+pub use digests::sp800_185;
+#[cfg_attr(docsrs, doc(cfg(feature = "uuid")))]
+#[cfg(feature = "uuid")]
+pub use digests::uuids;
+#[cfg_attr(docsrs, doc(cfg(feature = "sha3")))]
+#[cfg(feature = "sha3")]
+pub use digests::xof::{self, XofHasher};
+#[cfg_attr(docsrs, doc(cfg(feature = "xxh3")))]
+#[cfg(feature = "xxh3")]
+pub use digests::xxh3;
+#[cfg_attr(docsrs, doc(cfg(feature = "xxh64")))]
+#[cfg(feature = "xxh64")]
+pub use digests::xxh64;
+#[cfg_attr(docsrs, doc(cfg(feature = "digest")))]
 #[cfg(feature = "digest")]
-impl<D: Digest + digest::Reset, T> WriteHasher<D, T> {
-    pub fn reset(&mut self) {
-        <D as Digest>::reset(&mut self.hasher)
-    }
-}
-
-/// A minimal version of [`Digest`][digest::digest] trait that is used to implement the WriteHasher
-/// and all implementations of the Digest trait.
-pub trait MinDigest {
-    type Output;
-    fn update(&mut self, data: impl AsRef<[u8]>);
-    fn finalize(self) -> Self::Output;
-}
-
-impl<MD: MinDigest, T> MinDigest for WriteHasher<MD, T> {
-    type Output = MD::Output;
-    fn update(&mut self, data: impl AsRef<[u8]>) {
-        self.hasher.update(data)
-    }
-    fn finalize(self) -> MD::Output {
-        self.hasher.finalize()
-    }
-}
+pub use digests::BoxedDynDigest;
+#[cfg_attr(docsrs, doc(cfg(all(feature = "blake3", feature = "rayon"))))]
+#[cfg(all(feature = "blake3", feature = "rayon"))]
+pub use digests::ParallelBlake3;
+pub use digests::{
+    combine_all, crc24, hash_bytes, hash_iter, AnyDigest, AnyHasher, CombineDigest, DynMinDigest,
+    MinDigest, MinDigestReset, StdHasher,
+};
+#[cfg(any(feature = "stdio", feature = "tokio"))]
+pub use dir::HashDirOptions;
+#[cfg(feature = "stdio")]
+pub use dir::{hash_dir, hash_dir_with_options};
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+pub use dir::{hash_dir_async, hash_dir_with_options_async};
+pub use duplex::DuplexHasher;
+pub use encoding::{FromOutputBytes, HexEq, OutputBytes};
+pub use error::HashWriterError;
+#[cfg(feature = "flate2")]
+pub use gzip::{GzipHashingEncoder, GzipTrailer, GzipVerifyingReader};
+#[cfg_attr(docsrs, doc(cfg(feature = "memmap2")))]
+#[cfg(all(feature = "stdio", feature = "memmap2"))]
+pub use helpers::hash_file_mmap;
+#[cfg_attr(docsrs, doc(cfg(target_os = "linux")))]
+#[cfg(all(feature = "stdio", target_os = "linux"))]
+pub use helpers::hash_file_sparse;
+#[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+#[cfg(all(feature = "stdio", feature = "rayon"))]
+pub use helpers::hash_files;
+pub use helpers::HashFileOptions;
+#[cfg(feature = "stdio")]
+pub use helpers::{
+    hash_file, hash_file_with_hasher, hash_file_with_options, hash_pipe_to_command, hash_reader,
+    hash_reader_with_hasher, readers_equal_by_hash, CopyProgress, NonBlockingCopier,
+};
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+pub use helpers::{
+    hash_file_async, hash_file_with_hasher_async, hash_file_with_options_async,
+    hash_pipe_to_command_async, hash_reader_async, hash_reader_with_hasher_async,
+    readers_equal_by_hash_async,
+};
+#[cfg_attr(docsrs, doc(cfg(all(feature = "xattr", unix))))]
+#[cfg(all(feature = "stdio", feature = "xattr", unix))]
+pub use helpers::{hash_file_cached, CacheOptions, CacheStatus};
+#[cfg(feature = "stdio")]
+pub use normalize::NormalizingWriter;
+pub use normalize::{Normalization, Normalize, NormalizingWriteHasher};
+pub use reader::{ReadHasher, SeekPolicy};
+#[cfg(feature = "stdio")]
+pub use self_test::self_test;
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+#[cfg(feature = "serde")]
+pub use state::{ResumableDigest, StateBlob, StateError};
+pub use verifying::VerifyingWriteHasher;
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio-offload")))]
+#[cfg(feature = "tokio-offload")]
+pub use writer::OffloadWriteHasher;
+#[cfg(any(feature = "stdio", feature = "tokio", feature = "futures"))]
+pub use writer::ProgressWriter;
+pub use writer::WriteHasher;
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio-blocking")))]
+#[cfg(feature = "tokio-blocking")]
+pub use writer::{BlockingWriteHasher, CancelHandle};
 
-#[cfg(feature = "digest")]
-impl<T: Digest> MinDigest for T {
-    type Output = digest::Output<T>;
-    fn update(&mut self, data: impl AsRef<[u8]>) {
-        <T as Digest>::update(self, data)
-    }
-    fn finalize(self) -> Self::Output {
-        <T as Digest>::finalize(self)
-    }
-}
+#[cfg(test)]
+#[allow(unused_imports)]
+use helpers::decode_hex;
 
+// The backend crates of the enabled features, so they can be named without depending on them
+// directly, e.g. `write_hasher::sha2::Sha256`.
+//
+// The `poly1305` and `crc32c` crates aren't re-exported since those names are taken by this
+// crate's own modules.
+#[cfg(feature = "ascon")]
+pub use ascon_hash;
+#[cfg(feature = "blake2")]
+pub use blake2;
+#[cfg(feature = "blake3")]
+pub use blake3;
+#[cfg(feature = "crc")]
+pub use crc;
+#[cfg(feature = "crc32fast")]
+pub use crc32fast;
 #[cfg(any(
+    feature = "digest",
     feature = "sha2",
     feature = "sha1",
     feature = "md2",
     feature = "md4",
     feature = "md5",
-    feature = "blake2"
+    feature = "md-5",
+    feature = "blake2",
+    feature = "ascon",
+    feature = "skein",
+    feature = "ripemd",
+    feature = "sha3",
+    feature = "hkdf",
+    feature = "mac"
 ))]
-macro_rules! delegate_digest_mindigest {
-    ($($x:ty),*) => {
-        $(
-            impl MinDigest for $x {
-                type Output = digest::Output<$x>;
-                fn update(&mut self, data: impl AsRef<[u8]>) {
-                    <Self as digest::Digest>::update(self, data)
-                }
-                fn finalize(self) -> Self::Output {
-                    <Self as digest::Digest>::finalize(self)
-                }
-            }
-
-            impl<T> crate::WriteHasher<$x, T> {
-                pub fn new(inner: T) -> Self {
-                    Self {
-                        hasher: <$x as ::digest::Digest>::new(),
-                        inner,
-                    }
-                }
-            }
-
-        )*
-    };
-}
-
-#[cfg(feature = "sha2")]
-mod sha2 {
-    use super::MinDigest;
-    delegate_digest_mindigest!(
-        sha2::Sha224,
-        sha2::Sha256,
-        sha2::Sha384,
-        sha2::Sha512,
-        sha2::Sha512_224,
-        sha2::Sha512_256
-    );
-}
-
-#[cfg(feature = "sha1")]
-mod sha1 {
-    use super::MinDigest;
-    delegate_digest_mindigest!(sha1::Sha1);
-}
-
+pub use digest;
+#[cfg(feature = "flate2")]
+pub use flate2;
+#[cfg(feature = "hkdf")]
+pub use hkdf;
 #[cfg(feature = "md2")]
-mod md2 {
-    use super::MinDigest;
-    delegate_digest_mindigest!(md2::Md2);
-}
-
+pub use md2;
 #[cfg(feature = "md4")]
-mod md4 {
-    use super::MinDigest;
-    delegate_digest_mindigest!(md4::Md4);
-}
-
+pub use md4;
 #[cfg(feature = "md5")]
-mod md5 {
-    use super::MinDigest;
-    impl MinDigest for md5::Context {
-        type Output = md5::Digest;
-        fn update(&mut self, data: impl AsRef<[u8]>) {
-            self.consume(data)
-        }
-        fn finalize(self) -> Self::Output {
-            self.compute()
-        }
-    }
-
-    impl<T> crate::WriteHasher<md5::Context, T> {
-        pub fn new(inner: T) -> Self {
-            Self {
-                hasher: md5::Context::new(),
-                inner,
-            }
-        }
-    }
-}
-
-#[cfg(feature = "blake2")]
-mod blake2 {
-    use super::MinDigest;
-    // use digest::consts::*;
-    // use digest::typenum::*;
-
-    // delegate_digest_mindigest!(blake2::Blake2b);
-    delegate_digest_mindigest!(blake2::Blake2b512);
-    // delegate_digest_mindigest!(blake2::Blake2bCore);
-    // delegate_digest_mindigest!(blake2::Blake2bMac512);
-    // delegate_digest_mindigest!(blake2::Blake2bVar);
-    // delegate_digest_mindigest!(blake2::Blake2s);
-    delegate_digest_mindigest!(blake2::Blake2s256);
-    // delegate_digest_mindigest!(blake2::Blake2sCore);
-    // delegate_digest_mindigest!(blake2::Blake2sMac256);
-    // delegate_digest_mindigest!(blake2::Blake2sVar);
-}
-
-#[cfg(feature = "crc32fast")]
-mod crc32fast {
-    use super::MinDigest;
-    impl MinDigest for crc32fast::Hasher {
-        type Output = u32;
-        fn update(&mut self, data: impl AsRef<[u8]>) {
-            self.update(data.as_ref())
-        }
-        fn finalize(self) -> Self::Output {
-            self.finalize()
-        }
-    }
-
-    impl<T> crate::WriteHasher<crc32fast::Hasher, T> {
-        pub fn new(inner: T) -> Self {
-            Self {
-                hasher: crc32fast::Hasher::new(),
-                inner,
-            }
-        }
-    }
-}
-
-// #[cfg(feature = "crc32c")]
-pub mod crc32c {
-    use super::MinDigest;
-    #[repr(transparent)]
-    #[derive(Debug, Default)]
-    pub struct Crc32c(u32);
-
-    impl Crc32c {
-        pub fn new() -> Self {
-            Default::default()
-        }
-    }
-
-    impl MinDigest for Crc32c {
-        type Output = u32;
-        fn update(&mut self, data: impl AsRef<[u8]>) {
-            self.0 = crc32c::crc32c_append(self.0, data.as_ref())
-        }
-        fn finalize(self) -> Self::Output {
-            self.0
-        }
-    }
-}
-
-#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
-#[cfg(feature = "tokio")]
-impl<D: MinDigest, T: tokio::io::AsyncWrite + std::marker::Unpin> tokio::io::AsyncWrite
-    for WriteHasher<D, T>
-{
-    fn poll_write(
-        self: Pin<&mut Self>,
-        cx: &mut std::task::Context<'_>,
-        buf: &[u8],
-    ) -> std::task::Poll<std::io::Result<usize>> {
-        let ah = self.project();
-        let r = ah.inner.poll_write(cx, buf);
-        if let Poll::Ready(Ok(n)) = r {
-            ah.hasher.update(&buf[..n]);
-        }
-        r
-    }
-    fn poll_flush(
-        self: Pin<&mut Self>,
-        cx: &mut std::task::Context<'_>,
-    ) -> std::task::Poll<std::io::Result<()>> {
-        let ah = self.project();
-        ah.inner.poll_flush(cx)
-    }
-    fn poll_shutdown(
-        self: Pin<&mut Self>,
-        cx: &mut std::task::Context<'_>,
-    ) -> std::task::Poll<std::io::Result<()>> {
-        let ah = self.project();
-        ah.inner.poll_shutdown(cx)
-    }
-}
+pub use md5;
+#[cfg(feature = "md-5")]
+pub use md_5;
+#[cfg(feature = "ripemd")]
+pub use ripemd;
+#[cfg(feature = "sha1")]
+pub use sha1;
+#[cfg(feature = "sha1cd")]
+pub use sha1collisiondetection;
+#[cfg(feature = "sha2")]
+pub use sha2;
+#[cfg(feature = "sha3")]
+pub use sha3;
+#[cfg(feature = "skein")]
+pub use skein;
+#[cfg(feature = "uuid")]
+pub use uuid;
+#[cfg(any(feature = "xxh3", feature = "xxh64"))]
+pub use xxhash_rust;
 
-#[cfg(feature = "futures")]
-impl<D: MinDigest, T: futures::io::AsyncWrite + std::marker::Unpin> futures::io::AsyncWrite
-    for WriteHasher<D, T>
-{
-    fn poll_write(
-        self: Pin<&mut Self>,
-        cx: &mut std::task::Context<'_>,
-        buf: &[u8],
-    ) -> std::task::Poll<futures::io::Result<usize>> {
-        let ah = self.project();
-        let r = ah.inner.poll_write(cx, buf);
-        if let Poll::Ready(Ok(n)) = r {
-            ah.hasher.update(&buf[..n]);
-        }
-        r
-    }
-    fn poll_flush(
-        self: Pin<&mut Self>,
-        cx: &mut std::task::Context<'_>,
-    ) -> std::task::Poll<futures::io::Result<()>> {
-        let ah = self.project();
-        ah.inner.poll_flush(cx)
-    }
-    fn poll_close(
-        self: Pin<&mut Self>,
-        cx: &mut std::task::Context<'_>,
-    ) -> std::task::Poll<futures::io::Result<()>> {
-        let ah = self.project();
-        ah.inner.poll_close(cx)
-    }
+/// `use write_hasher::prelude::*;` to bring the hashers and the [`MinDigest`] trait into scope
+pub mod prelude {
+    pub use crate::{HexEq, MinDigest, OutputBytes, ReadHasher, SeekPolicy, WriteHasher};
 }
 
-#[cfg(feature = "stdio")]
-impl<D: MinDigest, T: std::io::Write> std::io::Write for WriteHasher<D, T> {
-    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        let r = std::io::Write::write(&mut self.inner, buf);
-        if let Ok(n) = r {
-            MinDigest::update(&mut self.hasher, &buf[..n]);
-        }
-        r
-    }
-    fn flush(&mut self) -> std::io::Result<()> {
-        self.inner.flush()
-    }
-}
+#[cfg_attr(docsrs, doc(cfg(feature = "test-util")))]
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
 
 #[cfg(test)]
 mod tests {
@@ -359,7 +304,7 @@ mod tests {
     #[cfg(any(feature = "sha2", feature = "digest"))]
     async fn test_read() {
         extern crate sha2;
-        let mut src = tokio::fs::File::open(".gitignore").await.unwrap();
+        let mut src = tokio::fs::File::open("LICENSE").await.unwrap();
         let sink = tokio::io::sink();
         let mut hasher = WriteHasher::<sha2::Sha256, _>::new(sink);
         tokio::io::copy(&mut src, &mut hasher).await.unwrap();
@@ -367,17 +312,52 @@ mod tests {
         let x = hasher.finalize();
         let x = format!("{:x}", x);
         assert_eq!(
-            "c1e953ee360e77de57f7b02f1b7880bd6a3dc22d1a69e953c2ac2c52cc52d247",
+            "f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f",
             x
         );
     }
 
+    #[tokio::test]
+    #[cfg(feature = "tokio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_read_hasher_tokio_copy() {
+        extern crate sha2;
+        use tokio::io::AsyncRead;
+        let src = tokio::fs::File::open("LICENSE").await.unwrap();
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(src);
+        let copied = tokio::io::copy(&mut hasher, &mut tokio::io::sink())
+            .await
+            .unwrap();
+        assert_eq!(copied, 1092);
+        assert_eq!(
+            "f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f",
+            format!("{:x}", hasher.finalize())
+        );
+
+        // only what a poll adds to the ReadBuf is hashed, not what was already in it
+        let src = std::fs::read("LICENSE").unwrap();
+        let mut hasher =
+            ReadHasher::<sha2::Sha256, _>::new(test_util::ShortReader::new(&src[..], 100));
+        let mut storage = [0u8; 256];
+        let mut buf = tokio::io::ReadBuf::new(&mut storage);
+        buf.put_slice(b"already filled");
+        let mut hashed = 0;
+        while hashed < src.len() {
+            core::future::poll_fn(|cx| core::pin::Pin::new(&mut hasher).poll_read(cx, &mut buf))
+                .await
+                .unwrap();
+            hashed += buf.filled().len() - b"already filled".len();
+            buf.set_filled(b"already filled".len());
+        }
+        assert_eq!(sha256_hex(&src), format!("{:x}", hasher.finalize()));
+    }
+
     #[tokio::test]
     #[cfg(feature = "futures")]
     #[cfg(any(feature = "sha2", feature = "digest"))]
     async fn test_read_futures() {
         extern crate sha2;
-        let src = std::fs::read(".gitignore").unwrap();
+        let src = std::fs::read("LICENSE").unwrap();
         let src = futures::io::Cursor::new(src);
         let sink = futures::io::sink();
         let mut hasher = WriteHasher::<sha2::Sha256, _>::new(sink);
@@ -386,24 +366,332 @@ mod tests {
         let x = hasher.finalize();
         let x = format!("{:x}", x);
         assert_eq!(
-            "c1e953ee360e77de57f7b02f1b7880bd6a3dc22d1a69e953c2ac2c52cc52d247",
+            "f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f",
             x
         );
     }
 
+    #[tokio::test]
+    #[cfg(feature = "futures")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_read_hasher_futures_copy() {
+        extern crate sha2;
+        let src = std::fs::read("LICENSE").unwrap();
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(futures::io::Cursor::new(src));
+        let copied = futures::io::copy(&mut hasher, &mut futures::io::sink())
+            .await
+            .unwrap();
+        assert_eq!(copied, 1092);
+        assert_eq!(
+            "f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f",
+            format!("{:x}", hasher.finalize())
+        );
+    }
+
     #[tokio::test]
     #[cfg(feature = "tokio")]
     #[cfg(feature = "crc32fast")]
     async fn test_crc32() {
         extern crate crc32fast;
-        let mut src = tokio::fs::File::open(".gitignore").await.unwrap();
+        let mut src = tokio::fs::File::open("LICENSE").await.unwrap();
         let sink = tokio::io::sink();
         let mut hasher =
             WriteHasher::<crc32fast::Hasher, _>::new_with_hasher(sink, Default::default());
         tokio::io::copy(&mut src, &mut hasher).await.unwrap();
         // hasher.write_all(b"hello worlding").await.unwrap();
         let x = hasher.finalize();
-        assert_eq!(x, 0x705ffe14);
+        assert_eq!(x, 0xa4545a59);
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_write_hasher_inner() {
+        extern crate sha2;
+        use std::io::Write;
+        let src = std::fs::read("LICENSE").unwrap();
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(Vec::new());
+        // written around the hasher, so not part of the digest
+        hasher.get_mut().extend_from_slice(b"header\n");
+        hasher.write_all(&src).unwrap();
+        let captured = hasher.get_ref().clone();
+        assert_eq!(format!("{:x}", hasher.finalize()), sha256_hex(&src));
+        assert_eq!(captured, [&b"header\n"[..], &src].concat());
+
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(Vec::new());
+        hasher.write_all(&src).unwrap();
+        assert_eq!(hasher.into_inner(), src);
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_write_hasher_bytes_written() {
+        extern crate sha2;
+        use std::io::Write;
+        use test_util::*;
+        let src = std::fs::read("LICENSE").unwrap();
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(ShortWriter::new(Vec::new(), 13));
+        hasher.write_all(&src).unwrap();
+        assert_eq!(hasher.bytes_written(), 1092);
+        assert_eq!(format!("{:x}", hasher.finalize()), sha256_hex(&src));
+
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(FailAfter::new(Vec::new(), 500));
+        hasher.write_all(&src).unwrap_err();
+        assert_eq!(hasher.bytes_written(), 500);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "tokio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_write_hasher_bytes_written_tokio() {
+        extern crate sha2;
+        use test_util::*;
+        use tokio::io::AsyncWriteExt;
+        let src = std::fs::read("LICENSE").unwrap();
+        let sink = PendingWriter::new(ShortWriter::new(Vec::new(), 13));
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(sink);
+        hasher.write_all(&src).await.unwrap();
+        assert_eq!(hasher.bytes_written(), 1092);
+        assert_eq!(format!("{:x}", hasher.finalize()), sha256_hex(&src));
+
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(FailAfter::new(Vec::new(), 500));
+        hasher.write_all(&src).await.unwrap_err();
+        assert_eq!(hasher.bytes_written(), 500);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "futures")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_write_hasher_bytes_written_futures() {
+        extern crate sha2;
+        use futures::io::AsyncWriteExt;
+        use test_util::*;
+        let src = std::fs::read("LICENSE").unwrap();
+        let sink = PendingWriter::new(ShortWriter::new(Vec::new(), 13));
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(sink);
+        hasher.write_all(&src).await.unwrap();
+        assert_eq!(hasher.bytes_written(), 1092);
+        assert_eq!(format!("{:x}", hasher.finalize()), sha256_hex(&src));
+
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(FailAfter::new(Vec::new(), 500));
+        hasher.write_all(&src).await.unwrap_err();
+        assert_eq!(hasher.bytes_written(), 500);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "crc32c")]
+    #[cfg(any(feature = "stdio", feature = "tokio", feature = "futures"))]
+    async fn test_write_hasher_hash_ranges() {
+        use crate::crc32c::Crc32c;
+        let src: Vec<u8> = (0..100u8).collect();
+        let ranges = vec![3..10, 10..27, 40..95];
+        let expected = hash_bytes::<Crc32c>([&src[3..27], &src[40..95]].concat());
+        // the 29 byte write straddles all three ranges
+        let sizes = [1, 2, 5, 29, 13, 31, 19];
+        assert_eq!(sizes.iter().sum::<usize>(), src.len());
+
+        #[cfg(feature = "stdio")]
+        {
+            use std::io::Write;
+            let mut hasher =
+                WriteHasher::<Crc32c, _>::new(Vec::new()).with_hash_ranges(ranges.clone());
+            let mut rest = &src[..];
+            for size in sizes {
+                let (chunk, tail) = rest.split_at(size);
+                hasher.write_all(chunk).unwrap();
+                rest = tail;
+            }
+            assert_eq!(hasher.get_ref(), &src);
+            assert_eq!(hasher.finalize(), expected);
+
+            let mut hasher = WriteHasher::<Crc32c, _>::new(std::io::sink()).skip_prefix(33);
+            for chunk in src.chunks(7) {
+                hasher.write_all(chunk).unwrap();
+            }
+            assert_eq!(hasher.finalize(), hash_bytes::<Crc32c>(&src[33..]));
+
+            // offsets count the paused writes, but not what is fed to the hasher directly
+            let mut hasher =
+                WriteHasher::<Crc32c, _>::new(std::io::sink()).with_hash_ranges(vec![6..9, 50..60]);
+            hasher.hasher_mut().update(&src[90..]);
+            hasher.pause_hashing();
+            hasher.write_all(&src[..4]).unwrap();
+            hasher.resume_hashing();
+            let bufs = [
+                std::io::IoSlice::new(&src[4..7]),
+                std::io::IoSlice::new(&src[7..12]),
+            ];
+            assert_eq!(hasher.write_vectored(&bufs).unwrap(), 8);
+            assert_eq!(hasher.bytes_hashed(), 3);
+            assert_eq!(
+                hasher.finalize(),
+                hash_bytes::<Crc32c>([&src[90..], &src[6..9]].concat())
+            );
+        }
+        #[cfg(feature = "tokio")]
+        {
+            use tokio::io::AsyncWriteExt;
+            let mut hasher =
+                WriteHasher::<Crc32c, _>::new(test_util::ShortWriter::new(Vec::new(), 11))
+                    .with_hash_ranges(ranges.clone());
+            let mut rest = &src[..];
+            for size in sizes {
+                let (chunk, tail) = rest.split_at(size);
+                hasher.write_all(chunk).await.unwrap();
+                rest = tail;
+            }
+            assert_eq!(hasher.finalize(), expected);
+        }
+        #[cfg(feature = "futures")]
+        {
+            use futures::io::AsyncWriteExt;
+            let mut hasher =
+                WriteHasher::<Crc32c, _>::new(test_util::ShortWriter::new(Vec::new(), 11))
+                    .with_hash_ranges(ranges.clone());
+            let mut rest = &src[..];
+            for size in sizes {
+                let (chunk, tail) = rest.split_at(size);
+                hasher.write_all(chunk).await.unwrap();
+                rest = tail;
+            }
+            assert_eq!(hasher.finalize(), expected);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_pause_hashing() {
+        extern crate sha2;
+        use std::io::{IoSlice, Write};
+        use test_util::*;
+        let payload = std::fs::read("LICENSE").unwrap();
+        let header = [b"HDR1".as_slice(), &[0; 60]].concat();
+        let expected = [header.as_slice(), &payload].concat();
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(ShortWriter::new(Vec::new(), 13));
+        hasher.pause_hashing();
+        assert!(hasher.is_hashing_paused());
+        // a short vectored write, the rest of the header goes through write_all
+        let n = hasher
+            .write_vectored(&[IoSlice::new(&header[..4]), IoSlice::new(&header[4..])])
+            .unwrap();
+        assert_eq!(n, 13);
+        hasher.write_all(&header[n..]).unwrap();
+        hasher.resume_hashing();
+        hasher.write_all(&payload).unwrap();
+        assert_eq!(hasher.bytes_written(), expected.len() as u64);
+        assert_eq!(hasher.bytes_hashed(), payload.len() as u64);
+        let (digest, written) = hasher.finalize_into_parts();
+        assert_eq!(format!("{digest:x}"), sha256_hex(&payload));
+        assert_eq!(written.into_inner(), expected);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "tokio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_pause_hashing_tokio() {
+        extern crate sha2;
+        use test_util::*;
+        use tokio::io::AsyncWriteExt;
+        let payload = std::fs::read("LICENSE").unwrap();
+        let header = [b"HDR1".as_slice(), &[0; 60]].concat();
+        let expected = [header.as_slice(), &payload].concat();
+        let sink = PendingWriter::new(ShortWriter::new(Vec::new(), 13));
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(sink);
+        hasher.pause_hashing();
+        hasher.write_all(&header).await.unwrap();
+        hasher.resume_hashing();
+        hasher.write_all(&payload).await.unwrap();
+        assert_eq!(hasher.bytes_written(), expected.len() as u64);
+        assert_eq!(hasher.bytes_hashed(), payload.len() as u64);
+        assert_eq!(format!("{:x}", hasher.finalize()), sha256_hex(&payload));
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "futures")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_pause_hashing_futures() {
+        extern crate sha2;
+        use futures::io::AsyncWriteExt;
+        use test_util::*;
+        let payload = std::fs::read("LICENSE").unwrap();
+        let header = [b"HDR1".as_slice(), &[0; 60]].concat();
+        let expected = [header.as_slice(), &payload].concat();
+        let sink = PendingWriter::new(ShortWriter::new(Vec::new(), 13));
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(sink);
+        hasher.pause_hashing();
+        hasher.write_all(&header).await.unwrap();
+        hasher.resume_hashing();
+        hasher.write_all(&payload).await.unwrap();
+        assert_eq!(hasher.bytes_written(), expected.len() as u64);
+        assert_eq!(hasher.bytes_hashed(), payload.len() as u64);
+        assert_eq!(format!("{:x}", hasher.finalize()), sha256_hex(&payload));
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_write_hasher_hasher_mut() {
+        extern crate sha2;
+        use std::io::Write;
+        let src = std::fs::read("LICENSE").unwrap();
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(Vec::new());
+        MinDigest::update(hasher.hasher_mut(), b"write-hasher v1\0");
+        hasher.write_all(&src).unwrap();
+        assert_eq!(hasher.get_ref(), &src);
+        let digest = format!("{:x}", MinDigest::finalize(hasher.hasher_ref().clone()));
+        assert_ne!(digest, sha256_hex(&src));
+        assert_eq!(
+            digest,
+            sha256_hex(&[&b"write-hasher v1\0"[..], &src].concat())
+        );
+        assert_eq!(
+            format!("{:x}", MinDigest::finalize(hasher.into_hasher())),
+            digest
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_write_hasher_finalize_into_parts() {
+        extern crate sha2;
+        use std::io::Write;
+        let src = std::fs::read("LICENSE").unwrap();
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(Vec::new());
+        hasher.write_all(&src).unwrap();
+        let (digest, written) = hasher.finalize_into_parts();
+        assert_eq!(format!("{:x}", digest), sha256_hex(&src));
+        assert_eq!(written, src);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "tokio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_write_hasher_finalize_into_parts_tokio() {
+        extern crate sha2;
+        use tokio::io::AsyncWriteExt;
+        let src = std::fs::read("LICENSE").unwrap();
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(Vec::new());
+        hasher.write_all(&src).await.unwrap();
+        let (digest, written) = hasher.finalize_into_parts();
+        assert_eq!(format!("{:x}", digest), sha256_hex(&src));
+        assert_eq!(written, src);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "futures")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_write_hasher_finalize_into_parts_futures() {
+        extern crate sha2;
+        use futures::io::AsyncWriteExt;
+        let src = std::fs::read("LICENSE").unwrap();
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(futures::io::Cursor::new(Vec::new()));
+        hasher.write_all(&src).await.unwrap();
+        let (digest, written) = hasher.finalize_into_parts();
+        assert_eq!(format!("{:x}", digest), sha256_hex(&src));
+        assert_eq!(written.into_inner(), src);
     }
 
     #[test]
@@ -424,10 +712,4914 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_read_hasher_ensure_eof() {
+        extern crate sha2;
+        use std::io::Read;
+        let src = std::fs::File::open("LICENSE").unwrap();
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(src);
+        let mut buf = [0u8; 546];
+        hasher.read_exact(&mut buf).unwrap();
+        let x = hasher.finalize_ensure_eof().unwrap();
+        assert_eq!(
+            "f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f",
+            format!("{:x}", x)
+        );
+
+        let src = std::fs::File::open("LICENSE").unwrap();
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(src);
+        hasher.read_exact(&mut buf).unwrap();
+        let x = hasher.finalize_partial();
+        assert_eq!(
+            "c81ecab4ce2abb6750d534fdf5ed7fc0085ecd0fc8bf22cd14b28292fec47363",
+            format!("{:x}", x)
+        );
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "tokio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_read_hasher_ensure_eof_tokio() {
+        extern crate sha2;
+        use tokio::io::AsyncReadExt;
+        let src = tokio::fs::File::open("LICENSE").await.unwrap();
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(src);
+        let mut buf = [0u8; 546];
+        hasher.read_exact(&mut buf).await.unwrap();
+        let x = hasher.finalize_ensure_eof_async().await.unwrap();
+        assert_eq!(
+            "f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f",
+            format!("{:x}", x)
+        );
+
+        let src = tokio::fs::File::open("LICENSE").await.unwrap();
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(src);
+        hasher.read_exact(&mut buf).await.unwrap();
+        let x = hasher.finalize_partial();
+        assert_eq!(
+            "c81ecab4ce2abb6750d534fdf5ed7fc0085ecd0fc8bf22cd14b28292fec47363",
+            format!("{:x}", x)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_read_hasher_expected() {
+        extern crate sha2;
+        use std::io::Read;
+        let src = std::fs::File::open("LICENSE").unwrap();
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(src)
+            .with_expected_hex("F7A15336677AB4814056F78DA0DA416DB655073780F0388580D58A1D04BE556F")
+            .unwrap();
+        let mut buf = Vec::new();
+        hasher.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf.len(), 1092);
+
+        let src = std::fs::File::open("LICENSE").unwrap();
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(src).with_expected([0u8; 32]);
+        let err = std::io::copy(&mut hasher, &mut std::io::sink()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        let src = std::fs::File::open("LICENSE").unwrap();
+        let hasher = ReadHasher::<sha2::Sha256, _>::new(src).with_expected([0u8; 32]);
+        let err = hasher.finalize_ensure_eof().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        assert!(ReadHasher::<sha2::Sha256, _>::new(std::io::empty())
+            .with_expected_hex("not hex")
+            .is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_read_hasher_verify_without_eof() {
+        extern crate sha2;
+        use std::io::Read;
+        let src = std::fs::File::open("LICENSE").unwrap();
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(src)
+            .with_expected_hex("f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f")
+            .unwrap();
+        let mut buf = [0u8; 1092];
+        hasher.read_exact(&mut buf[..546]).unwrap();
+        assert_eq!(
+            hasher.verify().unwrap_err().kind(),
+            std::io::ErrorKind::InvalidData
+        );
+        hasher.read_exact(&mut buf[546..]).unwrap();
+        hasher.verify().unwrap();
+
+        let hasher = ReadHasher::<sha2::Sha256, _>::new(std::io::empty());
+        assert_eq!(
+            hasher.verify().unwrap_err().kind(),
+            std::io::ErrorKind::InvalidInput
+        );
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "tokio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_read_hasher_expected_tokio() {
+        extern crate sha2;
+        let src = tokio::fs::File::open("LICENSE").await.unwrap();
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(src)
+            .with_expected_hex("f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f")
+            .unwrap();
+        tokio::io::copy(&mut hasher, &mut tokio::io::sink())
+            .await
+            .unwrap();
+
+        let src = tokio::fs::File::open("LICENSE").await.unwrap();
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(src).with_expected([0u8; 32]);
+        let err = tokio::io::copy(&mut hasher, &mut tokio::io::sink())
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "futures")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_read_hasher_buf_read_futures() {
+        extern crate sha2;
+        use futures::io::{AsyncBufReadExt, AsyncReadExt};
+        let src = std::fs::read("LICENSE").unwrap();
+        let reader = futures::io::BufReader::with_capacity(64, futures::io::Cursor::new(&src));
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(reader);
+        let mut line = String::new();
+        hasher.read_line(&mut line).await.unwrap();
+        assert_eq!(line, " The MIT License (MIT)\n");
+        let mut buf = [0u8; 100];
+        hasher.read_exact(&mut buf).await.unwrap();
+        hasher.read_line(&mut line).await.unwrap();
+        let mut rest = Vec::new();
+        hasher.read_to_end(&mut rest).await.unwrap();
+        let x = hasher.finalize();
+        assert_eq!(
+            "f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f",
+            format!("{:x}", x)
+        );
+
+        // a buffer that isn't handed out again right away, every other poll_fill_buf is Pending
+        let reader = futures::io::BufReader::with_capacity(64, futures::io::Cursor::new(&src));
+        let reader = test_util::PendingReader::new(test_util::ShortReader::new(reader, 50));
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(reader);
+        let mut lines = 0;
+        loop {
+            line.clear();
+            match hasher.read_line(&mut line).await.unwrap() {
+                0 => break,
+                _ => lines += 1,
+            }
+        }
+        assert_eq!(lines, src.iter().filter(|&&b| b == b'\n').count());
+        assert_eq!(format!("{:x}", hasher.finalize()), sha256_hex(&src));
+
+        // seeking to where the consumer is doesn't count the rest of the buffer as read
+        use futures::io::AsyncSeekExt;
+        let reader = futures::io::BufReader::with_capacity(64, futures::io::Cursor::new(&src));
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(reader);
+        line.clear();
+        hasher.read_line(&mut line).await.unwrap();
+        let at = line.len() as u64;
+        hasher.seek(std::io::SeekFrom::Start(at)).await.unwrap();
+        hasher.read_to_end(&mut rest).await.unwrap();
+        assert_eq!(format!("{:x}", hasher.finalize()), sha256_hex(&src));
+
+        let reader = futures::io::BufReader::with_capacity(64, futures::io::Cursor::new(&src));
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(reader)
+            .with_seek_policy(SeekPolicy::HashOnlyFirstPass);
+        hasher.read_line(&mut line).await.unwrap();
+        hasher.seek(std::io::SeekFrom::Start(0)).await.unwrap();
+        hasher.read_to_end(&mut rest).await.unwrap();
+        assert!(hasher.is_digest_valid());
+        assert_eq!(format!("{:x}", hasher.finalize()), sha256_hex(&src));
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "tokio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_read_hasher_buf_read_tokio() {
+        extern crate sha2;
+        use tokio::io::{AsyncBufReadExt, AsyncReadExt};
+        let src = std::fs::read("LICENSE").unwrap();
+        let reader = tokio::io::BufReader::with_capacity(64, std::io::Cursor::new(&src));
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(reader);
+        let mut line = String::new();
+        hasher.read_line(&mut line).await.unwrap();
+        assert_eq!(line, " The MIT License (MIT)\n");
+        let mut buf = [0u8; 100];
+        hasher.read_exact(&mut buf).await.unwrap();
+        hasher.read_line(&mut line).await.unwrap();
+        let mut rest = Vec::new();
+        hasher.read_to_end(&mut rest).await.unwrap();
+        assert_eq!(format!("{:x}", hasher.finalize()), sha256_hex(&src));
+
+        let reader = tokio::io::BufReader::with_capacity(64, std::io::Cursor::new(&src));
+        let reader = test_util::PendingReader::new(test_util::ShortReader::new(reader, 50));
+        let hasher = ReadHasher::<sha2::Sha256, _>::new(reader)
+            .with_expected_hex("f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f")
+            .unwrap();
+        let mut lines = hasher.lines();
+        let mut count = 0;
+        while lines.next_line().await.unwrap().is_some() {
+            count += 1;
+        }
+        assert_eq!(count, src.iter().filter(|&&b| b == b'\n').count());
+        assert_eq!(
+            format!("{:x}", lines.into_inner().finalize()),
+            sha256_hex(&src)
+        );
+
+        // seeking to where the consumer is doesn't count the rest of the buffer as read
+        use tokio::io::AsyncSeekExt;
+        let reader = tokio::io::BufReader::with_capacity(64, std::io::Cursor::new(&src));
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(reader);
+        line.clear();
+        hasher.read_line(&mut line).await.unwrap();
+        let at = line.len() as u64;
+        hasher.seek(std::io::SeekFrom::Start(at)).await.unwrap();
+        hasher.read_to_end(&mut rest).await.unwrap();
+        assert_eq!(format!("{:x}", hasher.finalize()), sha256_hex(&src));
+
+        let reader = tokio::io::BufReader::with_capacity(64, std::io::Cursor::new(&src));
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(reader)
+            .with_seek_policy(SeekPolicy::HashOnlyFirstPass);
+        hasher.read_line(&mut line).await.unwrap();
+        hasher.seek(std::io::SeekFrom::Start(0)).await.unwrap();
+        hasher.read_to_end(&mut rest).await.unwrap();
+        assert!(hasher.is_digest_valid());
+        assert_eq!(format!("{:x}", hasher.finalize()), sha256_hex(&src));
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_hash_chain() {
+        extern crate sha2;
+        let src = std::fs::read("LICENSE").unwrap();
+        let segments = [&src[..100], &src[100..101], &src[101..]];
+        let x = hash_chain::<sha2::Sha256, _>(segments).unwrap();
+        assert_eq!(
+            "f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f",
+            format!("{:x}", x)
+        );
+
+        let x = hash_chain::<sha2::Sha256, &[u8]>([]).unwrap();
+        assert_eq!(
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            format!("{:x}", x)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_hash_chain_error() {
+        extern crate sha2;
+        struct Failing;
+        impl std::io::Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("failing"))
+            }
+        }
+        let segments: [Box<dyn std::io::Read>; 3] = [
+            Box::new(&b"foo"[..]),
+            Box::new(Failing),
+            Box::new(&b"bar"[..]),
+        ];
+        let err = hash_chain::<sha2::Sha256, _>(segments).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::Other);
+        assert_eq!(err.to_string(), "failing");
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "tokio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_hash_chain_tokio() {
+        extern crate sha2;
+        let src = std::fs::read("LICENSE").unwrap();
+        let segments = [&src[..100], &src[100..101], &src[101..]];
+        let x = hash_chain_async::<sha2::Sha256, _, _>(segments)
+            .await
+            .unwrap();
+        assert_eq!(
+            "f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f",
+            format!("{:x}", x)
+        );
+
+        let mut hasher = ChainedReadHasher::<sha2::Sha256, _>::new(ReadChain::new(segments));
+        let mut dest = Vec::new();
+        tokio::io::copy(&mut hasher, &mut dest).await.unwrap();
+        assert_eq!(dest, src);
+        assert_eq!(
+            "f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f",
+            format!("{:x}", hasher.finalize())
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_read_hasher_seek_policy() {
+        extern crate sha2;
+        use std::io::{Read, Seek, SeekFrom};
+        let src = std::fs::read("LICENSE").unwrap();
+        let mut buf = [0u8; 300];
+
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(std::io::Cursor::new(&src));
+        hasher.read_exact(&mut buf).unwrap();
+        assert_eq!(hasher.stream_position().unwrap(), 300);
+        assert_eq!(
+            hasher.seek(SeekFrom::Start(0)).unwrap_err().kind(),
+            std::io::ErrorKind::Unsupported
+        );
+
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(std::io::Cursor::new(&src))
+            .with_seek_policy(SeekPolicy::InvalidateDigest);
+        hasher.read_exact(&mut buf).unwrap();
+        hasher.seek(SeekFrom::Start(300)).unwrap();
+        assert!(hasher.is_digest_valid());
+        hasher.seek(SeekFrom::Current(-10)).unwrap();
+        assert!(!hasher.is_digest_valid());
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_read_hasher_hash_only_first_pass() {
+        extern crate sha2;
+        use std::io::{Read, Seek, SeekFrom};
+        let src = std::fs::read("LICENSE").unwrap();
+        let mut buf = [0u8; 300];
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(std::io::Cursor::new(&src))
+            .with_seek_policy(SeekPolicy::HashOnlyFirstPass)
+            .with_expected_hex("f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f")
+            .unwrap();
+        hasher.read_exact(&mut buf).unwrap();
+        hasher.seek(SeekFrom::Start(100)).unwrap();
+        hasher.read_exact(&mut buf).unwrap();
+        hasher.seek(SeekFrom::Current(-350)).unwrap();
+        hasher.read_exact(&mut buf[..10]).unwrap();
+        std::io::copy(&mut hasher, &mut std::io::sink()).unwrap();
+        assert!(hasher.is_digest_valid());
+        assert_eq!(
+            "f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f",
+            format!("{:x}", hasher.finalize())
+        );
+
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(std::io::Cursor::new(&src))
+            .with_seek_policy(SeekPolicy::HashOnlyFirstPass)
+            .with_expected_hex("f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f")
+            .unwrap();
+        hasher.seek(SeekFrom::End(-50)).unwrap();
+        let err = std::io::copy(&mut hasher, &mut std::io::sink()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(!hasher.is_digest_valid());
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "tokio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_read_hasher_hash_only_first_pass_tokio() {
+        extern crate sha2;
+        use tokio::io::{AsyncReadExt, AsyncSeekExt};
+        let src = tokio::fs::File::open("LICENSE").await.unwrap();
+        let mut hasher =
+            ReadHasher::<sha2::Sha256, _>::new(src).with_seek_policy(SeekPolicy::HashOnlyFirstPass);
+        let mut buf = [0u8; 300];
+        hasher.read_exact(&mut buf).await.unwrap();
+        hasher.seek(std::io::SeekFrom::Start(7)).await.unwrap();
+        hasher.read_exact(&mut buf).await.unwrap();
+        hasher.rewind().await.unwrap();
+        let x = hasher.finalize_ensure_eof_async().await.unwrap();
+        assert_eq!(
+            "f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f",
+            format!("{:x}", x)
+        );
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "tokio-blocking")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_blocking_write_hasher() {
+        extern crate sha2;
+        let src = std::fs::read("LICENSE").unwrap();
+        let expected = src.clone();
+        let mut writer = BlockingWriteHasher::<sha2::Sha256, _>::new(Vec::new());
+        let (writer, dest) = tokio::task::spawn_blocking(move || {
+            use std::io::Write;
+            // tar style, fixed size header blocks followed by the padded contents
+            let mut header = [0u8; 512];
+            header[..7].copy_from_slice(b"LICENSE");
+            writer.write_all(&header).unwrap();
+            for chunk in src.chunks(100) {
+                writer.write_all(chunk).unwrap();
+            }
+            writer
+                .write_all(&[0u8; 512][..512 - src.len() % 512])
+                .unwrap();
+            writer.flush().unwrap();
+            let dest = writer.finish().unwrap();
+            (writer, dest)
+        })
+        .await
+        .unwrap();
+        assert_eq!(dest.len(), 512 * 4);
+        assert_eq!(&dest[..7], b"LICENSE");
+        assert_eq!(&dest[512..512 + expected.len()], &expected[..]);
+        let x = writer.finalize();
+        assert_eq!(
+            format!("{:x}", x),
+            format!("{:x}", <sha2::Sha256 as sha2::Digest>::digest(&dest))
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[cfg(feature = "tokio-blocking")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_blocking_write_hasher_cancel() {
+        extern crate sha2;
+        use std::io::Write;
+        use std::time::Duration;
+        // a destination that never accepts anything, so the channel fills up and the blocking
+        // side ends up waiting on it
+        let (stalled, _keep) = tokio::io::duplex(1);
+        let cancel = CancelHandle::new();
+        let mut writer =
+            BlockingWriteHasher::new_with_cancel(stalled, sha2::Sha256::default(), cancel.clone());
+        let copy = tokio::task::spawn_blocking(move || {
+            let chunk = [0u8; 64];
+            let err = loop {
+                if let Err(err) = writer.write(&chunk) {
+                    break err;
+                }
+            };
+            (writer, err)
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!copy.is_finished());
+        cancel.cancel();
+
+        let (mut writer, err) = tokio::time::timeout(Duration::from_secs(5), copy)
+            .await
+            .expect("the blocking side should stop waiting on the channel")
+            .unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::Other);
+        assert!(matches!(
+            err.into_inner()
+                .unwrap()
+                .downcast::<HashWriterError>()
+                .as_deref(),
+            Ok(HashWriterError::Cancelled)
+        ));
+        let writer = tokio::task::spawn_blocking(move || {
+            assert!(writer.write(b"more").is_err());
+            assert!(writer.flush().is_err());
+            // the task has already exited, so this doesn't wait on the stalled writer
+            writer.finish().unwrap_err();
+            writer
+        });
+        let writer = tokio::time::timeout(Duration::from_secs(5), writer)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(
+            writer.try_finalize(),
+            Err(HashWriterError::Cancelled)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(feature = "crc32c")]
+    fn test_write_hasher_finalize_reset() {
+        use std::io::Write;
+        let mut hasher = WriteHasher::<crc32c::Crc32c, _>::new(Vec::new());
+        hasher.write_all(b"foo").unwrap();
+        assert_eq!(
+            hasher.finalize_reset(),
+            hash_bytes::<crc32c::Crc32c>(b"foo")
+        );
+        assert_eq!(hasher.bytes_written(), 0);
+        hasher.write_all(b"bar").unwrap();
+        let (bar, written) = hasher.finalize_into_parts();
+        assert_eq!(bar, hash_bytes::<crc32c::Crc32c>(b"bar"));
+        assert_eq!(written, b"foobar");
+
+        #[cfg(any(feature = "sha2", feature = "digest"))]
+        {
+            extern crate sha2;
+            let mut hasher = WriteHasher::<sha2::Sha256, _>::new(std::io::sink());
+            hasher.write_all(b"foo").unwrap();
+            assert_eq!(format!("{:x}", hasher.finalize_reset()), sha256_hex(b"foo"));
+            hasher.write_all(b"bar").unwrap();
+            assert_eq!(format!("{:x}", hasher.finalize()), sha256_hex(b"bar"));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(feature = "crc32c")]
+    fn test_write_hasher_digest_so_far() {
+        use std::io::Write;
+        let src = std::fs::read("LICENSE").unwrap();
+        let (first, second) = src.split_at(src.len() / 2);
+        let mut hasher = WriteHasher::<crc32c::Crc32c, _>::new(std::io::sink());
+        hasher.write_all(first).unwrap();
+        assert_eq!(hasher.digest_so_far(), hash_bytes::<crc32c::Crc32c>(first));
+        hasher.write_all(second).unwrap();
+        assert_eq!(hasher.finalize(), hash_bytes::<crc32c::Crc32c>(&src));
+
+        #[cfg(any(feature = "sha2", feature = "digest"))]
+        {
+            extern crate sha2;
+            let mut hasher = WriteHasher::<sha2::Sha256, _>::new(std::io::sink());
+            hasher.write_all(first).unwrap();
+            assert_eq!(format!("{:x}", hasher.digest_so_far()), sha256_hex(first));
+            hasher.write_all(second).unwrap();
+            assert_eq!(format!("{:x}", hasher.finalize()), sha256_hex(&src));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(feature = "crc32c")]
+    fn test_write_hasher_checkpoint() {
+        use std::io::Write;
+        let src = b"0123456789abcdef";
+        let sizes = [3, 4, 9];
+        let expected: Vec<_> = [10]
+            .iter()
+            .map(|&offset| {
+                (
+                    offset,
+                    hash_bytes::<crc32c::Crc32c>(&src[..offset as usize]),
+                )
+            })
+            .collect();
+        let mut checkpoints = Vec::new();
+        let mut hasher = WriteHasher::<crc32c::Crc32c, _>::new(Vec::new())
+            .with_checkpoint(10, |offset, crc| checkpoints.push((offset, crc)));
+        let mut rest = &src[..];
+        for size in sizes {
+            let (chunk, tail) = rest.split_at(size);
+            hasher.write_all(chunk).unwrap();
+            rest = tail;
+        }
+        assert_eq!(hasher.finalize(), hash_bytes::<crc32c::Crc32c>(src));
+        assert_eq!(checkpoints, expected);
+
+        // 3 + 4 + 9 + 3 + 4 + 9 crosses two boundaries, one write reaching 20 exactly
+        let mut checkpoints = Vec::new();
+        let mut hasher = WriteHasher::<crc32c::Crc32c, _>::new(std::io::sink())
+            .with_checkpoint(10, |offset, _| checkpoints.push(offset));
+        for size in sizes.iter().chain(&sizes) {
+            hasher.write_all(&vec![0; *size]).unwrap();
+        }
+        hasher.finalize();
+        assert_eq!(checkpoints, [10, 20, 30]);
+
+        // the offsets count hashed bytes, the paused write doesn't move them
+        let mut checkpoints = Vec::new();
+        let mut hasher = WriteHasher::<crc32c::Crc32c, _>::new(std::io::sink())
+            .with_checkpoint(10, |offset, crc| checkpoints.push((offset, crc)));
+        hasher.write_all(&src[..6]).unwrap();
+        hasher.pause_hashing();
+        hasher.write_all(b"unhashed").unwrap();
+        hasher.resume_hashing();
+        hasher.write_all(&src[6..]).unwrap();
+        assert_eq!(hasher.bytes_written(), 24);
+        assert_eq!(hasher.finalize(), hash_bytes::<crc32c::Crc32c>(src));
+        assert_eq!(checkpoints, expected);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "tokio")]
+    #[cfg(feature = "crc32c")]
+    async fn test_write_hasher_checkpoint_tokio() {
+        use tokio::io::AsyncWriteExt;
+        let src = b"0123456789abcdef";
+        let sizes = [3, 4, 9];
+        let expected: Vec<_> = [10]
+            .iter()
+            .map(|&offset| {
+                (
+                    offset,
+                    hash_bytes::<crc32c::Crc32c>(&src[..offset as usize]),
+                )
+            })
+            .collect();
+        let mut checkpoints = Vec::new();
+        let mut hasher = WriteHasher::<crc32c::Crc32c, _>::new(Vec::new())
+            .with_checkpoint(10, |offset, crc| checkpoints.push((offset, crc)));
+        let mut rest = &src[..];
+        for size in sizes {
+            let (chunk, tail) = rest.split_at(size);
+            hasher.write_all(chunk).await.unwrap();
+            rest = tail;
+        }
+        assert_eq!(hasher.finalize(), hash_bytes::<crc32c::Crc32c>(src));
+        assert_eq!(checkpoints, expected);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "futures")]
+    #[cfg(feature = "crc32c")]
+    async fn test_write_hasher_checkpoint_futures() {
+        use futures::io::AsyncWriteExt;
+        let src = b"0123456789abcdef";
+        let sizes = [3, 4, 9];
+        let expected: Vec<_> = [10]
+            .iter()
+            .map(|&offset| {
+                (
+                    offset,
+                    hash_bytes::<crc32c::Crc32c>(&src[..offset as usize]),
+                )
+            })
+            .collect();
+        let mut checkpoints = Vec::new();
+        let mut hasher = WriteHasher::<crc32c::Crc32c, _>::new(futures::io::sink())
+            .with_checkpoint(10, |offset, crc| checkpoints.push((offset, crc)));
+        let mut rest = &src[..];
+        for size in sizes {
+            let (chunk, tail) = rest.split_at(size);
+            hasher.write_all(chunk).await.unwrap();
+            rest = tail;
+        }
+        assert_eq!(hasher.finalize(), hash_bytes::<crc32c::Crc32c>(src));
+        assert_eq!(checkpoints, expected);
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(feature = "crc32c")]
+    fn test_dyn_min_digest() {
+        use std::io::Write;
+        fn by_name(name: &str) -> Option<Box<dyn DynMinDigest>> {
+            Some(match name {
+                #[cfg(any(feature = "sha2", feature = "digest"))]
+                "sha256" => Box::new(<sha2::Sha256 as Default>::default()),
+                "crc32c" => Box::new(crc32c::Crc32c::new()),
+                _ => return None,
+            })
+        }
+        extern crate sha2;
+        let src = std::fs::read("LICENSE").unwrap();
+        let algorithms = [
+            (
+                "crc32c",
+                helpers::encode_hex(&hash_bytes::<crc32c::Crc32c>(&src).to_be_bytes()),
+            ),
+            #[cfg(any(feature = "sha2", feature = "digest"))]
+            ("sha256", sha256_hex(&src)),
+        ];
+        for (name, expected) in algorithms {
+            let mut hasher = WriteHasher::new_with_hasher(std::io::sink(), by_name(name).unwrap());
+            hasher.write_all(&src).unwrap();
+            assert_eq!(helpers::encode_hex(&hasher.finalize()), expected);
+        }
+        assert!(by_name("md6").is_none());
+    }
+
+    #[test]
+    #[cfg(all(feature = "stdio", feature = "digest"))]
+    fn test_boxed_dyn_digest() {
+        extern crate sha2;
+        use digest::DynDigest;
+        fn by_name(name: &str) -> Option<Box<dyn DynDigest>> {
+            match name {
+                "sha256" => Some(Box::new(sha2::Sha256::default())),
+                "sha512" => Some(Box::new(sha2::Sha512::default())),
+                _ => None,
+            }
+        }
+        let mut src = std::fs::File::open("LICENSE").unwrap();
+        let hasher = BoxedDynDigest::new(by_name("sha256").unwrap());
+        let mut hasher = WriteHasher::new_with_hasher(std::io::sink(), hasher);
+        std::io::copy(&mut src, &mut hasher).unwrap();
+        assert_eq!(hasher.digest_so_far().len(), 32);
+        assert_eq!(
+            helpers::encode_hex(&hasher.finalize()),
+            "f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f"
+        );
+        assert!(by_name("md6").is_none());
+    }
+
+    #[test]
+    fn test_any_hasher() {
+        fn check<D: MinDigest + Default>(names: &[&str], canonical: &str)
+        where
+            D::Output: OutputBytes,
+        {
+            let src = std::fs::read("LICENSE").unwrap();
+            let expected = hash_bytes::<D>(&src).to_bytes().into_owned();
+            for name in names {
+                let mut hasher: AnyHasher = name.parse().unwrap();
+                assert_eq!(hasher.name(), canonical);
+                hasher.update(&src);
+                let digest = hasher.finalize();
+                assert_eq!(digest.algorithm, canonical);
+                assert_eq!(digest.bytes, expected, "{}", name);
+            }
+        }
+        #[cfg(feature = "crc32c")]
+        check::<crc32c::Crc32c>(&["crc32c", "CRC-32C", "crc_32c"], "crc32c");
+        check::<crc24::Crc24>(&["crc24", "CRC-24"], "crc24");
+        #[cfg(feature = "adler")]
+        check::<adler32::Adler32>(&["adler32", "Adler-32"], "adler32");
+        #[cfg(feature = "crc64")]
+        {
+            check::<crc64::Crc64Xz>(&["crc64xz", "CRC-64/XZ", "crc64"], "crc64xz");
+            check::<crc64::Crc64Nvme>(&["crc64nvme", "CRC-64/NVME"], "crc64nvme");
+        }
+        #[cfg(feature = "sha1")]
+        check::<sha1::Sha1>(&["sha1", "SHA-1"], "sha1");
+        #[cfg(feature = "sha2")]
+        {
+            check::<sha2::Sha224>(&["sha224", "SHA-224"], "sha224");
+            check::<sha2::Sha256>(&["sha256", "sha-256", "SHA256"], "sha256");
+            check::<sha2::Sha384>(&["sha384", "SHA-384"], "sha384");
+            check::<sha2::Sha512>(&["sha512", "SHA-512"], "sha512");
+            check::<sha2::Sha512_224>(&["sha512/224", "SHA-512/224", "sha512_224"], "sha512/224");
+            check::<sha2::Sha512_256>(&["sha512/256", "SHA-512/256", "sha512_256"], "sha512/256");
+        }
+        #[cfg(feature = "md2")]
+        check::<md2::Md2>(&["md2", "MD2"], "md2");
+        #[cfg(feature = "md4")]
+        check::<md4::Md4>(&["md4", "MD4"], "md4");
+        #[cfg(feature = "md5")]
+        {
+            let mut hasher: AnyHasher = "MD5".parse().unwrap();
+            hasher.update(b"abc");
+            let expected = md5::compute(b"abc");
+            assert_eq!(hasher.finalize().bytes, expected.0);
+        }
+        #[cfg(feature = "blake2")]
+        {
+            check::<blake2::Blake2b512>(&["blake2b512", "BLAKE2b", "blake2b-512"], "blake2b512");
+            check::<blake2::Blake2s256>(&["blake2s256", "BLAKE2s", "blake2s-256"], "blake2s256");
+        }
+        #[cfg(feature = "blake3")]
+        check::<blake3::Hasher>(&["blake3", "BLAKE3"], "blake3");
+        #[cfg(feature = "crc32fast")]
+        check::<crc32fast::Hasher>(&["crc32", "CRC-32"], "crc32");
+        #[cfg(feature = "xxh3")]
+        {
+            check::<xxh3::Xxh3_64>(&["xxh3_64", "xxh3", "XXH3-64"], "xxh3_64");
+            check::<xxh3::Xxh3_128>(&["xxh3_128", "XXH3-128"], "xxh3_128");
+        }
+        #[cfg(feature = "xxh64")]
+        check::<xxh64::Xxh64>(&["xxh64", "XXH64"], "xxh64");
+
+        assert!(matches!(
+            "md6".parse::<AnyHasher>(),
+            Err(HashWriterError::Parse(_))
+        ));
+        #[cfg(not(feature = "sha2"))]
+        assert!("sha256".parse::<AnyHasher>().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_verifying_write_hasher() {
+        extern crate sha2;
+        use std::io::Write;
+        let src = std::fs::read("LICENSE").unwrap();
+        let expected = "f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f";
+        let mut writer = WriteHasher::<sha2::Sha256, _>::new(Vec::new())
+            .with_expected_hex(expected)
+            .unwrap();
+        writer.write_all(&src).unwrap();
+        assert_eq!(writer.finalize_verify().unwrap(), src);
+
+        let mut writer = WriteHasher::<sha2::Sha256, _>::new(Vec::new())
+            .with_expected_hex(expected)
+            .unwrap();
+        writer.write_all(&src[1..]).unwrap();
+        let err = writer.finalize_verify().unwrap_err();
+        assert!(err.to_string().contains(expected), "{}", err);
+        assert!(matches!(err, HashWriterError::Mismatch { .. }));
+
+        // a truncated expected digest never matches
+        let mut writer = WriteHasher::<sha2::Sha256, _>::new(Vec::new())
+            .with_expected_hex(&expected[..62])
+            .unwrap();
+        writer.write_all(&src).unwrap();
+        assert!(matches!(
+            writer.finalize_verify(),
+            Err(HashWriterError::Mismatch { expected, .. }) if expected.len() == 31
+        ));
+        assert!(matches!(
+            WriteHasher::<sha2::Sha256, _>::new(Vec::<u8>::new())
+                .with_expected_hex(&expected[..63]),
+            Err(HashWriterError::Parse(_))
+        ));
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "tokio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_verifying_write_hasher_tokio() {
+        extern crate sha2;
+        use tokio::io::AsyncWriteExt;
+        let src = std::fs::read("LICENSE").unwrap();
+        let expected = "f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f";
+        let mut writer = WriteHasher::<sha2::Sha256, _>::new(Vec::new())
+            .with_expected_hex(expected)
+            .unwrap()
+            .verify_on_shutdown();
+        writer.write_all(&src).await.unwrap();
+        writer.shutdown().await.unwrap();
+        assert_eq!(writer.finalize_verify().unwrap(), src);
+
+        let mut writer = WriteHasher::<sha2::Sha256, _>::new(Vec::new())
+            .with_expected_hex(expected)
+            .unwrap()
+            .verify_on_shutdown();
+        writer.write_all(&src[1..]).await.unwrap();
+        let err = writer.shutdown().await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "futures")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_verifying_write_hasher_futures() {
+        extern crate sha2;
+        use futures::io::AsyncWriteExt;
+        let src = std::fs::read("LICENSE").unwrap();
+        let expected = "f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f";
+        let mut writer = WriteHasher::<sha2::Sha256, _>::new(futures::io::sink())
+            .with_expected_hex(expected)
+            .unwrap()
+            .verify_on_shutdown();
+        writer.write_all(&src[1..]).await.unwrap();
+        let err = writer.close().await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "tokio-offload")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_offload_write_hasher() {
+        extern crate sha2;
+        use test_util::*;
+        use tokio::io::AsyncWriteExt;
+        let src = std::fs::read("LICENSE").unwrap();
+
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(PendingWriter::new(Vec::new()))
+            .with_blocking_threshold(64);
+        // small writes hashed inline between large ones hashed on the blocking pool
+        for (i, chunk) in src.chunks(100).enumerate() {
+            let (small, large) = chunk.split_at(if i % 2 == 0 { 10 } else { 90 });
+            hasher.write_all(small).await.unwrap();
+            hasher.write_all(large).await.unwrap();
+        }
+        hasher.flush().await.unwrap();
+        assert_eq!(hasher.bytes_written(), src.len() as u64);
+        let (digest, written) = hasher.finalize_into_parts().await.unwrap();
+        assert_eq!(written.into_inner(), src);
+        assert_eq!(format!("{:x}", digest), sha256_hex(&src));
+
+        // the same with short writes, and an update still in flight at finalize
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(ShortWriter::new(Vec::new(), 300))
+            .with_blocking_threshold(0);
+        hasher.write_all(&src).await.unwrap();
+        let digest = hasher.finalize().await.unwrap();
+        assert_eq!(digest, hash_bytes::<sha2::Sha256>(&src));
+
+        // a pause and the counts carry over into the offloading writer
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(Vec::new());
+        hasher.pause_hashing();
+        hasher.write_all(b"HDR\0").await.unwrap();
+        let mut hasher = hasher.with_blocking_threshold(64);
+        assert!(hasher.is_hashing_paused());
+        hasher.write_all(&[0; 100]).await.unwrap();
+        hasher.resume_hashing();
+        hasher.write_all(&src).await.unwrap();
+        assert_eq!(hasher.bytes_written(), src.len() as u64 + 104);
+        assert_eq!(hasher.bytes_hashed(), src.len() as u64);
+        assert_eq!(
+            hasher.try_finalize().await.unwrap(),
+            hash_bytes::<sha2::Sha256>(&src)
+        );
+
+        // so does seeking away from the hashed data
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(std::io::Cursor::new(Vec::new()));
+        hasher.write_all(&src).await.unwrap();
+        tokio::io::AsyncSeekExt::rewind(&mut hasher).await.unwrap();
+        let hasher = hasher.with_blocking_threshold(64);
+        assert!(!hasher.is_digest_linear());
+        assert!(matches!(
+            hasher.try_finalize().await,
+            Err(HashWriterError::Invalidated)
+        ));
+
+        // and after converting, through the offloading writer's own seeks
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(std::io::Cursor::new(Vec::new()))
+            .with_blocking_threshold(64);
+        hasher.write_all(&src).await.unwrap();
+        tokio::io::AsyncSeekExt::seek(&mut hasher, std::io::SeekFrom::End(0))
+            .await
+            .unwrap();
+        assert!(hasher.is_digest_linear());
+        tokio::io::AsyncSeekExt::rewind(&mut hasher).await.unwrap();
+        assert!(!hasher.is_digest_linear());
+
+        // vectored writes are passed on as such, a large one is hashed on the blocking pool
+        // with the parts outside the hash ranges left out
+        let sink = ShortWriter::new(Vec::new(), 300);
+        let hasher =
+            WriteHasher::<sha2::Sha256, _>::new(sink).with_hash_ranges(vec![4..400, 600..700]);
+        let mut hasher = hasher.with_blocking_threshold(64);
+        assert!(tokio::io::AsyncWrite::is_write_vectored(&hasher));
+        let mut written = 0;
+        while written < src.len() {
+            let (head, tail) = src[written..].split_at((src.len() - written).min(50));
+            let bufs = [std::io::IoSlice::new(head), std::io::IoSlice::new(tail)];
+            written += hasher.write_vectored(&bufs).await.unwrap();
+        }
+        assert_eq!(hasher.bytes_hashed(), 496);
+        assert_eq!(
+            hasher.finalize().await.unwrap(),
+            hash_bytes::<sha2::Sha256>([&src[4..400], &src[600..700]].concat())
+        );
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "async-digest")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_async_backend_write_hasher() {
+        extern crate sha2;
+        use test_util::*;
+        use tokio::io::AsyncWriteExt;
+        let src = std::fs::read("LICENSE").unwrap();
+
+        let backend = Loopback::new(sha2::Sha256::default(), 3);
+        let mut hasher = AsyncBackendWriteHasher::new_with_hasher(
+            PendingWriter::new(ShortWriter::new(Vec::new(), 100)),
+            backend,
+        );
+        for chunk in src.chunks(300) {
+            hasher.write_all(chunk).await.unwrap();
+        }
+        hasher.shutdown().await.unwrap();
+        assert_eq!(hasher.get_ref().get_ref().get_ref(), &src);
+        assert_eq!(sha256_hex(&src), format!("{:x}", hasher.finalize().await));
+
+        // the same through the object safe trait, with an update still in flight at finalize
+        let backend: Box<dyn DynAsyncMinDigest<Output = _>> =
+            Box::new(Loopback::new(sha2::Sha256::default(), 10));
+        let mut hasher = AsyncBackendWriteHasher::new_with_hasher(Vec::new(), backend);
+        hasher.write_all(&src).await.unwrap();
+        let mut sync_hasher = WriteHasher::<sha2::Sha256, _>::new(Vec::new());
+        tokio::io::AsyncWriteExt::write_all(&mut sync_hasher, &src)
+            .await
+            .unwrap();
+        assert_eq!(hasher.finalize().await, sync_hasher.finalize());
+    }
+
+    #[cfg(any(feature = "stdio", feature = "tokio", feature = "futures"))]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn sha256_hex(data: &[u8]) -> String {
+        extern crate sha2;
+        format!("{:x}", <sha2::Sha256 as sha2::Digest>::digest(data))
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_misbehaving_writers() {
+        extern crate sha2;
+        use std::io::Write;
+        use test_util::*;
+        let src = std::fs::read("LICENSE").unwrap();
+
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(ShortWriter::new(Vec::new(), 7));
+        hasher.write_all(&src).unwrap();
+        assert_eq!(hasher.inner.get_ref(), &src);
+        assert_eq!(sha256_hex(&src), format!("{:x}", hasher.finalize()));
+
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(InterruptingWriter::new(Vec::new()));
+        hasher.write_all(&src).unwrap();
+        assert_eq!(sha256_hex(&src), format!("{:x}", hasher.finalize()));
+
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(PendingWriter::new(Vec::new()));
+        let err = hasher.write(&src).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::WouldBlock);
+        assert_eq!(hasher.write(&src).unwrap(), src.len());
+        assert_eq!(sha256_hex(&src), format!("{:x}", hasher.finalize()));
+
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(FailAfter::new(Vec::new(), 500));
+        hasher.write_all(&src).unwrap_err();
+        let accepted = hasher.inner.get_ref().clone();
+        assert_eq!(accepted, &src[..500]);
+        assert_eq!(sha256_hex(&accepted), format!("{:x}", hasher.finalize()));
+
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(ZeroAfter::new(Vec::new(), 500));
+        let err = hasher.write_all(&src).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::WriteZero);
+        assert_eq!(sha256_hex(&src[..500]), format!("{:x}", hasher.finalize()));
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_misbehaving_readers() {
+        extern crate sha2;
+        use std::io::Read;
+        use test_util::*;
+        let src = std::fs::read("LICENSE").unwrap();
+
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(ShortReader::new(&src[..], 7));
+        let mut dest = Vec::new();
+        hasher.read_to_end(&mut dest).unwrap();
+        assert_eq!(dest, src);
+        assert_eq!(sha256_hex(&src), format!("{:x}", hasher.finalize()));
+
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(InterruptingReader::new(&src[..]));
+        let mut dest = Vec::new();
+        hasher.read_to_end(&mut dest).unwrap();
+        assert_eq!(sha256_hex(&src), format!("{:x}", hasher.finalize()));
+
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(FailAfter::new(&src[..], 500));
+        let mut dest = Vec::new();
+        hasher.read_to_end(&mut dest).unwrap_err();
+        assert_eq!(dest.len(), 500);
+        assert_eq!(sha256_hex(&dest), format!("{:x}", hasher.finalize()));
+
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(ZeroAfter::new(&src[..], 500));
+        let mut dest = Vec::new();
+        hasher.read_to_end(&mut dest).unwrap();
+        assert_eq!(sha256_hex(&src[..500]), format!("{:x}", hasher.finalize()));
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "tokio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_misbehaving_tokio() {
+        extern crate sha2;
+        use test_util::*;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let src = std::fs::read("LICENSE").unwrap();
+
+        let sink = PendingWriter::new(ShortWriter::new(Vec::new(), 13));
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(sink);
+        hasher.write_all(&src).await.unwrap();
+        assert_eq!(hasher.inner.get_ref().get_ref(), &src);
+        assert_eq!(sha256_hex(&src), format!("{:x}", hasher.finalize()));
+
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(FailAfter::new(Vec::new(), 500));
+        hasher.write_all(&src).await.unwrap_err();
+        assert_eq!(sha256_hex(&src[..500]), format!("{:x}", hasher.finalize()));
+
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(ZeroAfter::new(Vec::new(), 500));
+        hasher.write_all(&src).await.unwrap_err();
+        assert_eq!(sha256_hex(&src[..500]), format!("{:x}", hasher.finalize()));
+
+        let source = PendingReader::new(ShortReader::new(&src[..], 13));
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(source);
+        let mut dest = Vec::new();
+        hasher.read_to_end(&mut dest).await.unwrap();
+        assert_eq!(dest, src);
+        assert_eq!(sha256_hex(&src), format!("{:x}", hasher.finalize()));
+
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(FailAfter::new(&src[..], 500));
+        let mut dest = Vec::new();
+        hasher.read_to_end(&mut dest).await.unwrap_err();
+        assert_eq!(sha256_hex(&src[..500]), format!("{:x}", hasher.finalize()));
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "futures")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_misbehaving_futures() {
+        extern crate sha2;
+        use futures::io::{AsyncReadExt, AsyncWriteExt};
+        use test_util::*;
+        let src = std::fs::read("LICENSE").unwrap();
+
+        let sink = PendingWriter::new(ShortWriter::new(Vec::new(), 13));
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(sink);
+        hasher.write_all(&src).await.unwrap();
+        assert_eq!(hasher.inner.get_ref().get_ref(), &src);
+        assert_eq!(sha256_hex(&src), format!("{:x}", hasher.finalize()));
+
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(FailAfter::new(Vec::new(), 500));
+        hasher.write_all(&src).await.unwrap_err();
+        assert_eq!(sha256_hex(&src[..500]), format!("{:x}", hasher.finalize()));
+
+        let source = PendingReader::new(ShortReader::new(&src[..], 13));
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(source);
+        let mut dest = Vec::new();
+        hasher.read_to_end(&mut dest).await.unwrap();
+        assert_eq!(dest, src);
+        assert_eq!(sha256_hex(&src), format!("{:x}", hasher.finalize()));
+
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(ZeroAfter::new(&src[..], 500));
+        let mut dest = Vec::new();
+        hasher.read_to_end(&mut dest).await.unwrap();
+        assert_eq!(sha256_hex(&src[..500]), format!("{:x}", hasher.finalize()));
+    }
+
+    #[test]
+    #[cfg(all(feature = "stdio", feature = "ascon"))]
+    fn test_ascon() {
+        use digest::XofReader;
+        use std::io::Write;
+        let msg = (0u8..=0x20).collect::<Vec<u8>>();
+        let mut hasher = WriteHasher::<ascon_hash::AsconHash256, _>::new(std::io::sink());
+        for chunk in msg.chunks(7) {
+            hasher.write_all(chunk).unwrap();
+        }
+        assert_eq!(
+            "a58665a2cb9530c502096a7957a76e428af4ad044b4da5c471f9da6f7b3e5868",
+            format!("{:x}", hasher.finalize())
+        );
+
+        let hasher = WriteHasher::<ascon_hash::AsconHash256, _>::new(std::io::sink());
+        assert_eq!(
+            "0b3be5850f2f6b98caf29f8fdea89b64a1fa70aa249b8f839bd53baa304d92b2",
+            format!("{:x}", hasher.finalize())
+        );
+
+        let msg = (0u8..=0x12).collect::<Vec<u8>>();
+        let mut hasher = WriteHasher::<ascon_hash::AsconXof128, _>::new(std::io::sink());
+        for chunk in msg.chunks(3) {
+            hasher.write_all(chunk).unwrap();
+        }
+        let mut out = [0u8; 32];
+        hasher.finalize().read(&mut out);
+        assert_eq!(
+            decode_hex("F4B87B886CE28D50BDA038F31593BC6408F177CD10897AE6401A091782D806FE").unwrap(),
+            out
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "stdio", feature = "blake2"))]
+    fn test_blake2_mac() {
+        use blake2::{Blake2bMac512, Blake2sMac256};
+        use std::io::Write;
+        // blake2b-kat.txt and blake2s-kat.txt of the reference implementation, the key is
+        // 0, 1, 2, .. and the input of length n is 0, 1, .., n - 1
+        let cases: [(usize, &str, &str); 5] = [
+            (
+                0,
+                "10ebb67700b1868efb4417987acf4690ae9d972fb7a590c2f02871799aaa4786b5e996e8f0f4eb981fc214b005f42d2ff4233499391653df7aefcbc13fc51568",
+                "48a8997da407876b3d79c0d92325ad3b89cbb754d86ab71aee047ad345fd2c49",
+            ),
+            (
+                1,
+                "961f6dd1e4dd30f63901690c512e78e4b45e4742ed197c3c5e45c549fd25f2e4187b0bc9fe30492b16b0d0bc4ef9b0f34c7003fac09a5ef1532e69430234cebd",
+                "40d15fee7c328830166ac3f918650f807e7e01e177258cdc0a39b11f598066f1",
+            ),
+            (
+                64,
+                "65676d800617972fbd87e4b9514e1c67402b7a331096d3bfac22f1abb95374abc942f16e9ab0ead33b87c91968a6e509e119ff07787b3ef483e1dcdccf6e3022",
+                "8975b0577fd35566d750b362b0897a26c399136df07bababbde6203ff2954ed4",
+            ),
+            (
+                65,
+                "939fa189699c5d2c81ddd1ffc1fa207c970b6a3685bb29ce1d3e99d42f2f7442da53e95a72907314f4588399a3ff5b0a92beb3f6be2694f9f86ecf2952d5b41c",
+                "21fe0ceb0052be7fb0f004187cacd7de67fa6eb0938d927677f2398c132317a8",
+            ),
+            (
+                255,
+                "142709d62e28fcccd0af97fad0f8465b971e82201dc51070faa0372aa43e92484be1c1e73ba10906d5d1853db6a4106e0a7bf9800d373d6dee2d46d62ef2a461",
+                "3fb735061abc519dfe979e54c1ee5bfad0a9d858b3315bad34bde999efd724dd",
+            ),
+        ];
+        let key: Vec<u8> = (0..64).collect();
+        for (len, blake2b, blake2s) in cases {
+            let data: Vec<u8> = (0..len as u8).collect();
+            let blake2b = helpers::decode_hex(blake2b).unwrap();
+            let blake2s = helpers::decode_hex(blake2s).unwrap();
+            for chunk_size in [1, 7, 64] {
+                let blake2b_mac = || WriteHasher::<Blake2bMac512, _>::new_keyed(Vec::new(), &key);
+                let blake2s_mac =
+                    || WriteHasher::<Blake2sMac256, _>::new_keyed(Vec::new(), &key[..32]);
+                let (mut b, mut b_verify) = (blake2b_mac().unwrap(), blake2b_mac().unwrap());
+                let (mut s, mut s_verify) = (blake2s_mac().unwrap(), blake2s_mac().unwrap());
+                for chunk in data.chunks(chunk_size) {
+                    b.write_all(chunk).unwrap();
+                    s.write_all(chunk).unwrap();
+                    b_verify.write_all(chunk).unwrap();
+                    s_verify.write_all(chunk).unwrap();
+                }
+                let (tag, written) = b.finalize_into_parts();
+                assert_eq!(tag.as_slice(), blake2b);
+                assert_eq!(written, data);
+                assert_eq!(s.finalize().as_slice(), blake2s);
+                b_verify.finalize_verify_tag(&blake2b).unwrap();
+                assert!(s_verify.finalize_verify_tag(&blake2b[..32]).is_err());
+            }
+        }
+
+        assert!(WriteHasher::<Blake2bMac512, _>::new_keyed(std::io::sink(), &[0; 65]).is_err());
+        assert!(WriteHasher::<Blake2sMac256, _>::new_keyed(std::io::sink(), &[0; 33]).is_err());
+    }
+
+    #[test]
+    #[cfg(all(feature = "stdio", feature = "blake2"))]
+    fn test_blake2_var() {
+        use blake2::{Blake2bVar, Blake2sVar};
+        use std::io::Write;
+        const FOX: &[u8] = b"The quick brown fox jumps over the lazy dog";
+        // `b2sum -l <bits>` (BLAKE2b) and Python's hashlib.blake2s(digest_size=<bytes>)
+        let blake2b: [(usize, &[u8], &str); 4] = [
+            (1, b"abc", "6b"),
+            (20, b"abc", "384264f676f39536840523f284921cdc68b6846b"),
+            (20, FOX, "3c523ed102ab45a37d54f5610d5a983162fde84f"),
+            (
+                32,
+                FOX,
+                "01718cec35cd3d796dd00020e0bfecb473ad23457d063b75eff29c0ffa2e58a9",
+            ),
+        ];
+        let blake2s: [(usize, &[u8], &str); 3] = [
+            (16, b"abc", "aa4938119b1dc7b87cbad0ffd200d0ae"),
+            (20, FOX, "5a604fec9713c369e84b0ed68daed7d7504ef240"),
+            (
+                32,
+                b"abc",
+                "508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982",
+            ),
+        ];
+        for (len, input, expected) in blake2b {
+            let mut hasher =
+                WriteHasher::<Blake2bVar, _>::new_with_output_size(std::io::sink(), len).unwrap();
+            for chunk in input.chunks(5) {
+                hasher.write_all(chunk).unwrap();
+            }
+            assert_eq!(helpers::encode_hex(&hasher.finalize()), expected);
+        }
+        for (len, input, expected) in blake2s {
+            let mut hasher =
+                WriteHasher::<Blake2sVar, _>::new_with_output_size(std::io::sink(), len).unwrap();
+            for chunk in input.chunks(5) {
+                hasher.write_all(chunk).unwrap();
+            }
+            assert_eq!(helpers::encode_hex(&hasher.finalize()), expected);
+        }
+        // a full size output is the same as the fixed size variant
+        assert_eq!(
+            WriteHasher::<Blake2bVar, _>::new_with_output_size(std::io::sink(), 64)
+                .unwrap()
+                .finalize(),
+            hash_bytes::<blake2::Blake2b512>(b"").to_vec()
+        );
+
+        for len in [0, 65] {
+            assert!(
+                WriteHasher::<Blake2bVar, _>::new_with_output_size(std::io::sink(), len).is_err()
+            );
+        }
+        for len in [0, 33] {
+            assert!(
+                WriteHasher::<Blake2sVar, _>::new_with_output_size(std::io::sink(), len).is_err()
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "stdio", feature = "md-5"))]
+    fn test_md_5() {
+        use std::io::Write;
+        // RFC 1321 A.5
+        let vectors: [(&[u8], &str); 7] = [
+            (b"", "d41d8cd98f00b204e9800998ecf8427e"),
+            (b"a", "0cc175b9c0f1b6a831c399e269772661"),
+            (b"abc", "900150983cd24fb0d6963f7d28e17f72"),
+            (b"message digest", "f96b697d7cb7938d525a2f31aaf161d0"),
+            (
+                b"abcdefghijklmnopqrstuvwxyz",
+                "c3fcd3d76192e4007dfb496cca67e13b",
+            ),
+            (
+                b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789",
+                "d174ab98d277d9f5a5611c2c9f419d9f",
+            ),
+            (
+                b"12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+                "57edf4a22be3c955ac49da2e2107b67a",
+            ),
+        ];
+        for (input, expected) in vectors {
+            let mut hasher = WriteHasher::<::md_5::Md5, _>::new(std::io::sink());
+            for chunk in input.chunks(7) {
+                hasher.write_all(chunk).unwrap();
+            }
+            assert_eq!(format!("{:x}", hasher.finalize()), expected);
+        }
+        #[cfg(feature = "md5")]
+        assert_eq!(
+            hash_bytes::<::md_5::Md5>(b"abc").as_slice(),
+            md5::compute(b"abc").0
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "stdio", feature = "ripemd"))]
+    fn test_ripemd() {
+        fn copy<D: MinDigest + Default>(src: &[u8]) -> String
+        where
+            D::Output: core::fmt::LowerHex,
+        {
+            let mut hasher = WriteHasher::<D, _>::new(std::io::sink());
+            std::io::copy(&mut &src[..], &mut hasher).unwrap();
+            format!("{:x}", hasher.finalize())
+        }
+        assert_eq!(
+            copy::<::ripemd::Ripemd160>(b"abc"),
+            "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc"
+        );
+        assert_eq!(
+            copy::<::ripemd::Ripemd160>(b"message digest"),
+            "5d0689ef49d2fae572b881b123a85ffa21595f36"
+        );
+        assert_eq!(
+            copy::<::ripemd::Ripemd128>(b"abc"),
+            "c14a12199c66e4ba84636b0f69144c77"
+        );
+        assert_eq!(
+            copy::<::ripemd::Ripemd256>(b"abc"),
+            "afbd6e228b9d8cbbcef5ca2d03e6dba10ac0bc7dcbe4680e1e42d2e975459b65"
+        );
+        assert_eq!(
+            copy::<::ripemd::Ripemd320>(b"abc"),
+            "de4c01b3054f8930a79d09ae738e92301e5a17085beffdc1b8d116713e74f82fa942d64cdbc4682d"
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "stdio", feature = "skein"))]
+    fn test_skein() {
+        use std::io::Write;
+        let msg = (0u8..=0xff).rev().collect::<Vec<u8>>();
+
+        let mut hasher = WriteHasher::<::skein::Skein256, _>::new(std::io::sink());
+        hasher.write_all(&[0xff]).unwrap();
+        assert_eq!(
+            "0b98dcd198ea0e50a7a244c444e25c23da30c10fc9a1f270a6637f1f34e67ed2",
+            format!("{:x}", hasher.finalize())
+        );
+        let mut hasher = WriteHasher::<::skein::Skein256, _>::new(std::io::sink());
+        for chunk in msg[..32].chunks(5) {
+            hasher.write_all(chunk).unwrap();
+        }
+        assert_eq!(
+            "8d0fa4ef777fd759dfd4044e6f6a5ac3c774aec943dcfc07927b723b5dbf408b",
+            format!("{:x}", hasher.finalize())
+        );
+
+        let mut hasher = WriteHasher::<::skein::Skein512, _>::new(std::io::sink());
+        hasher.write_all(&[0xff]).unwrap();
+        assert_eq!(
+            "71b7bce6fe6452227b9ced6014249e5bf9a9754c3ad618ccc4e0aae16b316cc8\
+             ca698d864307ed3e80b6ef1570812ac5272dc409b5a012df2a579102f340617a",
+            format!("{:x}", hasher.finalize())
+        );
+        let mut hasher = WriteHasher::<::skein::Skein512, _>::new(std::io::sink());
+        for chunk in msg[..64].chunks(9) {
+            hasher.write_all(chunk).unwrap();
+        }
+        assert_eq!(
+            "45863ba3be0c4dfc27e75d358496f4ac9a736a505d9313b42b2f5eada79fc17f\
+             63861e947afb1d056aa199575ad3f8c9a3cc1780b5e5fa4cae050e989876625b",
+            format!("{:x}", hasher.finalize())
+        );
+
+        let mut hasher = WriteHasher::<::skein::Skein1024, _>::new(std::io::sink());
+        hasher.write_all(&[0xff]).unwrap();
+        assert_eq!(
+            "e62c05802ea0152407cdd8787fda9e35703de862a4fbc119cff8590afe79250b\
+             ccc8b3faf1bd2422ab5c0d263fb2f8afb3f796f048000381531b6f00d85161bc\
+             0fff4bef2486b1ebcd3773fabf50ad4ad5639af9040e3f29c6c931301bf79832\
+             e9da09857e831e82ef8b4691c235656515d437d2bda33bcec001c67ffde15ba8",
+            format!("{:x}", hasher.finalize())
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "stdio", feature = "serde"))]
+    #[cfg(feature = "crc32c")]
+    fn test_resume_from_state() {
+        use crate::crc32c::Crc32c;
+        use std::io::Write;
+
+        fn resume<D: ResumableDigest + Default>(data: &[u8]) -> D::Output {
+            let (first, second) = data.split_at(data.len() / 2);
+            let mut hasher = WriteHasher::<D, _>::new(std::io::sink());
+            hasher.write_all(first).unwrap();
+            let json = serde_json::to_string(&hasher.serialize_state()).unwrap();
+            drop(hasher);
+
+            let blob: StateBlob = serde_json::from_str(&json).unwrap();
+            let mut hasher = WriteHasher::<D, _>::new_from_state(Vec::new(), &blob).unwrap();
+            hasher.write_all(second).unwrap();
+            assert_eq!(hasher.bytes_written(), data.len() as u64);
+            let (digest, written) = hasher.finalize_into_parts();
+            assert_eq!(written, second);
+            digest
+        }
+
+        let data = std::fs::read("LICENSE").unwrap();
+        assert_eq!(resume::<Crc32c>(&data), hash_bytes::<Crc32c>(&data));
+        #[cfg(feature = "crc32fast")]
+        assert_eq!(
+            resume::<crc32fast::Hasher>(&data),
+            hash_bytes::<crc32fast::Hasher>(&data)
+        );
+
+        let mut hasher = WriteHasher::<Crc32c, _>::new(std::io::sink());
+        hasher.write_all(&data).unwrap();
+        let blob = hasher.serialize_state();
+        let restore = |blob: &StateBlob| {
+            WriteHasher::<Crc32c, _>::new_from_state(std::io::sink(), blob).map(|_| ())
+        };
+        assert_eq!(
+            restore(&StateBlob {
+                version: 2,
+                ..blob.clone()
+            }),
+            Err(StateError::Version(2))
+        );
+        assert_eq!(
+            restore(&StateBlob {
+                algorithm: "crc32".into(),
+                ..blob.clone()
+            }),
+            Err(StateError::Algorithm {
+                expected: "crc32c",
+                found: "crc32".into()
+            })
+        );
+        assert_eq!(
+            restore(&StateBlob {
+                state: vec![0; 5],
+                ..blob
+            }),
+            Err(StateError::Malformed)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    fn test_std_hasher() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::Hasher;
+        use std::io::Write;
+
+        /// FNV-1a, which like SipHash doesn't care how its input is split
+        struct Fnv1a(u64);
+        impl Default for Fnv1a {
+            fn default() -> Self {
+                Self(0xcbf29ce484222325)
+            }
+        }
+        impl Hasher for Fnv1a {
+            fn write(&mut self, bytes: &[u8]) {
+                for &byte in bytes {
+                    self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x100000001b3);
+                }
+            }
+            fn finish(&self) -> u64 {
+                self.0
+            }
+        }
+
+        let data = std::fs::read("LICENSE").unwrap();
+        let mut direct = DefaultHasher::new();
+        direct.write(&data);
+        let sip = direct.finish();
+        let mut direct = Fnv1a::default();
+        direct.write(&data);
+        let fnv = direct.finish();
+        assert_eq!(hash_bytes::<StdHasher<DefaultHasher>>(&data), sip);
+        assert_eq!(hash_bytes::<StdHasher<Fnv1a>>(&data), fnv);
+        assert_eq!(hash_bytes::<StdHasher<Fnv1a>>(b"a"), 0xaf63dc4c8601ec8c);
+
+        for chunk_size in [1, 3, 8, 100] {
+            let mut h_sip = WriteHasher::<StdHasher<DefaultHasher>, _>::new(std::io::sink());
+            let mut h_fnv = WriteHasher::<StdHasher<Fnv1a>, _>::new(std::io::sink());
+            for chunk in data.chunks(chunk_size) {
+                h_sip.write_all(chunk).unwrap();
+                h_fnv.write_all(chunk).unwrap();
+            }
+            assert_eq!(h_sip.finalize(), sip);
+            assert_eq!(h_fnv.finalize(), fnv);
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "stdio", feature = "adler"))]
+    fn test_adler32() {
+        use adler32::Adler32;
+        use std::io::Write;
+        let mut hasher = WriteHasher::<Adler32, _>::new(Vec::new());
+        for chunk in b"Wikipedia".chunks(2) {
+            hasher.write_all(chunk).unwrap();
+        }
+        let (adler, written) = hasher.finalize_into_parts();
+        assert_eq!(adler, 0x11E60398);
+        assert_eq!(written, b"Wikipedia");
+        assert_eq!(Adler32::new().finalize(), 1);
+
+        // long enough for the sums to be reduced several times, and all 0xff to maximize them
+        let data = [0xffu8; 20000];
+        let one_shot = hash_bytes::<Adler32>(&data);
+        // computed the way RFC 1950 defines it, a byte at a time
+        let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), &byte| {
+            let a = (a + u32::from(byte)) % 65521;
+            (a, (b + a) % 65521)
+        });
+        assert_eq!(one_shot, (b << 16) | a);
+        for chunk_size in [1, 100, 5552, 5553, 9999] {
+            let mut hasher = WriteHasher::<Adler32, _>::new(std::io::sink());
+            for chunk in data.chunks(chunk_size) {
+                hasher.write_all(chunk).unwrap();
+            }
+            assert_eq!(hasher.finalize(), one_shot);
+        }
+
+        let (head, tail) = data.split_at(7000);
+        let mut hasher = WriteHasher::<Adler32, _>::new_with_initial(
+            std::io::sink(),
+            hash_bytes::<Adler32>(head),
+        );
+        hasher.write_all(tail).unwrap();
+        assert_eq!(hasher.finalize(), one_shot);
+    }
+
+    #[test]
+    #[cfg(all(feature = "stdio", feature = "crc"))]
+    fn test_crc_hasher() {
+        use ::crc::{Algorithm, Crc};
+        use crc_generic::CrcWidth;
+        use std::io::Write;
+        fn check<W: CrcWidth + PartialEq + core::fmt::Debug>(
+            hasher: CrcHasher<'_, W>,
+            algorithm: &Algorithm<W>,
+        ) {
+            for chunk_size in [1, 2, 4, 9] {
+                let mut writer = WriteHasher::new_with_hasher(Vec::new(), hasher.clone());
+                for chunk in b"123456789".chunks(chunk_size) {
+                    writer.write_all(chunk).unwrap();
+                }
+                let (crc, written) = writer.finalize_into_parts();
+                assert_eq!(written, b"123456789");
+                assert_eq!(crc, algorithm.check, "{chunk_size}");
+            }
+        }
+        static MODBUS: Crc<u16> = Crc::<u16>::new(&::crc::CRC_16_MODBUS);
+        check(CrcHasher::new(&MODBUS), &::crc::CRC_16_MODBUS);
+        let bzip2 = CrcHasher::from_algorithm(&::crc::CRC_32_BZIP2);
+        check(bzip2, &::crc::CRC_32_BZIP2);
+        let go_iso = Crc::<u64>::new(&::crc::CRC_64_GO_ISO);
+        check(CrcHasher::new(&go_iso), &::crc::CRC_64_GO_ISO);
+        check(
+            CrcHasher::from_algorithm(&::crc::CRC_8_SMBUS),
+            &::crc::CRC_8_SMBUS,
+        );
+        let mut iscsi = CrcHasher::from_algorithm(&::crc::CRC_32_ISCSI);
+        iscsi.update(b"123456789");
+        #[cfg(feature = "crc32c")]
+        assert_eq!(iscsi.finalize(), hash_bytes::<crc32c::Crc32c>(b"123456789"));
+
+        let mut hasher = WriteHasher::new_crc(std::io::sink(), &::crc::CRC_16_MODBUS);
+        hasher.write_all(b"123456789").unwrap();
+        assert_eq!(hasher.finalize(), 0x4b37);
+        // the same algorithm reuses its table
+        assert!(core::ptr::eq(
+            <u16 as CrcWidth>::table(&::crc::CRC_16_MODBUS),
+            <u16 as CrcWidth>::table(&::crc::CRC_16_MODBUS)
+        ));
+    }
+
+    #[test]
+    #[cfg(all(feature = "stdio", feature = "crc64"))]
+    fn test_crc64() {
+        use crc64::{Crc64Nvme, Crc64Xz};
+        use std::io::Write;
+        // the check values of the CRC catalogue
+        assert_eq!(hash_bytes::<Crc64Xz>(b"123456789"), 0x995DC9BBDF1939FA);
+        assert_eq!(hash_bytes::<Crc64Nvme>(b"123456789"), 0xAE8B14860A799888);
+        assert_eq!(Crc64Xz::new().finalize(), 0);
+
+        let data = std::fs::read("LICENSE").unwrap();
+        let xz = hash_bytes::<Crc64Xz>(&data);
+        let nvme = hash_bytes::<Crc64Nvme>(&data);
+        for chunk_size in [1, 7, 64, 1000] {
+            let mut h_xz = WriteHasher::<Crc64Xz, _>::new(std::io::sink());
+            let mut h_nvme = WriteHasher::<Crc64Nvme, _>::new(std::io::sink());
+            for chunk in data.chunks(chunk_size) {
+                h_xz.write_all(chunk).unwrap();
+                h_nvme.write_all(chunk).unwrap();
+            }
+            assert_eq!(h_xz.finalize(), xz);
+            assert_eq!(h_nvme.finalize(), nvme);
+        }
+
+        let (head, tail) = data.split_at(500);
+        let mut continued = Crc64Xz::new_with_initial(hash_bytes::<Crc64Xz>(head));
+        continued.update(tail);
+        assert_eq!(continued.finalize(), xz);
+        let mut continued = Crc64Nvme::new_with_initial(hash_bytes::<Crc64Nvme>(head));
+        continued.update(tail);
+        assert_eq!(continued.finalize(), nvme);
+    }
+
+    #[test]
+    #[cfg(all(feature = "stdio", feature = "xxh3", feature = "xxh64"))]
+    fn test_xxhash() {
+        use std::io::Write;
+        use xxh3::{Xxh3_128, Xxh3_64};
+        use xxh64::Xxh64;
+        // the way xxhsum prints them
+        fn xxhsum<D: MinDigest>(
+            mut hasher: WriteHasher<D, std::io::Sink>,
+            chunks: &[&str],
+        ) -> String
+        where
+            D::Output: OutputBytes,
+        {
+            for chunk in chunks {
+                hasher.write_all(chunk.as_bytes()).unwrap();
+            }
+            helpers::encode_hex(&hasher.finalize().to_bytes())
+        }
+        let sink = std::io::sink;
+        let fox = ["The quick ", "brown fox", "", " jumps over the lazy dog"];
+        let vectors: [(&str, &[&str], &str); 9] = [
+            ("xxh64", &[], "ef46db3751d8e999"),
+            ("xxh64", &["abc"], "44bc2cf5ad770999"),
+            ("xxh64", &fox, "0b242d361fda71bc"),
+            ("xxh3_64", &[], "2d06800538d394c2"),
+            ("xxh3_64", &["abc"], "78af5f94892f3950"),
+            ("xxh3_64", &fox, "ce7d19a5418fb365"),
+            ("xxh3_128", &[], "99aa06d3014798d86001c324468d497f"),
+            ("xxh3_128", &["abc"], "06b05ab6733a618578af5f94892f3950"),
+            ("xxh3_128", &fox, "ddd650205ca3e7fa24a1cc2e3a8a7651"),
+        ];
+        for (algorithm, chunks, expected) in vectors {
+            let actual = match algorithm {
+                "xxh64" => xxhsum(WriteHasher::<Xxh64, _>::new(sink()), chunks),
+                "xxh3_64" => xxhsum(WriteHasher::<Xxh3_64, _>::new(sink()), chunks),
+                _ => xxhsum(WriteHasher::<Xxh3_128, _>::new(sink()), chunks),
+            };
+            assert_eq!(actual, expected, "{algorithm} of {chunks:?}");
+        }
+
+        let data = "a".repeat(1000);
+        let chunks: Vec<&str> = (0..1000)
+            .step_by(90)
+            .map(|i| &data[i..(i + 90).min(1000)])
+            .collect();
+        for seed in [0, 1, 0x9e3779b97f4a7c15] {
+            let expected = xxhash_rust::xxh64::xxh64(data.as_bytes(), seed);
+            let actual = xxhsum(
+                WriteHasher::<Xxh64, _>::new_with_seed(sink(), seed),
+                &chunks,
+            );
+            assert_eq!(actual, format!("{expected:016x}"));
+            let expected = xxhash_rust::xxh3::xxh3_64_with_seed(data.as_bytes(), seed);
+            let actual = xxhsum(
+                WriteHasher::<Xxh3_64, _>::new_with_seed(sink(), seed),
+                &chunks,
+            );
+            assert_eq!(actual, format!("{expected:016x}"));
+            let expected = xxhash_rust::xxh3::xxh3_128_with_seed(data.as_bytes(), seed);
+            let actual = xxhsum(
+                WriteHasher::<Xxh3_128, _>::new_with_seed(sink(), seed),
+                &chunks,
+            );
+            assert_eq!(actual, format!("{expected:032x}"));
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "stdio", feature = "xxh3"))]
+    fn test_xxh3() {
+        use std::io::Write;
+        use xxh3::{Xxh3_128, Xxh3_64};
+        use xxhash_rust::xxh3::*;
+
+        assert_eq!(Xxh3_64::new().finalize(), 0x2d06800538d394c2);
+        assert_eq!(
+            Xxh3_128::new().finalize(),
+            0x99aa06d3014798d86001c324468d497f
+        );
+
+        let data = (0..2048u32)
+            .map(|i| (i * 7 % 251) as u8)
+            .collect::<Vec<u8>>();
+        let secret: [u8; xxh3::SECRET_SIZE] = core::array::from_fn(|i| (i * 13 % 256) as u8);
+        // XXH3 uses different algorithms for <= 16, <= 128, <= 240 and > 240 bytes
+        for len in [0, 1, 3, 4, 8, 9, 16, 17, 128, 129, 240, 241, 1024, 2048] {
+            let data = &data[..len];
+            for chunk_size in [1, 7, 64, 300] {
+                let mut h64 = WriteHasher::<Xxh3_64, _>::new(std::io::sink());
+                let mut h128 = WriteHasher::<Xxh3_128, _>::new(std::io::sink());
+                let mut seeded =
+                    WriteHasher::new_with_hasher(std::io::sink(), Xxh3_64::with_seed(42));
+                let mut secret64 =
+                    WriteHasher::new_with_hasher(std::io::sink(), Xxh3_64::with_secret(secret));
+                let mut secret128 =
+                    WriteHasher::new_with_hasher(std::io::sink(), Xxh3_128::with_secret(secret));
+                for chunk in data.chunks(chunk_size) {
+                    h64.write_all(chunk).unwrap();
+                    h128.write_all(chunk).unwrap();
+                    seeded.write_all(chunk).unwrap();
+                    secret64.write_all(chunk).unwrap();
+                    secret128.write_all(chunk).unwrap();
+                }
+                assert_eq!(h64.finalize(), xxh3_64(data), "len {len}");
+                assert_eq!(h128.finalize(), xxh3_128(data), "len {len}");
+                assert_eq!(seeded.finalize(), xxh3_64_with_seed(data, 42), "len {len}");
+                assert_eq!(
+                    secret64.finalize(),
+                    xxh3_64_with_secret(data, &secret),
+                    "len {len}"
+                );
+                assert_eq!(
+                    secret128.finalize(),
+                    xxh3_128_with_secret(data, &secret),
+                    "len {len}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "stdio", feature = "blake3"))]
+    fn test_blake3_xof() {
+        use std::io::Write;
+        let src = std::fs::read("LICENSE").unwrap();
+        let mut reference = ::blake3::Hasher::new();
+        reference.update(&src);
+
+        let mut hasher = WriteHasher::<::blake3::Hasher, _>::new(std::io::sink());
+        hasher.write_all(&src).unwrap();
+        let mut out = [0u8; 64];
+        hasher.finalize_xof().fill(&mut out);
+        let mut expected = [0u8; 64];
+        reference.finalize_xof().fill(&mut expected);
+        assert_eq!(out, expected);
+        assert_eq!(
+            &out[..32],
+            ::blake3::Hasher::finalize(&reference).as_bytes()
+        );
+
+        let mut hasher = WriteHasher::<::blake3::Hasher, _>::new(Vec::new());
+        hasher.write_all(&src).unwrap();
+        let (mut reader, dest) = hasher.finalize_xof_into_parts();
+        assert_eq!(dest, src);
+        let mut out = [0u8; 1024];
+        reader.fill(&mut out);
+        let mut expected = [0u8; 1024];
+        reference.finalize_xof().fill(&mut expected);
+        assert_eq!(out, expected);
+
+        let mut hasher = WriteHasher::<::blake3::Hasher, _>::new(std::io::sink());
+        hasher.write_all(&src).unwrap();
+        let mut out = [0u8; 1024];
+        hasher.finalize_xof_into(&mut out);
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    #[cfg(all(feature = "stdio", feature = "blake3"))]
+    fn test_blake3_modes() {
+        use std::io::Write;
+        // from the official test_vectors.json, the input is 0, 1, .., 250, 0, 1, .. truncated
+        const KEY: &[u8; 32] = b"whats the Elvish word for friend";
+        const CONTEXT: &str = "BLAKE3 2019-12-27 16:29:52 test vectors context";
+        let vectors = [
+            (
+                0,
+                "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262",
+                "92b2b75604ed3c761f9d6f62392c8a9227ad0ea3f09573e783f1498a4ed60d26",
+                "2cc39783c223154fea8dfb7c1b1660f2ac2dcbd1c1de8277b0b0dd39b7e50d7d",
+            ),
+            (
+                1,
+                "2d3adedff11b61f14c886e35afa036736dcd87a74d27b5c1510225d0f592e213",
+                "6d7878dfff2f485635d39013278ae14f1454b8c0a3a2d34bc1ab38228a80c95b",
+                "b3e2e340a117a499c6cf2398a19ee0d29cca2bb7404c73063382693bf66cb06c",
+            ),
+        ];
+        for (len, hash, keyed, derived) in vectors {
+            let input: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+            let mut hasher = WriteHasher::<::blake3::Hasher, _>::new(std::io::sink());
+            hasher.write_all(&input).unwrap();
+            assert_eq!(format!("{}", hasher.finalize()), hash);
+            let mut hasher = WriteHasher::<::blake3::Hasher, _>::new_keyed(std::io::sink(), KEY);
+            hasher.write_all(&input).unwrap();
+            assert_eq!(hasher.finalize().to_hex().as_str(), keyed);
+            let mut hasher = WriteHasher::new_derive_key(std::io::sink(), CONTEXT);
+            hasher.write_all(&input).unwrap();
+            assert_eq!(hasher.finalize().to_hex().as_str(), derived);
+        }
+
+        // b3sum LICENSE
+        let src = std::fs::read("LICENSE").unwrap();
+        let mut hasher = WriteHasher::<::blake3::Hasher, _>::new(std::io::sink());
+        hasher.write_all(&src).unwrap();
+        assert_eq!(
+            format!("{}", hasher.finalize()),
+            ::blake3::hash(&src).to_hex().as_str()
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "stdio", feature = "blake3", feature = "rayon"))]
+    fn test_parallel_blake3() {
+        use std::io::Write;
+        let src: Vec<u8> = (0..1 << 20).map(|i| (i * 31 % 251) as u8).collect();
+        let expected = ::blake3::hash(&src);
+        for min_parallel_len in [0, 4096, usize::MAX] {
+            let hasher = ParallelBlake3::default().with_min_parallel_len(min_parallel_len);
+            let mut hasher = WriteHasher::new_with_hasher(std::io::sink(), hasher);
+            for chunk in src.chunks(300 * 1024) {
+                hasher.write_all(chunk).unwrap();
+            }
+            assert_eq!(hasher.finalize(), expected);
+        }
+        let key = [7; 32];
+        let hasher = ParallelBlake3::new(::blake3::Hasher::new_keyed(&key));
+        let mut hasher = WriteHasher::new_with_hasher(std::io::sink(), hasher);
+        hasher.write_all(&src).unwrap();
+        assert_eq!(hasher.finalize(), ::blake3::keyed_hash(&key, &src));
+    }
+
+    #[test]
+    #[cfg(all(feature = "stdio", feature = "sha3"))]
+    fn test_sha3() {
+        use std::io::Write;
+        fn hex<D: MinDigest + Default>(data: &[u8]) -> String
+        where
+            D::Output: OutputBytes,
+        {
+            let mut hasher = WriteHasher::<D, _>::new(Vec::new());
+            // one byte at a time through the Write impl and the rest in one go
+            for byte in data.iter().take(2) {
+                hasher.write_all(&[*byte]).unwrap();
+            }
+            hasher.write_all(data.get(2..).unwrap_or_default()).unwrap();
+            let (digest, written) = hasher.finalize_into_parts();
+            assert_eq!(written, data);
+            helpers::encode_hex(&digest.to_bytes())
+        }
+        // NIST FIPS 202 examples and the original Keccak submission (as used by Ethereum)
+        let two_blocks = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+        assert_eq!(
+            hex::<sha3::Sha3_256>(b""),
+            "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a"
+        );
+        assert_eq!(
+            hex::<sha3::Sha3_256>(b"abc"),
+            "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532"
+        );
+        assert_eq!(
+            hex::<sha3::Sha3_256>(two_blocks),
+            "41c0dba2a9d6240849100376a8235e2c82e1b9998a999e21db32dd97496d3376"
+        );
+        assert_eq!(
+            hex::<sha3::Sha3_224>(b"abc"),
+            "e642824c3f8cf24ad09234ee7d3c766fc9a3a5168d0c94ad73b46fdf"
+        );
+        assert_eq!(
+            hex::<sha3::Sha3_384>(b"abc"),
+            "ec01498288516fc926459f58e2c6ad8df9b473cb0fc08c2596da7cf0e49be4b2\
+             98d88cea927ac7f539f1edf228376d25"
+        );
+        assert_eq!(
+            hex::<sha3::Sha3_512>(b"abc"),
+            "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e\
+             10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0"
+        );
+        assert_eq!(
+            hex::<sha3::Keccak256>(b""),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        assert_eq!(
+            hex::<sha3::Keccak256>(b"abc"),
+            "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"
+        );
+        assert_eq!(
+            hex::<sha3::Keccak512>(b""),
+            "0eab42de4c3ceb9235fc91acffe746b29c29a8c366b7c60e4e67c466f36a4304\
+             c00fa9caf9d87976ba469bcbe06713b435f091ef2769fb160cdab33d3670680e"
+        );
+
+        let mut hasher = WriteHasher::<sha3::Sha3_256, _>::new(std::io::sink());
+        hasher.write_all(b"abc").unwrap();
+        let first = hasher.finalize_reset();
+        hasher.write_all(b"abc").unwrap();
+        assert_eq!(hasher.finalize(), first);
+    }
+
+    #[test]
+    #[cfg(all(feature = "stdio", feature = "sha3"))]
+    fn test_shake() {
+        use std::io::Write;
+        use xof::{Shake128, Shake256};
+        // NIST's SHA-3 example values, truncated to the output length
+        let shake128 = [
+            (&b""[..], 16, "7f9c2ba4e88f827d616045507605853e"),
+            (
+                b"",
+                32,
+                "7f9c2ba4e88f827d616045507605853ed73b8093f6efbc88eb1a6eacfa66ef26",
+            ),
+            (
+                b"abc",
+                32,
+                "5881092dd818bf5cf8a3ddb793fbcba74097d5c526a6d35f97b83351940f2cc8",
+            ),
+        ];
+        let shake256 = [
+            (
+                &b""[..],
+                64,
+                "46b9dd2b0ba88d13233b3feb743eeb243fcd52ea62b81b82b50c27646ed5762f\
+                 d75dc4ddd8c0f200cb05019d67b592f6fc821c49479ab48640292eacb3b7c4be",
+            ),
+            (
+                b"abc",
+                32,
+                "483366601360a8771c6863080cc4114d8db44530f8f1e1ee4f94ea37e78b5739",
+            ),
+            (
+                b"abc",
+                64,
+                "483366601360a8771c6863080cc4114d8db44530f8f1e1ee4f94ea37e78b5739\
+                 d5a15bef186a5386c75744c0527e1faa9f8726e462a12a4feb06bd8801e751e4",
+            ),
+        ];
+        for (input, len, expected) in shake128 {
+            let hasher = Shake128::new(len);
+            let mut hasher = WriteHasher::new_with_hasher(std::io::sink(), hasher);
+            hasher.write_all(input).unwrap();
+            assert_eq!(hasher.finalize(), decode_hex(expected).unwrap());
+        }
+        for (input, len, expected) in shake256 {
+            let mut hasher = WriteHasher::<Shake256, _>::new_with_output_len(std::io::sink(), 8);
+            hasher.write_all(input).unwrap();
+            let mut out = vec![0; len];
+            hasher.finalize_xof_into(&mut out);
+            assert_eq!(out, decode_hex(expected).unwrap());
+        }
+
+        let data = std::fs::read("LICENSE").unwrap();
+        let mut reader = sha3::Shake256::default();
+        digest::Update::update(&mut reader, &data);
+        let mut expected = [0; 200];
+        digest::ExtendableOutput::finalize_xof_into(reader, &mut expected);
+        let mut one_shot = Shake256::new(64);
+        one_shot.update(&data);
+        assert_eq!(one_shot.finalize(), expected[..64]);
+        for chunk_size in [1, 135, 136, 137, 1000] {
+            let mut hasher = WriteHasher::<Shake256, _>::new_with_output_len(std::io::sink(), 64);
+            for chunk in data.chunks(chunk_size) {
+                hasher.write_all(chunk).unwrap();
+            }
+            let mut out = [0; 200];
+            digest::XofReader::read(&mut hasher.finalize_xof(), &mut out);
+            assert_eq!(out, expected);
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "stdio", feature = "sha3"))]
+    fn test_cshake() {
+        use sp800_185::{CShake128, CShake256};
+        use std::io::Write;
+        let data = (0u8..200).collect::<Vec<u8>>();
+        let vectors = [
+            (
+                CShake128::new(b"Email Signature", 32),
+                4,
+                "C1C36925B6409A04F1B504FCBCA9D82B4017277CB5ED2B2065FC1D3814D5AAF5",
+            ),
+            (
+                CShake128::new(b"Email Signature", 32),
+                200,
+                "C5221D50E4F822D96A2E8881A961420F294B7B24FE3D2094BAED2C6524CC166B",
+            ),
+        ];
+        for (hasher, len, expected) in vectors {
+            let mut hasher = WriteHasher::new_with_hasher(std::io::sink(), hasher);
+            for chunk in data[..len].chunks(3) {
+                hasher.write_all(chunk).unwrap();
+            }
+            assert_eq!(hasher.finalize(), decode_hex(expected).unwrap());
+        }
+        let vectors = [
+            (
+                CShake256::new(b"Email Signature", 64),
+                4,
+                "D008828E2B80AC9D2218FFEE1D070C48B8E4C87BFF32C9699D5B6896EEE0EDD1\
+                 64020E2BE0560858D9C00C037E34A96937C561A74C412BB4C746469527281C8C",
+            ),
+            (
+                CShake256::new(b"Email Signature", 64),
+                200,
+                "07DC27B11E51FBAC75BC7B3C1D983E8B4B85FB1DEFAF218912AC86430273091\
+                 727F42B17ED1DF63E8EC118F04B23633C1DFB1574C8FB55CB45DA8E25AFB092BB",
+            ),
+        ];
+        for (hasher, len, expected) in vectors {
+            let mut hasher = WriteHasher::new_with_hasher(std::io::sink(), hasher);
+            for chunk in data[..len].chunks(7) {
+                hasher.write_all(chunk).unwrap();
+            }
+            assert_eq!(hasher.finalize(), decode_hex(expected).unwrap());
+        }
+
+        // no function name and customization falls back to SHAKE
+        let mut hasher = WriteHasher::new_with_hasher(std::io::sink(), CShake128::new(b"", 32));
+        hasher.write_all(&data[..4]).unwrap();
+        let mut shake = sha3::Shake128::default();
+        digest::Update::update(&mut shake, &data[..4]);
+        let mut expected = [0u8; 32];
+        digest::ExtendableOutput::finalize_xof_into(shake, &mut expected);
+        assert_eq!(hasher.finalize(), expected);
+    }
+
+    #[test]
+    #[cfg(feature = "sha3")]
+    fn test_tuple_hash() {
+        use sp800_185::{TupleHash128, TupleHash256};
+        let mut hasher = TupleHash128::new(b"", 32);
+        hasher.update_field(&[0, 1, 2]);
+        hasher.update_field(&[0x10, 0x11, 0x12, 0x13, 0x14, 0x15]);
+        assert_eq!(
+            hasher.finalize(),
+            decode_hex("C5D8786C1AFB9B82111AB34B65B2C0048FA64E6D48E263264CE1707D3FFC8ED1").unwrap()
+        );
+
+        let mut hasher = TupleHash128::new(b"My Tuple App", 32);
+        hasher.update_field(&[0, 1, 2]);
+        hasher.update_field(&[0x10, 0x11, 0x12, 0x13, 0x14, 0x15]);
+        assert_eq!(
+            hasher.finalize(),
+            decode_hex("75CDB20FF4DB1154E841D758E24160C54BAE86EB8C13E7F5F40EB35588E96DFB").unwrap()
+        );
+
+        // fields written with update are closed by update_field, finish_field and finalize
+        let mut hasher = TupleHash128::new(b"My Tuple App", 32);
+        hasher.update([0, 1]);
+        hasher.update([2]);
+        hasher.update_field(&[0x10, 0x11, 0x12, 0x13, 0x14, 0x15]);
+        hasher.update([0x20, 0x21, 0x22, 0x23]);
+        hasher.update([0x24, 0x25, 0x26, 0x27, 0x28]);
+        assert_eq!(
+            hasher.finalize(),
+            decode_hex("E60F202C89A2631EDA8D4C588CA5FD07F39E5151998DECCF973ADB3804BB6E84").unwrap()
+        );
+
+        let mut hasher = TupleHash256::new(b"", 64);
+        hasher.update([0, 1, 2]);
+        hasher.finish_field();
+        hasher.update([0x10, 0x11, 0x12, 0x13, 0x14, 0x15]);
+        assert_eq!(
+            hasher.finalize(),
+            decode_hex(
+                "CFB7058CACA5E668F81A12A20A2195CE97A925F1DBA3E7449A56F82201EC6073\
+                 11AC2696B1AB5EA2352DF1423BDE7BD4BB78C9AED1A853C78672F9EB23BBE194"
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "stdio", feature = "sha3"))]
+    fn test_parallel_hash() {
+        use sp800_185::{ParallelHash128, ParallelHash256};
+        use std::io::Write;
+        let data = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15,
+            0x16, 0x17, 0x20, 0x21, 0x22, 0x23, 0x24, 0x25, 0x26, 0x27,
+        ];
+        let vectors = [
+            (
+                ParallelHash128::new(8, b"", 32),
+                "BA8DC1D1D979331D3F813603C67F72609AB5E44B94A0B8F9AF46514454A2B4F5",
+            ),
+            (
+                ParallelHash128::new(8, b"Parallel Data", 32),
+                "FC484DCB3F84DCEEDC353438151BEE58157D6EFED0445A81F165E495795B7206",
+            ),
+        ];
+        for chunk_size in [1, 5, 8, 24] {
+            for (hasher, expected) in vectors.clone() {
+                let mut hasher = WriteHasher::new_with_hasher(std::io::sink(), hasher);
+                for chunk in data.chunks(chunk_size) {
+                    hasher.write_all(chunk).unwrap();
+                }
+                assert_eq!(hasher.finalize(), decode_hex(expected).unwrap());
+            }
+            let mut hasher =
+                WriteHasher::new_with_hasher(std::io::sink(), ParallelHash256::new(8, b"", 64));
+            for chunk in data.chunks(chunk_size) {
+                hasher.write_all(chunk).unwrap();
+            }
+            assert_eq!(
+                hasher.finalize(),
+                decode_hex(
+                    "BC1EF124DA34495E948EAD207DD9842235DA432D2BBC54B4C110E64C45110553\
+                     1B7F2A3E0CE055C02805E7C2DE1FB746AF97A1DD01F43B824E31B87612410429"
+                )
+                .unwrap()
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "sha3", feature = "rayon"))]
+    fn test_parallel_hash_rayon() {
+        use sp800_185::ParallelHash128;
+        let data = (0..1 << 20)
+            .map(|i: u32| (i * 31 % 253) as u8)
+            .collect::<Vec<u8>>();
+        let mut parallel = ParallelHash128::new(1000, b"rayon", 32);
+        let mut sequential = ParallelHash128::new(1000, b"rayon", 32).with_parallel(false);
+        for chunk in data.chunks(100_003) {
+            parallel.update(chunk);
+            sequential.update(chunk);
+        }
+        assert_eq!(parallel.finalize(), sequential.finalize());
+    }
+
+    #[test]
+    #[cfg(all(feature = "rayon", any(feature = "sha2", feature = "digest")))]
+    #[cfg(feature = "crc32c")]
+    fn test_parallel_multi_hasher() {
+        extern crate sha2;
+        use crate::crc32c::Crc32c;
+        // xorshift, any data will do as long as it isn't all the same
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let data = (0..1 << 20)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect::<Vec<u8>>();
+
+        let mut parallel = ParallelMultiHasher::<(sha2::Sha256, sha2::Sha512, Crc32c)>::default()
+            .with_min_parallel_len(1000);
+        let mut sequential = MultiDigest::<(sha2::Sha256, sha2::Sha512, Crc32c)>::default();
+        // both short (sequential) and long (parallel) updates
+        for chunk in data.chunks(100_003).chain(data.chunks(999)) {
+            parallel.update(chunk);
+            sequential.update(chunk);
+        }
+        assert_eq!(parallel.finalize(), sequential.finalize());
+
+        let mut parallel = ParallelMultiHasher::new((Crc32c::new(), sha2::Sha256::default()));
+        parallel.update(&data);
+        let (crc, sha) = parallel.finalize();
+        assert_eq!(crc, hash_bytes::<Crc32c>(&data));
+        assert_eq!(format!("{:x}", sha), sha256_hex(&data));
+    }
+
+    #[test]
+    #[cfg(all(feature = "stdio", feature = "hkdf"))]
+    fn test_deriving_digest() {
+        extern crate sha2;
+        use kdf::DerivingDigest;
+        use std::io::Write;
+        // RFC 5869 A.1 and A.3 with the IKM written one byte at a time
+        let derive = |salt: Option<&[u8]>, info: &[u8]| {
+            let hasher = DerivingDigest::<sha2::Sha256>::new(salt, info, 42).unwrap();
+            let mut hasher = WriteHasher::new_with_hasher(std::io::sink(), hasher);
+            for byte in [0x0b; 22] {
+                hasher.write_all(&[byte]).unwrap();
+            }
+            hasher.finalize()
+        };
+        assert_eq!(
+            derive(
+                Some(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]),
+                &[0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9]
+            ),
+            decode_hex(
+                "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865"
+            )
+            .unwrap()
+        );
+        let okm = decode_hex(
+            "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d9d201395faa4b61a96c8",
+        )
+        .unwrap();
+        assert_eq!(derive(Some(&[]), &[]), okm);
+        assert_eq!(derive(None, &[]), okm);
+        assert!(DerivingDigest::<sha2::Sha256>::new(None, &[], 255 * 32 + 1).is_err());
+    }
+
+    #[test]
+    #[cfg(all(feature = "stdio", feature = "mac"))]
+    fn test_mac() {
+        use std::io::Write;
+        type HmacSha256 = hmac::Hmac<sha2::Sha256>;
+        // RFC 4231 test cases 1-4, 6 and 7 (5 is truncated)
+        let cases: [(Vec<u8>, Vec<u8>, &str); 6] = [
+            (
+                vec![0x0b; 20],
+                b"Hi There".to_vec(),
+                "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+            ),
+            (
+                b"Jefe".to_vec(),
+                b"what do ya want for nothing?".to_vec(),
+                "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            ),
+            (
+                vec![0xaa; 20],
+                vec![0xdd; 50],
+                "773ea91e36800e46854db8ebd09181a72959098b3ef8c122d9635514ced565fe",
+            ),
+            (
+                (1..=25).collect(),
+                vec![0xcd; 50],
+                "82558a389a443c0ea4cc819899f2083a85f0faa3e578f8077a2e3ff46729665b",
+            ),
+            (
+                vec![0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First".to_vec(),
+                "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+            ),
+            (
+                vec![0xaa; 131],
+                b"This is a test using a larger than block-size key and a larger than block-size data. The key needs to be hashed before being used by the HMAC algorithm.".to_vec(),
+                "9b09ffa71b942fcb27635fbcd5b0e944bfdc63644f0713938a7f51535c3a35e2",
+            ),
+        ];
+        for (key, data, expected) in &cases {
+            let expected = helpers::decode_hex(expected).unwrap();
+            for chunk_size in [1, 5, 64] {
+                let new = || {
+                    WriteHasher::<MacHasher<HmacSha256>, _>::new_with_key(std::io::sink(), key)
+                        .unwrap()
+                };
+                let mut hasher = new();
+                let mut verifier = new();
+                for chunk in data.chunks(chunk_size) {
+                    hasher.write_all(chunk).unwrap();
+                    verifier.write_all(chunk).unwrap();
+                }
+                assert_eq!(hasher.finalize().as_slice(), expected);
+                verifier.finalize_verify_tag(&expected).unwrap();
+            }
+            let mut forged = expected.clone();
+            forged[0] ^= 1;
+            let mut verifier =
+                WriteHasher::<MacHasher<HmacSha256>, _>::new_with_key(std::io::sink(), key)
+                    .unwrap();
+            verifier.write_all(data).unwrap();
+            assert!(verifier.finalize_verify_tag(&forged).is_err());
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "stdio", feature = "hkdf"))]
+    fn test_finalize_hkdf() {
+        extern crate sha2;
+        let mut src = std::fs::File::open("LICENSE").unwrap();
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(std::io::sink());
+        std::io::copy(&mut src, &mut hasher).unwrap();
+        let mut okm = [0; 64];
+        hasher
+            .finalize_hkdf(Some(b"salt"), b"info", &mut okm)
+            .unwrap();
+        let ikm =
+            decode_hex("f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f").unwrap();
+        let mut expected = [0; 64];
+        hkdf::SimpleHkdf::<sha2::Sha256>::new(Some(b"salt"), &ikm)
+            .expand(b"info", &mut expected)
+            .unwrap();
+        assert_eq!(okm, expected);
+
+        let hasher = WriteHasher::<sha2::Sha256, _>::new(std::io::sink());
+        assert!(hasher
+            .finalize_hkdf(None, &[], &mut [0; 255 * 32 + 1])
+            .is_err());
+    }
+
+    #[test]
+    #[cfg(all(feature = "stdio", feature = "poly1305"))]
+    fn test_poly1305() {
+        use std::io::Write;
+        // RFC 8439 2.5.2 and A.3 #1
+        let key = decode_hex("85d6be7857556d337f4452fe42d506a80103808afb0db2fd4abff6af4149f51b")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let message = b"Cryptographic Forum Research Group";
+        let tag = decode_hex("a8061dc1305136c6c22b8baf0c0127a9").unwrap();
+        for chunks in [
+            &[34][..],
+            &[1; 34],
+            &[15, 1, 16, 2],
+            &[16, 16, 2],
+            &[3, 20, 11],
+        ] {
+            let mut hasher =
+                WriteHasher::new_with_hasher(std::io::sink(), poly1305::Poly1305::new(&key));
+            let mut rest = &message[..];
+            for &chunk in chunks {
+                let (head, tail) = rest.split_at(chunk);
+                hasher.write_all(head).unwrap();
+                rest = tail;
+            }
+            assert!(rest.is_empty());
+            assert_eq!(hasher.finalize()[..], tag[..]);
+        }
+
+        let mut hasher = poly1305::Poly1305::new(&[0; 32]);
+        for _ in 0..8 {
+            hasher.update([0; 8]);
+        }
+        assert_eq!(hasher.finalize(), [0; 16]);
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    fn test_crc24() {
+        use crc24::{to_armor_string, Crc24};
+        use std::io::Write;
+        let mut hasher = Crc24::new();
+        hasher.update(b"123456789");
+        assert_eq!(hasher.finalize(), 0x21CF02);
+        assert_eq!(to_armor_string(0x21CF02), "=Ic8C");
+
+        // Footer of `gpg --enarmor < LICENSE`
+        let data = std::fs::read("LICENSE").unwrap();
+        for chunk_size in [1, 3, 64, data.len()] {
+            let mut hasher = WriteHasher::new_with_hasher(std::io::sink(), Crc24::new());
+            for chunk in data.chunks(chunk_size) {
+                hasher.write_all(chunk).unwrap();
+            }
+            assert_eq!(to_armor_string(hasher.finalize()), "=II2a");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "crc32c")]
+    fn test_module_paths() {
+        // the pre-split paths and the module paths name the same items
+        let hasher: writer::WriteHasher<digests::crc32c::Crc32c, ()> =
+            crate::WriteHasher::<crate::crc32c::Crc32c, ()>::new(());
+        let _: reader::ReadHasher<digests::crc24::Crc24, ()> =
+            crate::ReadHasher::<crate::crc24::Crc24, ()>::new(());
+        #[cfg(any(feature = "stdio", feature = "tokio"))]
+        let _: combinators::ChainedReadHasher<crc32c::Crc32c, core::iter::Empty<&[u8]>> =
+            crate::ReadHasher::new(crate::ReadChain::new(core::iter::empty()));
+        assert_eq!(reader::SeekPolicy::default(), crate::SeekPolicy::Forbid);
+
+        use crate::prelude::*;
+        let _: prelude::WriteHasher<crc32c::Crc32c, ()> = hasher;
+        fn finalize<D: prelude::MinDigest>(hasher: D) -> D::Output {
+            MinDigest::finalize(hasher)
+        }
+        assert_eq!(finalize(crate::crc32c::Crc32c::new()), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "digest")]
+    fn test_reexported_digest() {
+        extern crate sha2;
+        fn hash<D: crate::digest::Digest>(data: &[u8]) -> crate::digest::Output<D> {
+            D::digest(data)
+        }
+        assert_eq!(
+            format!("{:x}", hash::<sha2::Sha256>(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "sha2")]
+    fn test_reexported_backend() {
+        let hasher = WriteHasher::<crate::sha2::Sha256, _>::new(());
+        assert_eq!(
+            format!("{:x}", hasher.finalize()),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_hash_writer_error_variants() {
+        extern crate sha2;
+        use std::io::{Read, Seek, SeekFrom};
+        let empty_sha256 =
+            decode_hex("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855").unwrap();
+
+        let hasher = ReadHasher::<sha2::Sha256, _>::new(std::io::empty()).with_expected([0u8; 32]);
+        match hasher.verify() {
+            Err(HashWriterError::Mismatch { expected, actual }) => {
+                assert_eq!(expected, [0; 32]);
+                assert_eq!(actual, empty_sha256);
+            }
+            other => panic!("expected a mismatch, got {:?}", other),
+        }
+
+        // the Read impl wraps the same error in an io::Error
+        let mut hasher =
+            ReadHasher::<sha2::Sha256, _>::new(std::io::empty()).with_expected([0u8; 32]);
+        let err = hasher.read(&mut [0; 8]).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        let inner = err.get_ref().unwrap().downcast_ref::<HashWriterError>();
+        assert!(matches!(inner, Some(HashWriterError::Mismatch { .. })));
+
+        let mut hasher = ReadHasher::<sha2::Sha256, _>::new(std::io::Cursor::new([0u8; 16]))
+            .with_seek_policy(SeekPolicy::InvalidateDigest)
+            .with_expected(empty_sha256);
+        hasher.seek(SeekFrom::Start(8)).unwrap();
+        assert!(matches!(hasher.verify(), Err(HashWriterError::Invalidated)));
+
+        let err = ReadHasher::<sha2::Sha256, _>::new(std::io::empty())
+            .with_expected_hex("not hex")
+            .err()
+            .unwrap();
+        assert!(matches!(err, HashWriterError::Parse(_)));
+        assert_eq!(
+            std::io::Error::from(err).kind(),
+            std::io::ErrorKind::InvalidInput
+        );
+
+        let hasher = ReadHasher::<sha2::Sha256, _>::new(std::io::empty());
+        match hasher.verify() {
+            Err(HashWriterError::Io(err)) => {
+                assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput)
+            }
+            other => panic!("expected an io error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_hash_writer_error_conversions() {
+        let err = HashWriterError::from(std::io::Error::from(std::io::ErrorKind::BrokenPipe));
+        assert!(matches!(err, HashWriterError::Io(_)));
+        // io errors are unwrapped instead of nested
+        let err = std::io::Error::from(err);
+        assert_eq!(err.kind(), std::io::ErrorKind::BrokenPipe);
+        assert!(err.get_ref().is_none());
+
+        let err = HashWriterError::Length {
+            expected: 10,
+            actual: 4,
+        };
+        assert_eq!(err.to_string(), "length mismatch: expected 10 bytes, got 4");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        let err = HashWriterError::Mismatch {
+            expected: vec![0xab, 0x01],
+            actual: vec![0xcd, 0x02],
+        };
+        assert_eq!(err.to_string(), "digest mismatch: expected ab01, got cd02");
+        let err = std::io::Error::from(err);
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(err.get_ref().unwrap().is::<HashWriterError>());
+    }
+
+    #[test]
+    #[cfg(feature = "base32")]
+    fn test_base32() {
+        use encoding::{
+            decode_base32, decode_base32_crockford, encode_base32, encode_base32_crockford,
+        };
+        // RFC 4648 section 10
+        let vectors = [
+            ("", ""),
+            ("f", "MY======"),
+            ("fo", "MZXQ===="),
+            ("foo", "MZXW6==="),
+            ("foob", "MZXW6YQ="),
+            ("fooba", "MZXW6YTB"),
+            ("foobar", "MZXW6YTBOI======"),
+        ];
+        for (plain, encoded) in vectors {
+            assert_eq!(encode_base32(plain.as_bytes(), true), encoded);
+            let unpadded = encoded.trim_end_matches('=');
+            assert_eq!(encode_base32(plain.as_bytes(), false), unpadded);
+            assert_eq!(decode_base32(encoded).unwrap(), plain.as_bytes());
+            assert_eq!(decode_base32(unpadded).unwrap(), plain.as_bytes());
+        }
+        assert_eq!(encode_base32_crockford(b"foobar"), "CSQPYRK1E8");
+        assert_eq!(decode_base32_crockford("csqp-yrk1-e8").unwrap(), b"foobar");
+        assert_eq!(decode_base32_crockford("CSQPYRKIE8").unwrap(), b"foobar");
+        assert_eq!(decode_base32_crockford("C5QPYRK1EB"), None);
+        assert_eq!(decode_base32("MZXW6Y"), None);
+        assert_eq!(decode_base32("MZXW6=="), None);
+        assert_eq!(decode_base32("mzxw6==="), None);
+    }
+
+    #[test]
+    #[cfg(feature = "multihash")]
+    fn test_multihash() {
+        use multihash::{decode_varint, encode_varint, parse_multihash, to_multihash};
+        fn writer<D: MinDigest + Default>(src: &[u8]) -> WriteHasher<D, Vec<u8>> {
+            let mut hasher = WriteHasher::new(Vec::new());
+            hasher.update(src);
+            hasher
+        }
+        let src = b"hello world";
+        let sha256 = writer::<sha2::Sha256>(src).finalize_multihash();
+        assert_eq!(
+            helpers::encode_hex(&sha256),
+            "1220b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
+        );
+        assert_eq!(
+            helpers::encode_hex(&writer::<sha1::Sha1>(src).finalize_multihash()),
+            "11142aae6c35c94fcfb415dbe95f408b9ce91ee846ed"
+        );
+        let blake2b = writer::<blake2::Blake2b512>(src).finalize_multihash();
+        assert_eq!(&blake2b[..4], [0xc0, 0xe4, 0x02, 0x40]);
+        assert_eq!(
+            parse_multihash(&blake2b).unwrap(),
+            (multihash::BLAKE2B_512, &blake2b[4..])
+        );
+
+        // the CIDv1 of the raw block "hello world"
+        #[cfg(feature = "base32")]
+        {
+            let cid = "bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5e";
+            let bytes = encoding::decode_base32(&cid[1..].to_uppercase()).unwrap();
+            assert_eq!(bytes[..2], [0x01, 0x55]);
+            assert_eq!(bytes[2..], sha256);
+        }
+        let (code, digest) = parse_multihash(&sha256).unwrap();
+        assert_eq!(to_multihash(code, digest), sha256);
+
+        for value in [0, 1, 0x7f, 0x80, 0xb240, u64::MAX >> 1] {
+            let mut encoded = Vec::new();
+            encode_varint(value, &mut encoded);
+            assert_eq!(decode_varint(&encoded), Some((value, encoded.len())));
+        }
+        assert_eq!(decode_varint(&[0x80, 0x00]), None);
+        assert_eq!(decode_varint(&[0x80]), None);
+        assert!(matches!(
+            parse_multihash(&sha256[..20]),
+            Err(HashWriterError::Length { expected: 32, .. })
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "sri")]
+    fn test_sri() {
+        use sri::{format_sri, parse_sri, SriAlgorithm};
+        // the examples of https://www.w3.org/TR/SRI/#integrity-metadata-description
+        let src = b"alert('Hello, world.');";
+        let sha256 = "sha256-qznLcsROx4GACP2dm0UCKCzCG+HiZ1guq6ZZDob/Tng=";
+        let sha384 = "sha384-H8BRh8j48O9oYatfu5AZzq6A9RINhZO5H16dQZngK7T62em8MUt1FLm52t+eX6xO";
+        let sha512 = "sha512-Q2bFTOhEALkN8hOms2FKTDLy7eugP2zFZ1T8LCvX42Fp3WoNr3bjZSAHeOsHrbV1Fu9/A0EzCinRE7Af1ofPrw==";
+        fn writer<D: MinDigest + Default>(src: &[u8]) -> WriteHasher<D, Vec<u8>> {
+            let mut hasher = WriteHasher::new(Vec::new());
+            hasher.update(src);
+            hasher
+        }
+        assert_eq!(writer::<sha2::Sha256>(src).finalize_sri(), sha256);
+        assert_eq!(writer::<sha2::Sha384>(src).finalize_sri(), sha384);
+        assert_eq!(writer::<sha2::Sha512>(src).finalize_sri(), sha512);
+
+        for (metadata, algorithm) in [
+            (sha256, SriAlgorithm::Sha256),
+            (sha384, SriAlgorithm::Sha384),
+            (sha512, SriAlgorithm::Sha512),
+        ] {
+            let (parsed, digest) = parse_sri(metadata).unwrap();
+            assert_eq!(parsed, algorithm);
+            assert_eq!(format_sri(parsed, &digest).unwrap(), metadata);
+        }
+        let (_, digest) = parse_sri(&format!(" {}?foo=bar ", sha384)).unwrap();
+        assert_eq!(digest.len(), 48);
+
+        // verifying against parsed metadata
+        let (_, expected) = parse_sri(sha384).unwrap();
+        writer::<sha2::Sha384>(src)
+            .with_expected(expected)
+            .finalize_verify()
+            .unwrap();
+
+        assert!(matches!(
+            parse_sri("md5-kAFQmDzST7DWlj99KOF/cg=="),
+            Err(HashWriterError::Parse(_))
+        ));
+        assert!(matches!(
+            parse_sri("SHA256-qznLcsROx4GACP2dm0UCKCzCG+HiZ1guq6ZZDob/Tng="),
+            Err(HashWriterError::Parse(_))
+        ));
+        assert!(matches!(
+            parse_sri(&sha384.replace("sha384", "sha256")),
+            Err(HashWriterError::Parse(_))
+        ));
+        assert!(matches!(
+            format_sri(SriAlgorithm::Sha256, &[0; 20]),
+            Err(HashWriterError::Length { .. })
+        ));
+
+        #[cfg(feature = "crc32c")]
+        {
+            let mut hasher: AnyHasher = "crc32c".parse().unwrap();
+            hasher.update(src);
+            assert!(matches!(
+                hasher.finalize().to_sri(),
+                Err(HashWriterError::Parse(_))
+            ));
+        }
+        #[cfg(feature = "sha2")]
+        {
+            let mut hasher: AnyHasher = "sha-384".parse().unwrap();
+            hasher.update(src);
+            assert_eq!(hasher.finalize().to_sri().unwrap(), sha384);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_checksum_file_writer() {
+        extern crate sha2;
+        use std::io::Write;
+        // written by coreutils' sha256sum for files of these names and contents
+        let fixture = std::fs::read("testdata/SHA256SUMS").unwrap();
+        let mut sums = checksum_file::Writer::new(Vec::new());
+        let license = sums
+            .hash_entry("LICENSE", sha2::Sha256::default(), |writer| {
+                std::io::copy(&mut std::fs::File::open("LICENSE")?, writer)?;
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(
+            format!("{:x}", license),
+            "f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f"
+        );
+        for (name, contents) in [
+            ("new\nline", &b"abc"[..]),
+            ("back\\slash", b"abc"),
+            ("plain name.txt", b""),
+        ] {
+            sums.hash_entry(name, sha2::Sha256::default(), |writer| {
+                writer.write_all(contents)
+            })
+            .unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(sums.into_inner()).unwrap(),
+            String::from_utf8(fixture).unwrap()
+        );
+
+        let mut sums = checksum_file::Writer::new(Vec::new()).with_binary_marker(true);
+        sums.write_entry("a\rb", &0u32.to_be_bytes()).unwrap();
+        sums.write_entry("c", &0x1234u32.to_be_bytes()).unwrap();
+        assert_eq!(sums.into_inner(), b"\\00000000 *a\\rb\n00001234 *c\n");
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    fn test_checksum_file_parse() {
+        use std::path::Path;
+        let sums = std::fs::read("testdata/SHA256SUMS").unwrap();
+        let entries: Vec<_> = checksum_file::parse(&sums)
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+        let names: Vec<_> = entries.iter().map(|entry| entry.name.as_path()).collect();
+        assert_eq!(
+            names,
+            [
+                Path::new("LICENSE"),
+                Path::new("new\nline"),
+                Path::new("back\\slash"),
+                Path::new("plain name.txt"),
+            ]
+        );
+        assert_eq!(entries[1].line, 2);
+        assert_eq!(
+            helpers::encode_hex(&entries[0].expected),
+            "f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f"
+        );
+
+        let lines =
+            checksum_file::parse(b"\nabcd *bin\nxyz  name\nabcd name\nabcd  \n\\abcd  a\\tb\n");
+        assert!(lines[0].as_ref().unwrap().binary);
+        let errors: Vec<_> = lines[1..]
+            .iter()
+            .map(|line| line.as_ref().unwrap_err().line)
+            .collect();
+        assert_eq!(errors, [3, 4, 5, 6]);
+    }
+
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn checksum_dir(name: &str) -> (std::path::PathBuf, Vec<u8>) {
+        let dir = std::env::temp_dir().join(format!("write-hasher-{name}-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("new\nline"), b"abc").unwrap();
+        std::fs::write(dir.join("back\\slash"), b"abd").unwrap();
+        std::fs::write(dir.join("plain name.txt"), b"").unwrap();
+        std::fs::copy("LICENSE", dir.join("LICENSE")).unwrap();
+        let mut sums = std::fs::read("testdata/SHA256SUMS").unwrap();
+        sums.extend_from_slice(b"not a checksum line\n");
+        sums.extend_from_slice(
+            b"e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855  missing\n",
+        );
+        (dir, sums)
+    }
+
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn check_checksum_reports(reports: &[Result<checksum_file::Report, checksum_file::Malformed>]) {
+        use checksum_file::Status;
+        assert_eq!(reports.len(), 6);
+        assert!(matches!(reports[0].as_ref().unwrap().status, Status::Ok));
+        assert!(matches!(reports[1].as_ref().unwrap().status, Status::Ok));
+        match &reports[2].as_ref().unwrap().status {
+            Status::Mismatch { expected, actual } => {
+                assert_eq!(expected, &reports[1].as_ref().unwrap().entry.expected);
+                assert_eq!(helpers::encode_hex(actual), sha256_hex(b"abd"));
+            }
+            status => panic!("expected a mismatch, got {status:?}"),
+        }
+        assert!(matches!(reports[3].as_ref().unwrap().status, Status::Ok));
+        assert_eq!(reports[4].as_ref().unwrap_err().line, 5);
+        assert!(matches!(
+            reports[5].as_ref().unwrap().status,
+            Status::Missing
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_checksum_file_verify() {
+        let (dir, sums) = checksum_dir("sums");
+        let reports = checksum_file::verify::<sha2::Sha256>(&sums, &dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        check_checksum_reports(&reports);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "stdio")]
+    #[cfg(all(any(feature = "sha2", feature = "digest"), feature = "tokio"))]
+    async fn test_checksum_file_verify_async() {
+        let (dir, sums) = checksum_dir("sums-async");
+        let reports = checksum_file::verify_async::<sha2::Sha256>(&sums, &dir, 2).await;
+        std::fs::remove_dir_all(&dir).unwrap();
+        check_checksum_reports(&reports);
+    }
+
+    #[test]
+    #[cfg(feature = "base64")]
+    fn test_base64() {
+        use encoding::{decode_base64, decode_base64_url, encode_base64, encode_base64_url};
+        // RFC 4648 section 10
+        let vectors = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+        for (plain, encoded) in vectors {
+            assert_eq!(encode_base64(plain.as_bytes(), true), encoded);
+            let unpadded = encoded.trim_end_matches('=');
+            assert_eq!(encode_base64(plain.as_bytes(), false), unpadded);
+            assert_eq!(decode_base64(encoded).unwrap(), plain.as_bytes());
+            assert_eq!(decode_base64(unpadded).unwrap(), plain.as_bytes());
+        }
+        assert_eq!(encode_base64_url(&[0xfb, 0xff], false), "-_8");
+        assert_eq!(decode_base64_url("-_8").unwrap(), [0xfb, 0xff]);
+        assert_eq!(decode_base64("-_8"), None);
+        assert_eq!(decode_base64("Zm9vY"), None);
+        assert_eq!(decode_base64("Zm9=="), None);
+        assert_eq!(decode_base64("Zh=="), None);
+    }
+
+    #[test]
+    #[cfg(feature = "crc32c")]
+    fn test_finalize_text() {
+        #[cfg(feature = "stdio")]
+        use std::io::Write;
+        fn writer<D: MinDigest + Default>() -> WriteHasher<D, Vec<u8>> {
+            let mut hasher = WriteHasher::new(Vec::new());
+            hasher.update(b"abc");
+            hasher
+        }
+        assert_eq!(writer::<crc32c::Crc32c>().finalize_hex(), "364b3fb7");
+        assert_eq!(writer::<crc32c::Crc32c>().finalize_hex_upper(), "364B3FB7");
+        #[cfg(feature = "stdio")]
+        {
+            let mut hasher = WriteHasher::<crc32c::Crc32c, _>::new(Vec::new());
+            hasher.write_all(b"123456789").unwrap();
+            assert_eq!(hasher.finalize_hex(), "e3069283");
+        }
+        #[cfg(any(feature = "sha2", feature = "digest"))]
+        {
+            extern crate sha2;
+            let sha256 = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+            assert_eq!(writer::<sha2::Sha256>().finalize_hex(), sha256);
+            assert_eq!(
+                writer::<sha2::Sha256>().finalize_hex_upper(),
+                sha256.to_uppercase()
+            );
+            #[cfg(feature = "base64")]
+            {
+                assert_eq!(
+                    writer::<sha2::Sha256>().finalize_base64(),
+                    "ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0="
+                );
+                assert_eq!(
+                    writer::<sha2::Sha256>().finalize_base64_url(),
+                    "ungWv48Bz-pBQUDeXa4iI7ADYaOWF3qctBD_YfIAFa0"
+                );
+            }
+        }
+        #[cfg(feature = "base64")]
+        {
+            assert_eq!(writer::<crc32c::Crc32c>().finalize_base64(), "Nks/tw==");
+            let reader = ReadHasher::<crc32c::Crc32c, _>::new(&b""[..]);
+            assert_eq!(reader.finalize_base64_url(), "AAAAAA");
+        }
+        #[cfg(feature = "md5")]
+        {
+            let mut hasher = WriteHasher::new_with_hasher(Vec::<u8>::new(), md5::Context::new());
+            hasher.update(b"abc");
+            assert_eq!(hasher.finalize_hex(), "900150983cd24fb0d6963f7d28e17f72");
+            #[cfg(feature = "base64")]
+            {
+                let mut hasher =
+                    WriteHasher::new_with_hasher(Vec::<u8>::new(), md5::Context::new());
+                hasher.update(b"abc");
+                assert_eq!(hasher.finalize_base64(), "kAFQmDzST7DWlj99KOF/cg==");
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "base32")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_finalize_base32() {
+        extern crate sha2;
+        let data = std::fs::read("LICENSE").unwrap();
+        let finalize = |f: fn(WriteHasher<sha2::Sha256, ()>) -> String| {
+            let mut hasher = WriteHasher::<sha2::Sha256, _>::new(());
+            hasher.update(&data);
+            f(hasher)
+        };
+        let padded = finalize(WriteHasher::finalize_base32);
+        assert_eq!(
+            padded,
+            "66QVGNTHPK2ICQCW66G2BWSBNW3FKBZXQDYDRBMA2WFB2BF6KVXQ===="
+        );
+        assert_eq!(
+            finalize(WriteHasher::finalize_base32_unpadded),
+            "66QVGNTHPK2ICQCW66G2BWSBNW3FKBZXQDYDRBMA2WFB2BF6KVXQ"
+        );
+        let crockford = finalize(WriteHasher::finalize_base32_crockford);
+        assert_eq!(
+            crockford,
+            "YYGN6DK7FAT82G2PYY6T1PJ1DPV5A1SQG3R3H1C0TP51T15YANQG"
+        );
+        let sha256 =
+            decode_hex("f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f").unwrap();
+        assert_eq!(encoding::decode_base32(&padded).unwrap(), sha256);
+        assert_eq!(
+            encoding::decode_base32_crockford(&crockford).unwrap(),
+            sha256
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "base32")]
+    #[cfg(feature = "crc32c")]
+    fn test_finalize_base32_checksum() {
+        // integer checksums are encoded as their big-endian bytes, crc32c("123456789") = 0xe3069283
+        let mut hasher = WriteHasher::<crc32c::Crc32c, _>::new(());
+        hasher.update(b"123456789");
+        assert_eq!(hasher.finalize_base32(), "4MDJFAY=");
+        let mut hasher = ReadHasher::<crc32c::Crc32c, _>::new(());
+        hasher.update(b"123456789");
+        assert_eq!(hasher.finalize_base32_crockford(), "WC3950R");
+        assert_eq!(
+            encoding::decode_base32_crockford("WC3950R").unwrap(),
+            [0xe3, 0x06, 0x92, 0x83]
+        );
+    }
+
+    #[test]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_eq_hex() {
+        extern crate sha2;
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(());
+        hasher.update(std::fs::read("LICENSE").unwrap());
+        let digest = hasher.finalize();
+        assert!(digest.eq_hex("f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f"));
+        assert!(digest.eq_hex("F7A15336677AB4814056F78DA0DA416DB655073780F0388580D58A1D04BE556F"));
+        assert!(digest.eq_hex("f7A15336677aB4814056F78Da0da416db655073780f0388580d58a1d04be556F"));
+        assert!(
+            digest.eq_hex(" f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f\n")
+        );
+        // odd length, embedded whitespace, not hex, a prefix
+        assert!(!digest.eq_hex("f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556"));
+        assert!(!digest.eq_hex("f7a15336677ab481 4056f78da0da416db655073780f0388580d58a1d04be556f"));
+        assert!(!digest.eq_hex("z7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f"));
+        assert!(!digest.eq_hex("f7a15336"));
+        assert!(!digest.eq_hex(""));
+    }
+
+    #[test]
+    fn test_eq_hex_checksums() {
+        assert!(0xe3069283u32.eq_hex("E3069283"));
+        assert!(0x0306_9283u32.eq_hex("03069283"));
+        // the full width is required
+        assert!(!0x0306_9283u32.eq_hex("3069283"));
+        assert!(!0x0306_9283u32.eq_hex("0003069283"));
+        assert!(0x0123_4567_89ab_cdefu64.eq_hex("0123456789ABCDEF"));
+        assert!(!0x0123_4567_89ab_cdefu64.eq_hex("0123456789abcdee"));
+        assert!(u128::MAX.eq_hex(&"f".repeat(32)));
+        assert!([0xabu8, 0xcd].eq_hex("aBcD"));
+        assert!(vec![0u8; 4].eq_hex("00000000"));
+    }
+
+    #[test]
+    fn test_output_bytes() {
+        assert_eq!(0x1234u32.to_bytes()[..], [0, 0, 0x12, 0x34]);
+        assert_eq!(
+            0x0102_0304_0506_0708u64.to_bytes()[..],
+            [1, 2, 3, 4, 5, 6, 7, 8]
+        );
+        assert_eq!(1u128.to_bytes()[..], [&[0; 15][..], &[1]].concat());
+        assert_eq!([1u8, 2, 3].to_bytes()[..], [1, 2, 3]);
+        assert_eq!(vec![4u8, 5].to_bytes()[..], [4, 5]);
+
+        #[cfg(feature = "crc32c")]
+        {
+            let mut crc = crc32c::Crc32c::new();
+            crc.update(b"123456789");
+            assert_eq!(crc.finalize_bytes(), [0xe3, 0x06, 0x92, 0x83]);
+        }
+        let mut crc = WriteHasher::<crc24::Crc24, _>::new(());
+        crc.update(b"123456789");
+        // crc24 is a u32 as well
+        assert_eq!(crc.finalize_bytes(), [0, 0x21, 0xcf, 0x02]);
+    }
+
+    #[test]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_output_bytes_generic_array() {
+        extern crate sha2;
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(());
+        hasher.update(b"");
+        assert_eq!(
+            hasher.finalize_bytes(),
+            decode_hex("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855").unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "md5")]
+    fn test_output_bytes_md5() {
+        let mut hasher = WriteHasher::new_with_hasher((), md5::Context::new());
+        hasher.update(b"");
+        assert_eq!(
+            hasher.finalize_bytes(),
+            decode_hex("d41d8cd98f00b204e9800998ecf8427e").unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "blake3")]
+    fn test_output_bytes_blake3() {
+        let hash = ::blake3::hash(b"");
+        assert_eq!(hash.to_bytes()[..], hash.as_bytes()[..]);
+        assert_eq!(
+            WriteHasher::<::blake3::Hasher, _>::new(()).finalize_bytes(),
+            decode_hex("af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262").unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(feature = "crc32c")]
+    fn test_read_hasher_expected_checksum() {
+        use std::io::Read;
+        let reader = ReadHasher::<crc32c::Crc32c, _>::new(&b"123456789"[..]);
+        let mut reader = reader.with_expected([0xe3, 0x06, 0x92, 0x83]);
+        reader.read_to_end(&mut Vec::new()).unwrap();
+        let reader = ReadHasher::<crc32c::Crc32c, _>::new(&b"123456789"[..]);
+        let mut reader = reader.with_expected_hex("83920693").unwrap();
+        assert!(reader.read_to_end(&mut Vec::new()).is_err());
+    }
+
+    #[test]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_hash_iter() {
+        extern crate sha2;
+        let data = std::fs::read("LICENSE").unwrap();
+        let digest = hash_iter::<sha2::Sha256>(data.chunks(100));
+        assert_eq!(
+            format!("{:x}", digest),
+            "f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f"
+        );
+        assert_eq!(digest, hash_bytes::<sha2::Sha256>(&data));
+        let owned: Vec<Vec<u8>> = data.chunks(7).map(|chunk| chunk.to_vec()).collect();
+        assert_eq!(digest, hash_iter::<sha2::Sha256>(&owned));
+
+        assert_eq!(
+            format!("{:x}", hash_iter::<sha2::Sha256>(Vec::<&[u8]>::new())),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hash_iter::<sha2::Sha256>(Vec::<&[u8]>::new()),
+            hash_bytes::<sha2::Sha256>([])
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "crc32c")]
+    fn test_hash_iter_checksum() {
+        assert_eq!(
+            hash_iter::<crc32c::Crc32c>(["1234", "", "56789"]),
+            0xe3069283
+        );
+        assert_eq!(hash_bytes::<crc24::Crc24>("123456789"), 0x21cf02);
+        assert_eq!(hash_iter::<crc32c::Crc32c>(core::iter::empty::<&str>()), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_hash_reader() {
+        extern crate sha2;
+        use sha2::Digest;
+        let license = std::fs::read("LICENSE").unwrap();
+        let digest = hash_reader::<sha2::Sha256>(license.as_slice()).unwrap();
+        assert_eq!(format!("{:x}", digest), sha256_hex(&license));
+        assert_eq!(hash_file::<sha2::Sha256>("LICENSE").unwrap(), digest);
+        assert_eq!(
+            hash_reader::<sha2::Sha256>(std::io::empty()).unwrap(),
+            sha2::Sha256::digest(b"")
+        );
+
+        let mut prefixed = sha2::Sha256::new();
+        sha2::Digest::update(&mut prefixed, b"prefix");
+        let mut expected = b"prefix".to_vec();
+        expected.extend_from_slice(&license);
+        assert_eq!(
+            format!(
+                "{:x}",
+                hash_reader_with_hasher(prefixed.clone(), license.as_slice()).unwrap()
+            ),
+            sha256_hex(&expected)
+        );
+        assert_eq!(
+            format!("{:x}", hash_file_with_hasher(prefixed, "LICENSE").unwrap()),
+            sha256_hex(&expected)
+        );
+        assert_eq!(
+            hash_file_with_hasher(sha2::Sha256::new(), "does-not-exist")
+                .unwrap_err()
+                .kind(),
+            std::io::ErrorKind::NotFound
+        );
+    }
+
+    #[cfg(all(unix, feature = "stdio"))]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn dir_fixture(name: &str, reversed: bool) -> std::path::PathBuf {
+        let root = std::env::temp_dir().join(format!("write-hasher-{name}-{}", std::process::id()));
+        let mut files = [
+            ("a.txt", &b"alpha"[..]),
+            ("sub/b.txt", b"beta"),
+            ("sub/deeper/c", b""),
+            (".hidden", b"hidden"),
+            ("sub/.git/config", b"[core]"),
+        ];
+        if reversed {
+            files.reverse();
+        }
+        for (path, contents) in files {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+        std::os::unix::fs::symlink("a.txt", root.join("link")).unwrap();
+        root
+    }
+
+    #[test]
+    #[cfg(all(unix, feature = "stdio"))]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_hash_dir() {
+        extern crate sha2;
+        let root = dir_fixture("dir", false);
+        let copy = dir_fixture("dir-copy", true);
+        let digest = hash_dir::<sha2::Sha256>(&root).unwrap();
+        assert_eq!(hash_dir::<sha2::Sha256>(&root).unwrap(), digest);
+        assert_eq!(hash_dir::<sha2::Sha256>(&copy).unwrap(), digest);
+
+        let visible = HashDirOptions {
+            include_hidden: false,
+            ..Default::default()
+        };
+        let followed = HashDirOptions {
+            follow_symlinks: true,
+            ..Default::default()
+        };
+        let without_hidden = hash_dir_with_options::<sha2::Sha256>(&root, &visible).unwrap();
+        let following = hash_dir_with_options::<sha2::Sha256>(&root, &followed).unwrap();
+        assert_ne!(without_hidden, digest);
+        assert_ne!(following, digest);
+        assert_ne!(following, without_hidden);
+
+        std::fs::write(copy.join("sub/b.txt"), b"BETA").unwrap();
+        assert_ne!(hash_dir::<sha2::Sha256>(&copy).unwrap(), digest);
+        std::os::unix::fs::symlink("..", copy.join("sub/up")).unwrap();
+        assert!(hash_dir::<sha2::Sha256>(&copy).is_ok());
+        let looped = hash_dir_with_options::<sha2::Sha256>(&copy, &followed);
+        assert_eq!(looped.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+        std::fs::remove_dir_all(&root).unwrap();
+        std::fs::remove_dir_all(&copy).unwrap();
+
+        // the framing is stable, pin it with a tree of one file and one symlink
+        let root =
+            std::env::temp_dir().join(format!("write-hasher-dir-kat-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("d")).unwrap();
+        std::fs::write(root.join("d/x"), b"abc").unwrap();
+        std::os::unix::fs::symlink("d/x", root.join("l")).unwrap();
+        let digest = hash_dir::<sha2::Sha256>(&root).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+        let mut framed = b"fd/x\0".to_vec();
+        framed.extend_from_slice(&3u64.to_be_bytes());
+        framed.extend_from_slice(b"abcll\0");
+        framed.extend_from_slice(&3u64.to_be_bytes());
+        framed.extend_from_slice(b"d/x");
+        assert_eq!(format!("{:x}", digest), sha256_hex(&framed));
+    }
+
+    #[tokio::test]
+    #[cfg(all(unix, feature = "stdio", feature = "tokio"))]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_hash_dir_async() {
+        extern crate sha2;
+        let root = dir_fixture("dir-async", false);
+        for options in [
+            HashDirOptions::default(),
+            HashDirOptions {
+                include_hidden: false,
+                follow_symlinks: true,
+            },
+        ] {
+            assert_eq!(
+                hash_dir_with_options_async::<sha2::Sha256>(&root, &options)
+                    .await
+                    .unwrap(),
+                hash_dir_with_options::<sha2::Sha256>(&root, &options).unwrap()
+            );
+        }
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    #[cfg(all(feature = "stdio", feature = "rayon"))]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_hash_files() {
+        extern crate sha2;
+        let dir = std::env::temp_dir().join(format!("write-hasher-files-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("subdir")).unwrap();
+        let mut paths = Vec::new();
+        for i in 0..20 {
+            let path = dir.join(format!("{i}"));
+            std::fs::write(&path, i.to_string().repeat(i * 1000)).unwrap();
+            paths.push(path);
+        }
+        // a directory opens fine but can't be read, and a missing file can't be opened
+        paths.insert(5, dir.join("subdir"));
+        paths.insert(10, dir.join("missing"));
+        let results = hash_files::<sha2::Sha256>(paths.clone());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(results.len(), paths.len());
+        for ((path, result), expected) in results.iter().zip(&paths) {
+            assert_eq!(path, expected);
+            match path.file_name().unwrap().to_str().unwrap() {
+                "subdir" => assert!(result.is_err()),
+                "missing" => {
+                    let kind = result.as_ref().unwrap_err().kind();
+                    assert_eq!(kind, std::io::ErrorKind::NotFound)
+                }
+                name => {
+                    let i: usize = name.parse().unwrap();
+                    let contents = i.to_string().repeat(i * 1000);
+                    let output = result.as_ref().unwrap();
+                    assert_eq!(format!("{:x}", output), sha256_hex(contents.as_bytes()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "stdio", feature = "memmap2"))]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_hash_file_mmap() {
+        extern crate sha2;
+        let dir = std::env::temp_dir().join(format!("write-hasher-mmap-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let window = 64 << 10;
+        for len in [0, 1, 4095, window, window + 1, 3 * window + 100] {
+            let path = dir.join(len.to_string());
+            let contents: Vec<u8> = (0..len).map(|i| (i * 31 % 251) as u8).collect();
+            std::fs::write(&path, &contents).unwrap();
+            let expected = hash_file::<sha2::Sha256>(&path).unwrap();
+            assert_eq!(hash_file_mmap::<sha2::Sha256>(&path).unwrap(), expected);
+            let windowed = helpers::hash_file_mmap_windowed::<sha2::Sha256>(&path, window);
+            assert_eq!(windowed.unwrap(), expected);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            hash_file_mmap::<sha2::Sha256>("LICENSE").unwrap(),
+            hash_file::<sha2::Sha256>("LICENSE").unwrap()
+        );
+        #[cfg(unix)]
+        assert_eq!(
+            format!("{:x}", hash_file_mmap::<sha2::Sha256>("/dev/null").unwrap()),
+            sha256_hex(b"")
+        );
+        let missing = hash_file_mmap::<sha2::Sha256>("does-not-exist");
+        assert_eq!(missing.unwrap_err().kind(), std::io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_hash_file_options() {
+        extern crate sha2;
+        let expected = hash_file::<sha2::Sha256>("LICENSE").unwrap();
+        assert_eq!(
+            format!("{:x}", expected),
+            "f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f"
+        );
+        for advise_sequential in [false, true] {
+            for drop_cache_after in [false, true] {
+                for buffer_size in [0, 1, 100, 1 << 20] {
+                    let options = HashFileOptions {
+                        advise_sequential,
+                        drop_cache_after,
+                        buffer_size,
+                    };
+                    let digest = hash_file_with_options::<sha2::Sha256>("LICENSE", &options);
+                    assert_eq!(digest.unwrap(), expected, "{:?}", options);
+                }
+            }
+        }
+        let options = HashFileOptions {
+            drop_cache_after: true,
+            ..Default::default()
+        };
+        // hints on special files are ignored
+        #[cfg(unix)]
+        assert_eq!(
+            hash_file_with_options::<sha2::Sha256>("/dev/null", &options).unwrap(),
+            hash_bytes::<sha2::Sha256>([])
+        );
+        assert_eq!(
+            hash_file_with_options::<sha2::Sha256>("does-not-exist", &options)
+                .unwrap_err()
+                .kind(),
+            std::io::ErrorKind::NotFound
+        );
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "tokio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_hash_file_options_async() {
+        extern crate sha2;
+        let expected = hash_file_async::<sha2::Sha256>("LICENSE").await.unwrap();
+        assert_eq!(
+            format!("{:x}", expected),
+            "f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f"
+        );
+        for buffer_size in [1, 100, 1 << 20] {
+            let options = HashFileOptions {
+                advise_sequential: buffer_size != 100,
+                drop_cache_after: buffer_size != 1,
+                buffer_size,
+            };
+            let digest = hash_file_with_options_async::<sha2::Sha256>("LICENSE", &options).await;
+            assert_eq!(digest.unwrap(), expected);
+        }
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "tokio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_hash_reader_async() {
+        extern crate sha2;
+        use sha2::Digest;
+        let license = std::fs::read("LICENSE").unwrap();
+        let file = tokio::fs::File::open("LICENSE").await.unwrap();
+        let digest = hash_reader_async::<sha2::Sha256>(file).await.unwrap();
+        assert_eq!(format!("{:x}", digest), sha256_hex(&license));
+
+        let mut prefixed = sha2::Sha256::new();
+        sha2::Digest::update(&mut prefixed, b"prefix");
+        let mut expected = b"prefix".to_vec();
+        expected.extend_from_slice(&license);
+        let digest = hash_reader_with_hasher_async(prefixed.clone(), license.as_slice()).await;
+        assert_eq!(format!("{:x}", digest.unwrap()), sha256_hex(&expected));
+        let digest = hash_file_with_hasher_async(prefixed, "LICENSE").await;
+        assert_eq!(format!("{:x}", digest.unwrap()), sha256_hex(&expected));
+        let missing = hash_file_with_hasher_async(sha2::Sha256::new(), "does-not-exist").await;
+        assert_eq!(missing.unwrap_err().kind(), std::io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_inspect() {
+        extern crate sha2;
+        use std::io::{IoSlice, Write};
+        let data = std::fs::read("LICENSE").unwrap();
+        let mut chunks = Vec::new();
+        let sink = test_util::ShortWriter::new(Vec::new(), 100);
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(sink)
+            .with_inspect(|chunk| chunks.push(chunk.len()));
+        hasher.write_all(&data[..500]).unwrap();
+        // the short writer only takes (part of) the first slice
+        let n = hasher
+            .write_vectored(&[IoSlice::new(&data[500..550]), IoSlice::new(&data[550..])])
+            .unwrap();
+        hasher.write_all(&data[500 + n..]).unwrap();
+        let written = hasher.inner.get_ref().clone();
+        assert_eq!(
+            format!("{:x}", hasher.finalize()),
+            "f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f"
+        );
+        assert_eq!(written, data);
+        assert!(chunks.iter().all(|&len| len <= 100));
+        assert_eq!(chunks.iter().sum::<usize>(), written.len());
+
+        // a vectored write to a writer accepting everything is hashed slice by slice
+        let mut chunks = Vec::new();
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(Vec::new())
+            .with_inspect(|chunk| chunks.push(chunk.to_vec()));
+        let n = hasher
+            .write_vectored(&[IoSlice::new(b"ab"), IoSlice::new(b""), IoSlice::new(b"cde")])
+            .unwrap();
+        assert_eq!(n, 5);
+        drop(hasher);
+        assert_eq!(chunks, [b"ab".to_vec(), b"cde".to_vec()]);
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_write_vectored_stdio() {
+        extern crate sha2;
+        use std::io::{IoSlice, Write};
+        // 3 bytes of a 2 slice, 10 byte write, ending in the second slice
+        let sink = test_util::ShortWriter::new(Vec::new(), 3);
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(sink);
+        let n = hasher
+            .write_vectored(&[IoSlice::new(b"ab"), IoSlice::new(b"cdefghij")])
+            .unwrap();
+        assert_eq!(n, 3);
+        assert_eq!(hasher.get_ref().get_ref(), b"abc");
+        assert_eq!(hasher.bytes_written(), 3);
+        assert_eq!(format!("{:x}", hasher.finalize()), sha256_hex(b"abc"));
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "tokio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_inspect_tokio() {
+        extern crate sha2;
+        use tokio::io::AsyncWriteExt;
+        let data = std::fs::read("LICENSE").unwrap();
+        let mut total = 0;
+        let sink = test_util::ShortWriter::new(test_util::PendingWriter::new(Vec::new()), 64);
+        let mut hasher =
+            WriteHasher::<sha2::Sha256, _>::new(sink).with_inspect(|chunk| total += chunk.len());
+        hasher.write_all(&data).await.unwrap();
+        let written = hasher.inner.get_ref().get_ref().len();
+        assert_eq!(
+            format!("{:x}", hasher.finalize()),
+            "f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f"
+        );
+        assert_eq!(total, written);
+        assert_eq!(total, data.len());
+    }
+
+    /// Every write of at most 13 bytes reported, adding up to `len`
+    #[cfg(any(feature = "stdio", feature = "tokio", feature = "futures"))]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn check_progress(reports: &[(u64, u64)], len: usize) {
+        assert!(reports.iter().all(|&(n, _)| n > 0 && n <= 13));
+        let sum = reports.iter().map(|&(n, _)| n).sum::<u64>();
+        assert_eq!(sum, len as u64);
+        assert_eq!(reports.last().unwrap().1, len as u64);
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_progress() {
+        extern crate sha2;
+        use std::io::Write;
+        use test_util::*;
+        let data = std::fs::read("LICENSE").unwrap();
+        // the callback doesn't have to be Send
+        let reports = std::rc::Rc::new(core::cell::RefCell::new(Vec::new()));
+        let sink = ShortWriter::new(Vec::new(), 13);
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(sink).with_progress({
+            let reports = reports.clone();
+            move |n, total| reports.borrow_mut().push((n, total))
+        });
+        for chunk in data.chunks(100) {
+            hasher.write_all(chunk).unwrap();
+        }
+        assert_eq!(hasher.write(&[]).unwrap(), 0);
+        assert_eq!(format!("{:x}", hasher.finalize()), sha256_hex(&data));
+        check_progress(&reports.borrow(), data.len());
+
+        let mut reports = Vec::new();
+        let sink = FailAfter::new(ShortWriter::new(Vec::new(), 13), 500);
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(sink)
+            .with_progress(|n, total| reports.push((n, total)));
+        hasher.write_all(&data).unwrap_err();
+        drop(hasher);
+        check_progress(&reports, 500);
+
+        // paused writes are reported, a vectored write once
+        let mut reports = Vec::new();
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(Vec::new())
+            .with_progress(|n, total| reports.push((n, total)));
+        hasher.pause_hashing();
+        hasher.write_all(b"HDR1").unwrap();
+        hasher.resume_hashing();
+        let bufs = [
+            std::io::IoSlice::new(&data[..10]),
+            std::io::IoSlice::new(&data[10..30]),
+        ];
+        assert_eq!(hasher.write_vectored(&bufs).unwrap(), 30);
+        assert_eq!(hasher.bytes_hashed(), 30);
+        assert_eq!(format!("{:x}", hasher.finalize()), sha256_hex(&data[..30]));
+        assert_eq!(reports, [(4, 4), (30, 34)]);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "tokio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_progress_tokio() {
+        extern crate sha2;
+        use test_util::*;
+        use tokio::io::AsyncWriteExt;
+        let data = std::fs::read("LICENSE").unwrap();
+        let mut reports = Vec::new();
+        let sink = PendingWriter::new(ShortWriter::new(Vec::new(), 13));
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(sink)
+            .with_progress(|n, total| reports.push((n, total)));
+        for chunk in data.chunks(100) {
+            hasher.write_all(chunk).await.unwrap();
+        }
+        assert_eq!(format!("{:x}", hasher.finalize()), sha256_hex(&data));
+        check_progress(&reports, data.len());
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "futures")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_progress_futures() {
+        extern crate sha2;
+        use futures::io::AsyncWriteExt;
+        use test_util::*;
+        let data = std::fs::read("LICENSE").unwrap();
+        let mut reports = Vec::new();
+        let sink = PendingWriter::new(ShortWriter::new(Vec::new(), 13));
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(sink)
+            .with_progress(|n, total| reports.push((n, total)));
+        for chunk in data.chunks(100) {
+            hasher.write_all(chunk).await.unwrap();
+        }
+        assert_eq!(format!("{:x}", hasher.finalize()), sha256_hex(&data));
+        check_progress(&reports, data.len());
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "tokio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_write_vectored_tokio() {
+        extern crate sha2;
+        use std::io::IoSlice;
+        use tokio::io::AsyncWriteExt;
+        let data = std::fs::read("LICENSE").unwrap();
+        let (first, second) = data.split_at(5);
+
+        // the writer accepts 7 bytes, ending in the middle of the second slice
+        let sink = test_util::ShortWriter::new(Vec::new(), 7);
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(sink);
+        assert!(tokio::io::AsyncWrite::is_write_vectored(&hasher));
+        let n = hasher
+            .write_vectored(&[IoSlice::new(first), IoSlice::new(&[]), IoSlice::new(second)])
+            .await
+            .unwrap();
+        assert_eq!(n, 7);
+        assert_eq!(hasher.get_ref().get_ref(), &data[..7]);
+        assert_eq!(format!("{:x}", hasher.finalize()), sha256_hex(&data[..7]));
+
+        let sink = test_util::PendingWriter::new(test_util::ShortWriter::new(Vec::new(), 100));
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(sink);
+        let mut written = 0;
+        while written < data.len() {
+            let (first, second) = data[written..].split_at((data.len() - written) / 2);
+            written += hasher
+                .write_vectored(&[IoSlice::new(first), IoSlice::new(second)])
+                .await
+                .unwrap();
+        }
+        assert_eq!(hasher.get_ref().get_ref().get_ref(), &data);
+        assert_eq!(format!("{:x}", hasher.finalize()), sha256_hex(&data));
+    }
+
+    #[test]
+    #[cfg(feature = "futures")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_inspect_futures() {
+        extern crate sha2;
+        use futures::io::AsyncWriteExt;
+        let data = std::fs::read("LICENSE").unwrap();
+        let mut total = 0;
+        let sink = test_util::ShortWriter::new(futures::io::Cursor::new(Vec::new()), 64);
+        let mut hasher =
+            WriteHasher::<sha2::Sha256, _>::new(sink).with_inspect(|chunk| total += chunk.len());
+        futures::executor::block_on(hasher.write_all(&data)).unwrap();
+        let written = hasher.inner.get_ref().get_ref().len();
+        hasher.finalize();
+        assert_eq!(total, written);
+        assert_eq!(total, data.len());
+    }
+
+    #[tokio::test]
+    #[cfg(any(feature = "stdio", feature = "tokio", feature = "futures"))]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_write_hasher_write_through_pointers() {
+        extern crate sha2;
+        type Hasher = WriteHasher<sha2::Sha256, Vec<u8>>;
+        let src = std::fs::read("LICENSE").unwrap();
+        let check = |hasher: &Hasher| {
+            assert_eq!(hasher.get_ref(), &src);
+            let digest = MinDigest::finalize(hasher.hasher_ref().clone());
+            assert_eq!(format!("{:x}", digest), sha256_hex(&src));
+        };
+
+        #[cfg(feature = "stdio")]
+        {
+            use std::io::Write;
+            fn write_all(mut writer: impl Write, src: &[u8]) {
+                writer.write_all(src).unwrap();
+            }
+            let mut hasher = Hasher::new(Vec::new());
+            write_all(&mut hasher, &src);
+            check(&hasher);
+            let mut hasher = Box::new(Hasher::new(Vec::new()));
+            write_all(&mut hasher, &src);
+            check(&hasher);
+            let mut hasher = Box::pin(Hasher::new(Vec::new()));
+            write_all(&mut hasher, &src);
+            check(&hasher);
+            let mut hasher = Hasher::new(Vec::new());
+            write_all(core::pin::Pin::new(&mut hasher), &src);
+            check(&hasher);
+        }
+        #[cfg(feature = "tokio")]
+        {
+            use tokio::io::{AsyncWrite, AsyncWriteExt};
+            async fn write_all(mut writer: impl AsyncWrite + Unpin, src: &[u8]) {
+                writer.write_all(src).await.unwrap();
+            }
+            let mut hasher = Hasher::new(Vec::new());
+            write_all(&mut hasher, &src).await;
+            check(&hasher);
+            let mut hasher = Box::new(Hasher::new(Vec::new()));
+            write_all(&mut hasher, &src).await;
+            check(&hasher);
+            let mut hasher = Box::pin(Hasher::new(Vec::new()));
+            write_all(&mut hasher, &src).await;
+            check(&hasher);
+        }
+        #[cfg(feature = "futures")]
+        {
+            use futures::io::{AsyncWrite, AsyncWriteExt};
+            async fn write_all(mut writer: impl AsyncWrite + Unpin, src: &[u8]) {
+                writer.write_all(src).await.unwrap();
+            }
+            let mut hasher = Hasher::new(Vec::new());
+            write_all(&mut hasher, &src).await;
+            check(&hasher);
+            let mut hasher = Box::new(Hasher::new(Vec::new()));
+            write_all(&mut hasher, &src).await;
+            check(&hasher);
+            let mut hasher = Box::pin(Hasher::new(Vec::new()));
+            write_all(&mut hasher, &src).await;
+            check(&hasher);
+        }
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "tokio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_write_hasher_duplex_tokio() {
+        extern crate sha2;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let src = std::fs::read("LICENSE").unwrap();
+        let (local, mut remote) = tokio::io::duplex(64);
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(local);
+
+        let len = src.len();
+        let echo = tokio::spawn(async move {
+            let mut request = vec![0; len];
+            remote.read_exact(&mut request).await.unwrap();
+            remote.write_all(b"response").await.unwrap();
+            request
+        });
+        hasher.write_all(&src).await.unwrap();
+        let mut response = [0; 8];
+        hasher.read_exact(&mut response).await.unwrap();
+        assert_eq!(&response, b"response");
+        assert_eq!(echo.await.unwrap(), src);
+        assert_eq!(format!("{:x}", hasher.finalize()), sha256_hex(&src));
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "tokio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    #[cfg(feature = "crc32c")]
+    async fn test_duplex_hasher_tokio() {
+        extern crate sha2;
+        use crate::crc32c::Crc32c;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let src = std::fs::read("LICENSE").unwrap();
+        let (local, mut remote) = tokio::io::duplex(64);
+        let mut stream = DuplexHasher::<Crc32c, sha2::Sha256, _>::new(local);
+
+        // a big request with a small reply
+        let len = src.len();
+        let server = tokio::spawn(async move {
+            let mut request = vec![0; len];
+            remote.read_exact(&mut request).await.unwrap();
+            remote.write_all(b"201 created").await.unwrap();
+            request
+        });
+        stream.write_all(&src).await.unwrap();
+        let mut reply = Vec::new();
+        stream.read_to_end(&mut reply).await.unwrap();
+        assert_eq!(reply, b"201 created");
+        assert_eq!(server.await.unwrap(), src);
+
+        let (read, written) = stream.finalize_both();
+        assert_eq!(read, hash_bytes::<Crc32c>(b"201 created"));
+        assert_eq!(format!("{:x}", written), sha256_hex(&src));
+    }
+
+    #[test]
+    #[cfg(all(feature = "stdio", feature = "futures"))]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_write_hasher_duplex_passthrough() {
+        extern crate sha2;
+        // a cursor reads and writes the same buffer, the reads don't reach the hasher
+        {
+            use std::io::{Read, Write};
+            let mut hasher =
+                WriteHasher::<sha2::Sha256, _>::new(std::io::Cursor::new(b"existing".to_vec()));
+            let mut read = [0; 4];
+            hasher.read_exact(&mut read).unwrap();
+            assert_eq!(&read, b"exis");
+            hasher.write_all(b"written").unwrap();
+            assert_eq!(hasher.get_ref().get_ref(), b"exiswritten");
+            assert_eq!(format!("{:x}", hasher.finalize()), sha256_hex(b"written"));
+        }
+        {
+            use futures::io::{AsyncReadExt, AsyncWriteExt};
+            let cursor = futures::io::Cursor::new(b"existing".to_vec());
+            let mut hasher = WriteHasher::<sha2::Sha256, _>::new(cursor);
+            let mut read = [0; 4];
+            futures::executor::block_on(hasher.read_exact(&mut read)).unwrap();
+            assert_eq!(&read, b"exis");
+            futures::executor::block_on(hasher.write_all(b"written")).unwrap();
+            assert_eq!(hasher.get_ref().get_ref(), b"exiswritten");
+            assert_eq!(format!("{:x}", hasher.finalize()), sha256_hex(b"written"));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_write_hasher_seek_stdio() {
+        extern crate sha2;
+        use std::io::{Seek, SeekFrom, Write};
+        let src = std::fs::read("LICENSE").unwrap();
+
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(std::io::Cursor::new(Vec::new()));
+        hasher.write_all(&src[..100]).unwrap();
+        assert_eq!(hasher.stream_position().unwrap(), 100);
+        // the position is known now, so seeking to it again doesn't move anything
+        assert_eq!(hasher.seek(SeekFrom::Start(100)).unwrap(), 100);
+        hasher.write_all(&src[100..]).unwrap();
+        assert!(hasher.is_digest_linear());
+        let digest = hasher.try_finalize().unwrap();
+        assert_eq!(format!("{:x}", digest), sha256_hex(&src));
+
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(std::io::Cursor::new(Vec::new()));
+        hasher.write_all(&[0; 8]).unwrap();
+        hasher.write_all(&src).unwrap();
+        hasher.seek(SeekFrom::Start(0)).unwrap();
+        hasher.write_all(&(src.len() as u64).to_le_bytes()).unwrap();
+        hasher.seek(SeekFrom::End(0)).unwrap();
+        assert!(!hasher.is_digest_linear());
+        let (digest, written) = hasher.finalize_into_parts();
+        // the digest is still the one of the bytes in the order they were written
+        let stream = [&[0; 8][..], &src, &(src.len() as u64).to_le_bytes()].concat();
+        assert_eq!(format!("{:x}", digest), sha256_hex(&stream));
+        assert_eq!(written.into_inner()[..8], (src.len() as u64).to_le_bytes());
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "tokio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_write_hasher_seek_tokio() {
+        extern crate sha2;
+        use std::io::SeekFrom;
+        use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+        let src = std::fs::read("LICENSE").unwrap();
+
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(std::io::Cursor::new(Vec::new()));
+        hasher.write_all(&src[..100]).await.unwrap();
+        // position queries don't move the writer
+        assert_eq!(hasher.stream_position().await.unwrap(), 100);
+        hasher.write_all(&src[100..]).await.unwrap();
+        assert!(hasher.is_digest_linear());
+        let digest = hasher.try_finalize().unwrap();
+        assert_eq!(format!("{:x}", digest), sha256_hex(&src));
+
+        // a length placeholder filled in at the end
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(std::io::Cursor::new(Vec::new()));
+        hasher.seek(SeekFrom::Start(0)).await.unwrap();
+        hasher.write_all(&[0; 8]).await.unwrap();
+        hasher.write_all(&src).await.unwrap();
+        assert!(hasher.is_digest_linear());
+        hasher.seek(SeekFrom::Start(0)).await.unwrap();
+        assert!(!hasher.is_digest_linear());
+        hasher
+            .write_all(&(src.len() as u64).to_le_bytes())
+            .await
+            .unwrap();
+        hasher.seek(SeekFrom::End(0)).await.unwrap();
+        assert!(!hasher.is_digest_linear());
+        assert!(matches!(
+            hasher.try_finalize(),
+            Err(HashWriterError::Invalidated)
+        ));
+    }
+
+    #[tokio::test]
+    #[cfg(any(feature = "tokio", feature = "futures"))]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_write_hasher_not_unpin() {
+        extern crate sha2;
+        use core::{marker::PhantomPinned, pin::Pin, task::Poll};
+
+        /// A writer that must stay pinned
+        #[pin_project::pin_project]
+        struct PinnedSink {
+            written: Vec<u8>,
+            #[pin]
+            _pinned: PhantomPinned,
+        }
+
+        #[cfg(feature = "tokio")]
+        impl tokio::io::AsyncWrite for PinnedSink {
+            fn poll_write(
+                self: Pin<&mut Self>,
+                _cx: &mut std::task::Context<'_>,
+                buf: &[u8],
+            ) -> Poll<std::io::Result<usize>> {
+                self.project().written.extend_from_slice(buf);
+                Poll::Ready(Ok(buf.len()))
+            }
+            fn poll_flush(
+                self: Pin<&mut Self>,
+                _cx: &mut std::task::Context<'_>,
+            ) -> Poll<std::io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+            fn poll_shutdown(
+                self: Pin<&mut Self>,
+                _cx: &mut std::task::Context<'_>,
+            ) -> Poll<std::io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        #[cfg(feature = "futures")]
+        impl futures::io::AsyncWrite for PinnedSink {
+            fn poll_write(
+                self: Pin<&mut Self>,
+                _cx: &mut std::task::Context<'_>,
+                buf: &[u8],
+            ) -> Poll<std::io::Result<usize>> {
+                self.project().written.extend_from_slice(buf);
+                Poll::Ready(Ok(buf.len()))
+            }
+            fn poll_flush(
+                self: Pin<&mut Self>,
+                _cx: &mut std::task::Context<'_>,
+            ) -> Poll<std::io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+            fn poll_close(
+                self: Pin<&mut Self>,
+                _cx: &mut std::task::Context<'_>,
+            ) -> Poll<std::io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        let src = std::fs::read("LICENSE").unwrap();
+        let new = || {
+            Box::pin(WriteHasher::<sha2::Sha256, _>::new(PinnedSink {
+                written: Vec::new(),
+                _pinned: PhantomPinned,
+            }))
+        };
+        #[cfg(feature = "tokio")]
+        {
+            use tokio::io::AsyncWriteExt;
+            let mut hasher = new();
+            hasher.as_mut().write_all(&src).await.unwrap();
+            assert_eq!(hasher.get_ref().written, src);
+            let digest = MinDigest::finalize(hasher.hasher_ref().clone());
+            assert_eq!(format!("{:x}", digest), sha256_hex(&src));
+        }
+        #[cfg(feature = "futures")]
+        {
+            use futures::io::AsyncWriteExt;
+            let mut hasher = new();
+            hasher.as_mut().write_all(&src).await.unwrap();
+            assert_eq!(hasher.get_ref().written, src);
+            let digest = MinDigest::finalize(hasher.hasher_ref().clone());
+            assert_eq!(format!("{:x}", digest), sha256_hex(&src));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "futures")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_write_vectored_futures() {
+        extern crate sha2;
+        use futures::io::AsyncWriteExt;
+        use std::io::IoSlice;
+        let data = std::fs::read("LICENSE").unwrap();
+        let (first, second) = data.split_at(5);
+
+        // the writer accepts 7 bytes, ending in the middle of the second slice
+        let sink = test_util::ShortWriter::new(futures::io::Cursor::new(Vec::new()), 7);
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(sink);
+        let n = futures::executor::block_on(hasher.write_vectored(&[
+            IoSlice::new(first),
+            IoSlice::new(&[]),
+            IoSlice::new(second),
+        ]))
+        .unwrap();
+        assert_eq!(n, 7);
+        assert_eq!(hasher.get_ref().get_ref().get_ref(), &data[..7]);
+        assert_eq!(format!("{:x}", hasher.finalize()), sha256_hex(&data[..7]));
+
+        // a slice ending exactly where the write does
+        let sink = test_util::ShortWriter::new(futures::io::Cursor::new(Vec::new()), 5);
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(sink);
+        let n = futures::executor::block_on(
+            hasher.write_vectored(&[IoSlice::new(first), IoSlice::new(second)]),
+        )
+        .unwrap();
+        assert_eq!(n, 5);
+        assert_eq!(format!("{:x}", hasher.finalize()), sha256_hex(first));
+    }
+
+    #[tokio::test]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    #[cfg(any(feature = "stdio", feature = "tokio"))]
+    async fn test_background_write_hasher() {
+        extern crate sha2;
+        use background::BackgroundWriteHasher;
+        use test_util::*;
+        let src = std::fs::read("LICENSE").unwrap();
+
+        #[cfg(feature = "stdio")]
+        {
+            use std::io::Write;
+            let sink = ShortWriter::new(Vec::new(), 13);
+            let mut hasher = BackgroundWriteHasher::<sha2::Sha256, _>::with_capacity(
+                sink,
+                Default::default(),
+                1,
+            );
+            hasher.write_all(&src).unwrap();
+            let (digest, written) = hasher.finalize_into_parts();
+            assert_eq!(written.into_inner(), src);
+            assert_eq!(format!("{:x}", digest), sha256_hex(&src));
+
+            // errors of the inner writer are returned right away
+            let mut hasher =
+                BackgroundWriteHasher::<sha2::Sha256, _>::new(FailAfter::new(Vec::new(), 500));
+            hasher.write_all(&src).unwrap_err();
+            assert_eq!(format!("{:x}", hasher.finalize()), sha256_hex(&src[..500]));
+        }
+        #[cfg(feature = "tokio")]
+        {
+            use tokio::io::AsyncWriteExt;
+            let sink = PendingWriter::new(ShortWriter::new(Vec::new(), 13));
+            let mut hasher = BackgroundWriteHasher::<sha2::Sha256, _>::with_capacity(
+                sink,
+                Default::default(),
+                1,
+            );
+            hasher.write_all(&src).await.unwrap();
+            hasher.flush().await.unwrap();
+            let (digest, written) = hasher.finalize_into_parts();
+            assert_eq!(written.into_inner().into_inner(), src);
+            let mut inline = WriteHasher::<sha2::Sha256, _>::new(tokio::io::sink());
+            inline.write_all(&src).await.unwrap();
+            assert_eq!(digest, inline.finalize());
+
+            let mut hasher =
+                BackgroundWriteHasher::<sha2::Sha256, _>::new(FailAfter::new(Vec::new(), 500));
+            hasher.write_all(&src).await.unwrap_err();
+            assert_eq!(format!("{:x}", hasher.finalize()), sha256_hex(&src[..500]));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    #[cfg(feature = "crc32c")]
+    fn test_multi_digest() {
+        extern crate sha2;
+        use crate::crc32c::Crc32c;
+        use std::io::Write;
+        let src = std::fs::read("LICENSE").unwrap();
+
+        let mut hasher = WriteHasher::<MultiDigest<(sha2::Sha256, Crc32c)>, _>::new(Vec::new());
+        hasher.write_all(&src).unwrap();
+        let (sha, crc) = hasher.finalize();
+        assert_eq!(format!("{:x}", sha), sha256_hex(&src));
+        assert_eq!(crc, hash_bytes::<Crc32c>(&src));
+
+        let mut hasher =
+            MultiDigest::<(Crc32c, sha2::Sha256, crate::crc24::Crc24, Crc32c)>::default();
+        for chunk in src.chunks(100) {
+            hasher.update(chunk);
+        }
+        let (crc, sha, crc24, again) = hasher.finalize();
+        assert_eq!(crc, hash_bytes::<Crc32c>(&src));
+        assert_eq!(format!("{:x}", sha), sha256_hex(&src));
+        assert_eq!(crc24, hash_bytes::<crate::crc24::Crc24>(&src));
+        assert_eq!(again, crc);
+    }
+
+    #[test]
+    #[cfg(feature = "crc32c")]
+    fn test_delimited_digest() {
+        use crate::crc32c::Crc32c;
+        let data = b"first\nsecond record\n\nlast";
+        let expected = |include: bool| {
+            let records: [&[u8]; 4] = if include {
+                [b"first\n", b"second record\n", b"\n", b"last"]
+            } else {
+                [b"first", b"second record", b"", b"last"]
+            };
+            records.map(hash_bytes::<Crc32c>).to_vec()
+        };
+        for include in [false, true] {
+            // every split point, including the delimiters landing on chunk boundaries
+            for split in 0..=data.len() {
+                let mut hasher = DelimitedDigest::<Crc32c>::new(b'\n', include);
+                hasher.update(&data[..split]);
+                hasher.update(&data[split..]);
+                assert_eq!(hasher.finalize(), expected(include), "split at {}", split);
+            }
+            let mut hasher = DelimitedDigest::<Crc32c>::new(b'\n', include);
+            for byte in data {
+                hasher.update([*byte]);
+            }
+            assert_eq!(hasher.finalize(), expected(include));
+        }
+
+        // a trailing delimiter doesn't start another record
+        let mut hasher = DelimitedDigest::<Crc32c>::new(b'\n', false);
+        hasher.update(b"a\nb");
+        hasher.update(b"\n");
+        assert_eq!(hasher.finalize(), [b"a", b"b"].map(hash_bytes::<Crc32c>));
+        assert!(DelimitedDigest::<Crc32c>::new(b'\n', false)
+            .finalize()
+            .is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "crc32c")]
+    fn test_delimited_digest_on_record() {
+        use crate::crc32c::Crc32c;
+        use std::sync::{Arc, Mutex};
+        let records = Arc::new(Mutex::new(Vec::new()));
+        let sink = records.clone();
+        let mut hasher = DelimitedDigest::<Crc32c>::new(b',', true)
+            .with_on_record(move |index, digest| sink.lock().unwrap().push((index, digest)));
+        hasher.update(b"ab,c");
+        assert_eq!(
+            *records.lock().unwrap(),
+            [(0, hash_bytes::<Crc32c>(b"ab,"))]
+        );
+        hasher.update(b"d,e");
+        assert!(hasher.finalize().is_empty());
+        assert_eq!(
+            *records.lock().unwrap(),
+            [
+                (0, hash_bytes::<Crc32c>(b"ab,")),
+                (1, hash_bytes::<Crc32c>(b"cd,")),
+                (2, hash_bytes::<Crc32c>(b"e")),
+            ]
+        );
+    }
+
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    #[test]
+    fn test_shared_digest_sequential_writers() {
+        extern crate sha2;
+        use std::io::Write;
+        let data = std::fs::read("LICENSE").unwrap();
+        let (first, second) = data.split_at(400);
+        let shared = SharedDigest::new(sha2::Sha256::default());
+        let mut a = WriteHasher::new_with_hasher(Vec::new(), shared.clone());
+        let mut b = WriteHasher::new_with_hasher(Vec::new(), shared.clone());
+        assert_eq!(shared.handles(), 3);
+        drop(shared);
+
+        let first = first.to_vec();
+        let writer_a = std::thread::spawn(move || {
+            a.write_all(&first).unwrap();
+            a
+        });
+        let a = writer_a.join().unwrap();
+        b.write_all(second).unwrap();
+
+        assert_eq!(a.inner, &data[..400]);
+        let b = b.hasher.try_finalize().unwrap_err();
+        assert_eq!(a.finalize(), None);
+        let digest = b.try_finalize().unwrap();
+        assert_eq!(
+            crate::helpers::encode_hex(&digest),
+            "f7a15336677ab4814056f78da0da416db655073780f0388580d58a1d04be556f"
+        );
+    }
+
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    #[test]
+    fn test_shared_digest_interleaved_writes() {
+        extern crate sha2;
+        use std::io::Write;
+        let data = std::fs::read("LICENSE").unwrap();
+        let chunks: Vec<&[u8]> = data.chunks(37).collect();
+
+        let shared = SharedDigest::new(sha2::Sha256::default());
+        let mut a = WriteHasher::new_with_hasher(Vec::new(), shared.clone());
+        let mut b = WriteHasher::new_with_hasher(Vec::new(), shared);
+        let local = LocalSharedDigest::new(sha2::Sha256::default());
+        let mut local_a = WriteHasher::new_with_hasher(Vec::new(), local.clone());
+        let mut local_b = WriteHasher::new_with_hasher(Vec::new(), local);
+        // alternating between the writers, in write order that's the whole file
+        for (i, chunk) in chunks.iter().enumerate() {
+            let (writer, local_writer) = match i % 2 {
+                0 => (&mut a, &mut local_a),
+                _ => (&mut b, &mut local_b),
+            };
+            writer.write_all(chunk).unwrap();
+            local_writer.write_all(chunk).unwrap();
+        }
+        let evens: Vec<u8> = chunks.iter().step_by(2).flat_map(|c| c.to_vec()).collect();
+        assert_eq!(a.get_ref(), &evens);
+        assert_eq!(local_a.get_ref(), &evens);
+
+        let whole = hash_bytes::<sha2::Sha256>(&data);
+        assert_eq!(local_b.hasher_ref().handles(), 2);
+        assert_eq!(local_a.finalize(), None);
+        assert_eq!(local_b.finalize(), Some(whole));
+
+        // any handle can take the digest so far with finalize_reset
+        assert_eq!(a.finalize_reset(), Some(whole));
+        b.write_all(b"abc").unwrap();
+        assert_eq!(a.finalize(), None);
+        assert_eq!(b.finalize(), Some(hash_bytes::<sha2::Sha256>(b"abc")));
+    }
+
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    #[test]
+    fn test_borrowed_digest() {
+        extern crate sha2;
+        struct Upload {
+            hasher: sha2::Sha256,
+        }
+        let data = std::fs::read("LICENSE").unwrap();
+        let (head, tail) = data.split_at(1000);
+        let mut upload = Upload {
+            hasher: sha2::Sha256::default(),
+        };
+        let mut out = Vec::new();
+        let mut writer = WriteHasher::new_with_hasher(&mut out, upload.hasher.borrowed());
+        std::io::copy(&mut &head[..], &mut writer).unwrap();
+        // finalizing the borrow doesn't consume the hasher
+        assert_eq!(writer.finalize(), hash_bytes::<sha2::Sha256>(head));
+        assert_eq!(out, head);
+        MinDigest::update(&mut upload.hasher, tail);
+        assert_eq!(
+            MinDigest::finalize(upload.hasher),
+            hash_bytes::<sha2::Sha256>(&data)
+        );
+    }
+
+    fn check_combine_random_splits<D: CombineDigest + Default>()
+    where
+        D::Output: Clone + PartialEq + core::fmt::Debug,
+    {
+        let data = std::fs::read("LICENSE").unwrap();
+        let whole = hash_bytes::<D>(&data);
+        // a fixed xorshift so failures are reproducible
+        let mut state = 0x2545F491u32;
+        let mut next = |bound: usize| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as usize % bound
+        };
+        for _ in 0..64 {
+            let mut cuts: Vec<usize> = (0..next(6)).map(|_| next(data.len() + 1)).collect();
+            cuts.extend([0, data.len()]);
+            cuts.sort_unstable();
+            let parts: Vec<_> = cuts
+                .windows(2)
+                .map(|w| (hash_bytes::<D>(&data[w[0]..w[1]]), (w[1] - w[0]) as u64))
+                .collect();
+            assert_eq!(combine_all::<D>(&parts), whole, "cuts {:?}", cuts);
+
+            // (a + b) + c == a + (b + c)
+            let (a, b) = (next(data.len() + 1), next(data.len() + 1));
+            let (a, b) = (a.min(b), a.max(b));
+            let hash = |range: core::ops::Range<usize>| hash_bytes::<D>(&data[range]);
+            let len = |range: core::ops::Range<usize>| range.len() as u64;
+            let left = D::combine(
+                D::combine(hash(0..a), hash(a..b), len(a..b)),
+                hash(b..data.len()),
+                len(b..data.len()),
+            );
+            let right = D::combine(
+                hash(0..a),
+                D::combine(hash(a..b), hash(b..data.len()), len(b..data.len())),
+                len(a..data.len()),
+            );
+            assert_eq!(left, whole);
+            assert_eq!(right, whole);
+        }
+        assert_eq!(combine_all::<D>(&[]), hash_bytes::<D>(b""));
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(feature = "crc32c")]
+    fn test_crc32c_segments() {
+        use crate::crc32c::Crc32c;
+        use std::io::Write;
+        let mut state = 0x9E3779B97F4A7C15u64;
+        let data: Vec<u8> = (0..5 * 1024 * 1024 + 13)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect();
+        let whole = hash_bytes::<Crc32c>(&data);
+
+        let mut segments = Vec::new();
+        let mut rest = &data[..];
+        for len in [1, 1_000_003, 0, 65_536, 2_500_000, 7] {
+            let (segment, tail) = rest.split_at(len);
+            segments.push(segment);
+            rest = tail;
+        }
+        segments.push(rest);
+        let hashed: Vec<(Crc32c, u64)> = std::thread::scope(|scope| {
+            let handles: Vec<_> = segments
+                .iter()
+                .map(|segment| {
+                    scope.spawn(move || {
+                        let mut hasher = WriteHasher::<Crc32c, _>::new(std::io::sink());
+                        for chunk in segment.chunks(8191) {
+                            hasher.write_all(chunk).unwrap();
+                        }
+                        (hasher.into_hasher(), segment.len() as u64)
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        let merged = hashed
+            .into_iter()
+            .fold(Crc32c::new(), |crc, (segment, len)| {
+                crc.combine(segment, len)
+            });
+        assert_eq!(merged.finalize(), whole);
+
+        // resuming from a stored partial checksum
+        let (first, second) = data.split_at(3_000_001);
+        let partial = hash_bytes::<Crc32c>(first);
+        let mut hasher = WriteHasher::<Crc32c, _>::new_with_initial(std::io::sink(), partial);
+        hasher.write_all(second).unwrap();
+        assert_eq!(hasher.finalize(), whole);
+    }
+
+    #[test]
+    fn test_combine_digest() {
+        #[cfg(feature = "crc32c")]
+        check_combine_random_splits::<crate::crc32c::Crc32c>();
+        check_combine_random_splits::<crate::crc24::Crc24>();
+        #[cfg(feature = "crc32fast")]
+        check_combine_random_splits::<::crc32fast::Hasher>();
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_non_blocking_copier() {
+        extern crate sha2;
+        use test_util::*;
+        let src = std::fs::read("LICENSE").unwrap();
+
+        // blocks on every other read and write, and the writer takes at most 100 bytes at once
+        let mut reader = PendingReader::new(&src[..]);
+        let mut writer = PendingWriter::new(ShortWriter::new(Vec::new(), 100));
+        let mut copier = NonBlockingCopier::with_capacity(sha2::Sha256::default(), 256);
+        let mut blocked = 0;
+        let digest = loop {
+            match copier.advance(&mut reader, &mut writer).unwrap() {
+                CopyProgress::WouldBlock => {
+                    // nothing written is left unhashed, nothing hashed is left unwritten
+                    assert_eq!(copier.copied(), writer.get_ref().get_ref().len() as u64);
+                    assert!(copier.pending().len() <= 256);
+                    blocked += 1;
+                }
+                CopyProgress::Done(digest) => break digest,
+            }
+        };
+        assert!(blocked > 10);
+        assert_eq!(writer.into_inner().into_inner(), src);
+        assert_eq!(sha256_hex(&src), format!("{:x}", digest));
+        assert!(copier.advance(&mut reader, &mut Vec::new()).is_err());
+
+        // a failing writer keeps what it accepted hashed and the rest pending
+        let mut copier = NonBlockingCopier::with_capacity(sha2::Sha256::default(), 256);
+        let mut writer = FailAfter::new(Vec::new(), 300);
+        copier.advance(&mut &src[..], &mut writer).unwrap_err();
+        assert_eq!(copier.copied(), 300);
+        assert_eq!(copier.pending(), &src[300..512]);
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(feature = "crc32c")]
+    fn test_self_test() {
+        // runs the vectors of whichever algorithms the current features enable
+        self_test::run_all().unwrap();
+
+        #[derive(Default)]
+        struct Broken(crc32c::Crc32c);
+        impl MinDigest for Broken {
+            type Output = u32;
+            fn update(&mut self, data: impl AsRef<[u8]>) {
+                // drops the last byte of every write
+                let data = data.as_ref();
+                self.0.update(&data[..data.len().saturating_sub(1)])
+            }
+            fn finalize(self) -> u32 {
+                self.0.finalize()
+            }
+        }
+        impl self_test::KnownAnswers for Broken {
+            const NAME: &'static str = "broken";
+            const VECTORS: &'static [self_test::Vector] = &[self_test::Vector {
+                input: b"123456789",
+                expected: "e3069283",
+            }];
+            fn new_hasher() -> Self {
+                Default::default()
+            }
+        }
+        let failure = self_test::<Broken>().unwrap_err();
+        assert_eq!(failure.algorithm, "broken");
+        assert_eq!(failure.input, b"123456789");
+        assert!(!failure.chunked);
+        assert_eq!(
+            failure.actual,
+            hash_bytes::<crc32c::Crc32c>(b"12345678").to_be_bytes()
+        );
+        assert_eq!(
+            failure.to_string(),
+            "broken self-test failed on \"123456789\" (single-shot): expected e3069283, got \
+             6087809a"
+        );
+    }
+
+    /// The length of the prefix of `data` with the CRC-32C `crc`, if any
+    #[cfg(unix)]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    #[cfg(feature = "crc32c")]
+    fn crc32c_prefix_len(data: &[u8], crc: u32) -> Option<usize> {
+        let mut hasher = crc32c::Crc32c::new();
+        for (i, byte) in data.iter().enumerate() {
+            if hasher.finalize() == crc {
+                return Some(i);
+            }
+            hasher.update([*byte]);
+        }
+        (hasher.finalize() == crc).then_some(data.len())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    #[cfg(feature = "crc32c")]
+    fn test_hash_pipe_to_command() {
+        extern crate sha2;
+        use std::process::{Command, Stdio};
+        let src = std::fs::read("LICENSE").unwrap();
+        let mut cmd = Command::new("cat");
+        cmd.stdout(Stdio::null());
+        let (status, digest) = hash_pipe_to_command::<sha2::Sha256>(&src[..], &mut cmd).unwrap();
+        assert!(status.success());
+        assert_eq!(sha256_hex(&src), format!("{:x}", digest));
+
+        // exits without reading, more than fits in the pipe buffer so the writes have to fail
+        let big: Vec<u8> = (0..4 << 20).map(|i: u32| (i % 251) as u8).collect();
+        let (status, digest) = hash_pipe_to_command::<crc32c::Crc32c>(
+            &big[..],
+            Command::new("sh").args(["-c", "exit 3"]),
+        )
+        .unwrap();
+        assert_eq!(status.code(), Some(3));
+        let accepted = crc32c_prefix_len(&big, digest).unwrap();
+        assert!(accepted < big.len());
+    }
+
+    #[tokio::test]
+    #[cfg(unix)]
+    #[cfg(feature = "tokio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    #[cfg(feature = "crc32c")]
+    async fn test_hash_pipe_to_command_async() {
+        extern crate sha2;
+        use std::process::Stdio;
+        use tokio::process::Command;
+        let src = std::fs::read("LICENSE").unwrap();
+        let mut cmd = Command::new("cat");
+        cmd.stdout(Stdio::null());
+        let (status, digest) = hash_pipe_to_command_async::<sha2::Sha256>(&src[..], &mut cmd)
+            .await
+            .unwrap();
+        assert!(status.success());
+        assert_eq!(sha256_hex(&src), format!("{:x}", digest));
+
+        let big: Vec<u8> = (0..4 << 20).map(|i: u32| (i % 251) as u8).collect();
+        let (status, digest) = hash_pipe_to_command_async::<crc32c::Crc32c>(
+            &big[..],
+            Command::new("sh").args(["-c", "exit 3"]),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status.code(), Some(3));
+        assert!(crc32c_prefix_len(&big, digest).unwrap() < big.len());
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_optional_digest() {
+        extern crate sha2;
+        use std::cell::Cell;
+        use std::io::Write;
+        use std::rc::Rc;
+
+        /// Counts the bytes it's asked to hash
+        #[derive(Default)]
+        struct Counting(Rc<Cell<usize>>);
+        impl MinDigest for Counting {
+            type Output = usize;
+            fn update(&mut self, data: impl AsRef<[u8]>) {
+                self.0.set(self.0.get() + data.as_ref().len())
+            }
+            fn finalize(self) -> usize {
+                self.0.get()
+            }
+        }
+
+        let src = std::fs::read("LICENSE").unwrap();
+        for enabled in [true, false] {
+            let work = Rc::new(Cell::new(0));
+            let hasher = OptionalDigest(enabled.then(|| Counting(work.clone())));
+            let mut writer = WriteHasher::new_with_hasher(Vec::new(), hasher);
+            for chunk in src.chunks(100) {
+                writer.write_all(chunk).unwrap();
+            }
+            let (counted, written) = writer.finalize_into_parts();
+            assert_eq!(written, src);
+            if enabled {
+                assert_eq!(counted, Some(src.len()));
+                assert_eq!(work.get(), src.len());
+            } else {
+                assert_eq!(counted, None);
+                assert_eq!(work.get(), 0);
+            }
+        }
+
+        // the same writer type either way
+        let writer = |enabled| {
+            let hasher = OptionalDigest::<sha2::Sha256>::new(enabled);
+            let mut writer = WriteHasher::new_with_hasher(std::io::sink(), hasher);
+            writer.write_all(&src).unwrap();
+            writer
+        };
+        let digest = writer(true).finalize().unwrap();
+        assert_eq!(format!("{:x}", digest), sha256_hex(&src));
+        assert_eq!(writer(false).finalize(), None);
+
+        // enabled by default
+        let mut hasher = WriteHasher::<OptionalDigest<sha2::Sha256>, _>::new(std::io::sink());
+        assert!(hasher.hasher_ref().is_enabled());
+        hasher.write_all(b"abc").unwrap();
+        assert_eq!(
+            hasher.finalize_reset(),
+            Some(hash_bytes::<sha2::Sha256>(b"abc"))
+        );
+        assert!(!OptionalDigest::<sha2::Sha256>::disabled().is_enabled());
+        assert_eq!(
+            OptionalDigest::<sha2::Sha256>::disabled().finalize_reset(),
+            None
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    #[cfg(feature = "crc32c")]
+    fn test_mapped_digest() {
+        extern crate sha2;
+        use std::io::Write;
+        let src = std::fs::read("LICENSE").unwrap();
+        let hasher = TeeDigest::new(
+            MinDigest::map(sha2::Sha256::default(), |digest| {
+                <[u8; 8]>::try_from(&digest[..8]).unwrap()
+            }),
+            crc32c::Crc32c::new().map(|crc| format!("crc32c:{:08x}", crc)),
+        );
+        let mut writer = WriteHasher::new_with_hasher(Vec::new(), hasher);
+        writer.write_all(&src).unwrap();
+        let (key, crc) = writer.finalize();
+        assert_eq!(crate::helpers::encode_hex(&key), sha256_hex(&src)[..16]);
+        assert_eq!(
+            crc,
+            format!("crc32c:{:08x}", hash_bytes::<crc32c::Crc32c>(&src))
+        );
+
+        // maps compose
+        let mut hasher = crc32c::Crc32c::new().map(u64::from).map(|crc| crc << 32);
+        hasher.update(b"123456789");
+        assert_eq!(hasher.finalize(), 0xe306_9283_0000_0000);
+    }
+
+    #[test]
+    #[cfg(all(feature = "stdio", feature = "sha1cd"))]
+    fn test_sha1cd() {
+        use sha1cd::{Sha1Cd, Sha1CdOutput};
+        use std::io::Write;
+        let hash = |hasher: Sha1Cd, data: &[u8]| {
+            let mut writer = WriteHasher::new_with_hasher(std::io::sink(), hasher);
+            for chunk in data.chunks(100) {
+                writer.write_all(chunk).unwrap();
+            }
+            writer.finalize()
+        };
+
+        let license = std::fs::read("LICENSE").unwrap();
+        assert!(!hash(Sha1Cd::new(), &license).collision_detected);
+        let mut hasher = Sha1Cd::new();
+        hasher.update(&license);
+        assert_eq!(
+            crate::helpers::encode_hex(&hasher.try_finalize().unwrap()),
+            "98b75da58d99527551a37c84f7dd66c7817aee69"
+        );
+
+        // the colliding 320 byte prefixes of the SHAttered PDFs
+        let first = std::fs::read("testdata/shattered-1-prefix.bin").unwrap();
+        let second = std::fs::read("testdata/shattered-2-prefix.bin").unwrap();
+        assert_ne!(first, second);
+        let (a, b) = (hash(Sha1Cd::new(), &first), hash(Sha1Cd::new(), &second));
+        assert!(a.collision_detected && b.collision_detected);
+        let mut hasher = Sha1Cd::new();
+        hasher.update(&first);
+        assert!(hasher.try_finalize().is_err());
+        // mitigated by default, so they no longer collide
+        assert_ne!(a.digest, b.digest);
+
+        let unmitigated = || Sha1Cd::from(sha1cd::Builder::default().safe_hash(false).build());
+        let (a, b) = (hash(unmitigated(), &first), hash(unmitigated(), &second));
+        assert_eq!(
+            a,
+            Sha1CdOutput {
+                digest: crate::helpers::decode_hex("f92d74e3874587aaf443d1db961d4e26dde13e9c")
+                    .unwrap()
+                    .try_into()
+                    .unwrap(),
+                collision_detected: true,
+            }
+        );
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    #[cfg(all(feature = "stdio", feature = "uuid"))]
+    fn test_name_based_uuids() {
+        use std::io::Write;
+        use uuids::{UuidV3, UuidV5};
+        let license = std::fs::read("LICENSE").unwrap();
+        for namespace in [::uuid::Uuid::NAMESPACE_DNS, ::uuid::Uuid::NAMESPACE_OID] {
+            for name in [&b""[..], b"example.com", &license] {
+                let mut v5 = WriteHasher::new_with_hasher(Vec::new(), UuidV5::new(namespace));
+                let mut v3 = WriteHasher::new_with_hasher(Vec::new(), UuidV3::new(namespace));
+                for chunk in name.chunks(7) {
+                    v5.write_all(chunk).unwrap();
+                    v3.write_all(chunk).unwrap();
+                }
+                // the namespace only goes into the digest
+                assert_eq!(v5.inner, name);
+                assert_eq!(v5.finalize(), ::uuid::Uuid::new_v5(&namespace, name));
+                assert_eq!(v3.finalize(), ::uuid::Uuid::new_v3(&namespace, name));
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "stdio", target_os = "linux"))]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_hash_file_sparse() {
+        extern crate sha2;
+        use std::io::{Seek, SeekFrom, Write};
+        use std::os::unix::fs::MetadataExt;
+        let license = std::fs::read("LICENSE").unwrap();
+        let path = std::env::temp_dir().join(format!("write-hasher-sparse-{}", std::process::id()));
+        let mut file = std::fs::File::create(&path).unwrap();
+        // leading hole, data, a hole, data and a trailing hole
+        for offset in [1 << 20, (1 << 20) + 8192, 9 << 20] {
+            file.seek(SeekFrom::Start(offset)).unwrap();
+            file.write_all(&license).unwrap();
+        }
+        file.set_len(16 << 20).unwrap();
+        drop(file);
+
+        let expected = hash_file::<sha2::Sha256>(&path).unwrap();
+        assert_eq!(hash_file_sparse::<sha2::Sha256>(&path).unwrap(), expected);
+        let mut file = std::fs::File::open(&path).unwrap();
+        let mut hasher = sha2::Sha256::default();
+        let read = helpers::hash_extents(&mut file, &mut hasher).unwrap();
+        assert_eq!(MinDigest::finalize(hasher), expected);
+        let metadata = file.metadata().unwrap();
+        if metadata.blocks() * 512 < metadata.len() {
+            // the filesystem did make it sparse
+            assert!(read < 1 << 20, "read {} bytes", read);
+        }
+        std::fs::remove_file(&path).unwrap();
+
+        let path = std::env::temp_dir().join(format!("write-hasher-holes-{}", std::process::id()));
+        std::fs::File::create(&path)
+            .unwrap()
+            .set_len(100_000)
+            .unwrap();
+        assert_eq!(
+            hash_file_sparse::<sha2::Sha256>(&path).unwrap(),
+            hash_bytes::<sha2::Sha256>(vec![0; 100_000])
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_readers_equal_by_hash() {
+        extern crate sha2;
+        let src = std::fs::read("LICENSE").unwrap();
+        let mut changed = src.clone();
+        changed[500] ^= 1;
+        let equal = |a: &[u8], b: &[u8], lengths| {
+            readers_equal_by_hash::<sha2::Sha256>(a, test_util::ShortReader::new(b, 7), lengths)
+                .unwrap()
+        };
+        assert!(equal(&src, &src, None));
+        assert!(equal(&src, &src, Some((1092, 1092))));
+        assert!(!equal(&src, &src[..1000], None));
+        assert!(!equal(&src, &changed, None));
+        assert!(equal(b"", b"", None));
+        // the lengths alone decide, nothing is read
+        let unreadable = test_util::FailAfter::new(&src[..], 0);
+        assert!(
+            !readers_equal_by_hash::<sha2::Sha256>(&src[..], unreadable, Some((1092, 1))).unwrap()
+        );
+        let unreadable = test_util::FailAfter::new(&src[..], 0);
+        readers_equal_by_hash::<sha2::Sha256>(&src[..], unreadable, None).unwrap_err();
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "tokio")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_readers_equal_by_hash_async() {
+        extern crate sha2;
+        let src = std::fs::read("LICENSE").unwrap();
+        let mut changed = src.clone();
+        changed[500] ^= 1;
+        let equal = |a: Vec<u8>, b: Vec<u8>| async move {
+            let a = test_util::PendingReader::new(&a[..]);
+            let b = test_util::ShortReader::new(&b[..], 7);
+            readers_equal_by_hash_async::<sha2::Sha256>(a, b, None)
+                .await
+                .unwrap()
+        };
+        assert!(equal(src.clone(), src.clone()).await);
+        assert!(!equal(src.clone(), src[..1000].to_vec()).await);
+        assert!(!equal(src.clone(), changed).await);
+        assert!(
+            !readers_equal_by_hash_async::<sha2::Sha256>(&src[..], &src[..], Some((1, 2)))
+                .await
+                .unwrap()
+        );
+        let unreadable = test_util::FailAfter::new(&src[..], 0);
+        readers_equal_by_hash_async::<sha2::Sha256>(&src[..], unreadable, None)
+            .await
+            .unwrap_err();
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "compat")]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    async fn test_compat_constructors() {
+        extern crate sha2;
+        let src = std::fs::read("LICENSE").unwrap();
+
+        // a futures sink driven by tokio::io::copy
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new_tokio_from_futures(
+            futures::io::Cursor::new(Vec::new()),
+        );
+        tokio::io::copy(&mut &src[..], &mut hasher).await.unwrap();
+        assert_eq!(hasher.inner.get_ref().get_ref(), &src);
+        assert_eq!(sha256_hex(&src), format!("{:x}", hasher.finalize()));
+
+        // a tokio sink driven by futures::io::copy
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new_futures_from_tokio(Vec::new());
+        futures::io::copy(&src[..], &mut hasher).await.unwrap();
+        assert_eq!(hasher.inner.get_ref(), &src);
+        assert_eq!(sha256_hex(&src), format!("{:x}", hasher.finalize()));
+
+        // switching ecosystems halfway keeps the digest going
+        let (head, tail) = src.split_at(500);
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(Vec::new());
+        tokio::io::AsyncWriteExt::write_all(&mut hasher, head)
+            .await
+            .unwrap();
+        let mut hasher = hasher.into_futures_compat();
+        futures::io::AsyncWriteExt::write_all(&mut hasher, tail)
+            .await
+            .unwrap();
+        assert_eq!(hasher.inner.get_ref(), &src);
+        assert_eq!(sha256_hex(&src), format!("{:x}", hasher.finalize()));
+
+        let mut hasher = WriteHasher::<sha2::Sha256, _>::new(futures::io::Cursor::new(Vec::new()));
+        futures::io::AsyncWriteExt::write_all(&mut hasher, head)
+            .await
+            .unwrap();
+        let mut hasher = hasher.into_tokio_compat();
+        tokio::io::AsyncWriteExt::write_all(&mut hasher, tail)
+            .await
+            .unwrap();
+        assert_eq!(hasher.inner.get_ref().get_ref(), &src);
+        assert_eq!(sha256_hex(&src), format!("{:x}", hasher.finalize()));
+    }
+
+    #[test]
+    #[cfg(feature = "stdio")]
+    #[cfg(feature = "crc32c")]
+    fn test_normalizing_write_hasher() {
+        use crate::crc32c::Crc32c;
+        use std::io::Write;
+        let all = Normalization {
+            crlf_to_lf: true,
+            trailing_newline: true,
+            strip_bom: true,
+        };
+        let normalized = |chunks: &[&[u8]], normalization| {
+            let mut hasher =
+                NormalizingWriteHasher::new_normalizing(Vec::new(), Crc32c::new(), normalization);
+            for chunk in chunks {
+                hasher.write_all(chunk).unwrap();
+            }
+            // the inner writer gets the raw bytes
+            assert_eq!(hasher.inner, chunks.concat());
+            hasher.finalize()
+        };
+        let crc = |data: &[u8]| hash_bytes::<Crc32c>(data);
+
+        let unix = b"first\nsecond\nthird";
+        let windows = b"\xEF\xBB\xBFfirst\r\nsecond\r\nthird";
+        // every way of splitting the Windows text in two, including the \r\n and the BOM
+        for split in 0..=windows.len() {
+            let (a, b) = windows.split_at(split);
+            assert_eq!(normalized(&[a, b], all), crc(b"first\nsecond\nthird\n"));
+            assert_eq!(normalized(&[a, b], all), normalized(&[unix], all));
+        }
+        assert_eq!(normalized(&[b"a\r", b"\r\n", b"\n"], all), crc(b"a\r\n\n"));
+        // lone \r are kept, also at the end
+        assert_eq!(
+            normalized(&[b"a\rb\r"], Normalization::default()),
+            crc(b"a\rb\r")
+        );
+        assert_eq!(normalized(&[b"a\r", b"b"], all), crc(b"a\rb\n"));
+        // a BOM anywhere else, or an incomplete one, is text
+        assert_eq!(
+            normalized(&[b"a\xEF\xBB\xBF"], all),
+            crc(b"a\xEF\xBB\xBF\n")
+        );
+        assert_eq!(normalized(&[b"\xEF", b"\xBBx"], all), crc(b"\xEF\xBBx\n"));
+        assert_eq!(normalized(&[b"\xEF\xBB"], all), crc(b"\xEF\xBB\n"));
+        // empty text stays empty, the BOM alone is empty text
+        assert_eq!(normalized(&[], all), crc(b""));
+        assert_eq!(normalized(&[b"\xEF\xBB", b"\xBF"], all), crc(b""));
+        assert_eq!(normalized(&[b"\r\n"], Normalization::default()), crc(b"\n"));
+
+        // normalizing what the inner writer receives too
+        let mut hasher = NormalizingWriteHasher::new_normalizing(
+            NormalizingWriter::new(Vec::new(), all),
+            Crc32c::new(),
+            all,
+        );
+        for chunk in windows.chunks(3) {
+            hasher.write_all(chunk).unwrap();
+        }
+        hasher.inner.finish().unwrap();
+        assert_eq!(hasher.inner.get_ref(), b"first\nsecond\nthird\n");
+        assert_eq!(hasher.finalize(), crc(b"first\nsecond\nthird\n"));
+    }
+
+    #[test]
+    #[cfg(feature = "flate2")]
+    fn test_gzip_verifying_reader() {
+        use crate::gzip::{GzipHashingEncoder, GzipVerifyingReader};
+        use std::io::{Read, Write};
+        let license = std::fs::read("LICENSE").unwrap();
+        let fixture = std::fs::read("testdata/LICENSE.gz").unwrap();
+        let inflate = |data: &[u8]| {
+            let mut out = Vec::new();
+            GzipVerifyingReader::new(data)
+                .read_to_end(&mut out)
+                .map(|_| out)
+        };
+
+        assert_eq!(inflate(&fixture).unwrap(), license);
+
+        // a second member, written by the encoder
+        let mut encoder = GzipHashingEncoder::new(fixture.clone(), Default::default());
+        encoder.write_all(b"second member").unwrap();
+        let (multi, trailer) = encoder.finish().unwrap();
+        assert_eq!(trailer.isize, 13);
+        let mut reader = GzipVerifyingReader::new(std::io::BufReader::with_capacity(7, &multi[..]));
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).unwrap();
+        assert_eq!(out, [&license[..], b"second member"].concat());
+        assert_eq!(reader.trailers().len(), 2);
+        assert_eq!(reader.trailers()[1], trailer);
+        assert_eq!(reader.trailers()[0].isize, 1092);
+
+        let mut bad_trailer = fixture.clone();
+        let crc_at = bad_trailer.len() - 8;
+        bad_trailer[crc_at] ^= 0x01;
+        let err = inflate(&bad_trailer).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("digest mismatch"));
+
+        let mut bad_length = fixture.clone();
+        let isize_at = bad_length.len() - 4;
+        bad_length[isize_at] ^= 0x01;
+        let err = inflate(&bad_length).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("length mismatch"));
+
+        let mut bad_data = fixture.clone();
+        let middle = bad_data.len() / 2;
+        bad_data[middle] ^= 0x55;
+        let err = inflate(&bad_data).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            std::io::ErrorKind::InvalidData | std::io::ErrorKind::UnexpectedEof
+        ));
+
+        let err = inflate(&fixture[..fixture.len() - 3]).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+        let err = inflate(b"not gzip at all").unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    #[cfg(all(feature = "xattr", feature = "stdio", unix))]
+    #[cfg(any(feature = "sha2", feature = "digest"))]
+    fn test_hash_file_cached() {
+        extern crate sha2;
+        use crate::helpers::{hash_file_cached, CacheOptions, CacheStatus};
+        let options = CacheOptions::new("sha256");
+        let cached = |path: &std::path::Path| {
+            let (digest, status) = hash_file_cached::<sha2::Sha256>(path, &options).unwrap();
+            (crate::helpers::encode_hex(&digest), status)
+        };
+        let license = std::fs::read("LICENSE").unwrap();
+
+        let path = std::env::temp_dir().join(format!("write-hasher-cached-{}", std::process::id()));
+        std::fs::write(&path, &license).unwrap();
+        let (digest, status) = cached(&path);
+        assert_eq!(digest, sha256_hex(&license));
+        if status == CacheStatus::Unsupported {
+            eprintln!("skipping, no extended attributes in {}", path.display());
+            std::fs::remove_file(&path).unwrap();
+            return;
+        }
+        assert_eq!(status, CacheStatus::Miss);
+        assert_eq!(cached(&path), (sha256_hex(&license), CacheStatus::Hit));
+
+        // a modified file invalidates the cache
+        let mut modified = license.clone();
+        modified.extend_from_slice(b"appended");
+        std::fs::write(&path, &modified).unwrap();
+        assert_eq!(cached(&path), (sha256_hex(&modified), CacheStatus::Miss));
+        assert_eq!(cached(&path), (sha256_hex(&modified), CacheStatus::Hit));
+        std::fs::remove_file(&path).unwrap();
+
+        // look for a filesystem without user extended attributes, tmpfs doesn't have them on
+        // older kernels
+        for dir in ["/dev/shm".into(), std::env::temp_dir()] {
+            let path = dir.join(format!("write-hasher-uncached-{}", std::process::id()));
+            if std::fs::write(&path, &license).is_err() {
+                continue;
+            }
+            let supported = xattr::set(&path, "user.write_hasher.probe", b"")
+                .map_or_else(|e| e.raw_os_error() != Some(libc::ENOTSUP), |_| true);
+            if !supported {
+                assert_eq!(
+                    cached(&path),
+                    (sha256_hex(&license), CacheStatus::Unsupported)
+                );
+                assert_eq!(
+                    cached(&path),
+                    (sha256_hex(&license), CacheStatus::Unsupported)
+                );
+            }
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
+
     #[tokio::test]
     #[ignore]
     #[cfg(all(feature = "tokio", feature = "stdio"))]
-    #[cfg(any(feature = "crc32c", feature = "digest"))]
+    #[cfg(feature = "crc32c")]
     async fn test_tokio_bigfile() {
         let mut src = tokio::fs::File::open("file.zip").await.unwrap();
         let sink = tokio::io::sink();
@@ -0,0 +1,131 @@
+//! [Multihash](https://multiformats.io/multihash/) output, the digest prefixed with the
+//! varint code of its algorithm and the varint length, as used in IPFS CIDs
+//!
+//! [`WriteHasher::finalize_multihash`] is only available for the algorithms with a registered
+//! code, through [`MultihashDigest`].
+//!
+//! ```rust
+//! extern crate sha2;
+//! use std::io::Write;
+//! use write_hasher::{multihash, WriteHasher};
+//! let mut writer = WriteHasher::<sha2::Sha256, _>::new(std::io::sink());
+//! writer.write_all(b"hello world").unwrap();
+//! let multihash = writer.finalize_multihash();
+//! assert_eq!(&multihash[..2], [0x12, 0x20]);
+//! let (code, digest) = multihash::parse_multihash(&multihash).unwrap();
+//! assert_eq!(code, multihash::SHA2_256);
+//! assert_eq!(digest.len(), 32);
+//! ```
+
+use crate::{HashWriterError, MinDigest, OutputBytes, ReadHasher, WriteHasher};
+
+pub const SHA1: u64 = 0x11;
+pub const SHA2_256: u64 = 0x12;
+pub const SHA2_512: u64 = 0x13;
+pub const SHA3_512: u64 = 0x14;
+pub const SHA3_384: u64 = 0x15;
+pub const SHA3_256: u64 = 0x16;
+pub const SHA3_224: u64 = 0x17;
+pub const BLAKE2B_512: u64 = 0xb240;
+pub const BLAKE2S_256: u64 = 0xb260;
+
+/// A hasher whose algorithm has a registered multihash code
+pub trait MultihashDigest {
+    const CODE: u64;
+}
+
+macro_rules! multihash_codes {
+    ($($(#[$attr:meta])* $hasher:ty => $code:expr;)*) => {
+        $(
+            $(#[$attr])*
+            impl MultihashDigest for $hasher {
+                const CODE: u64 = $code;
+            }
+        )*
+    };
+}
+
+multihash_codes! {
+    sha1::Sha1 => SHA1;
+    sha2::Sha256 => SHA2_256;
+    sha2::Sha512 => SHA2_512;
+    blake2::Blake2b512 => BLAKE2B_512;
+    blake2::Blake2s256 => BLAKE2S_256;
+    #[cfg(feature = "sha3")]
+    sha3::Sha3_512 => SHA3_512;
+    #[cfg(feature = "sha3")]
+    sha3::Sha3_384 => SHA3_384;
+    #[cfg(feature = "sha3")]
+    sha3::Sha3_256 => SHA3_256;
+    #[cfg(feature = "sha3")]
+    sha3::Sha3_224 => SHA3_224;
+}
+
+/// Append `value` as an unsigned LEB128 varint
+pub fn encode_varint(mut value: u64, out: &mut Vec<u8>) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Decode an unsigned varint from the start of `bytes`, returning it and the number of bytes it
+/// took. Returns `None` if it's truncated, not minimally encoded or longer than the 9 bytes
+/// multiformats allows.
+pub fn decode_varint(bytes: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    for (i, &byte) in bytes.iter().enumerate().take(9) {
+        value |= ((byte & 0x7F) as u64) << (i * 7);
+        if byte & 0x80 == 0 {
+            // a trailing zero byte would make the encoding ambiguous
+            if byte == 0 && i > 0 {
+                return None;
+            }
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
+/// Prefix `digest` with the varint `code` and length
+pub fn to_multihash(code: u64, digest: &[u8]) -> Vec<u8> {
+    let mut multihash = Vec::with_capacity(digest.len() + 4);
+    encode_varint(code, &mut multihash);
+    encode_varint(digest.len() as u64, &mut multihash);
+    multihash.extend_from_slice(digest);
+    multihash
+}
+
+/// Split a multihash into its code and digest, the digest has to fill the rest of `multihash`
+pub fn parse_multihash(multihash: &[u8]) -> Result<(u64, &[u8]), HashWriterError> {
+    let invalid = |what: &str| HashWriterError::Parse(format!("invalid multihash {}", what));
+    let (code, code_len) = decode_varint(multihash).ok_or_else(|| invalid("code"))?;
+    let rest = &multihash[code_len..];
+    let (len, len_len) = decode_varint(rest).ok_or_else(|| invalid("length"))?;
+    let digest = &rest[len_len..];
+    if digest.len() as u64 != len {
+        return Err(HashWriterError::Length {
+            expected: len,
+            actual: digest.len() as u64,
+        });
+    }
+    Ok((code, digest))
+}
+
+macro_rules! finalize_multihash {
+    ($hasher:ident) => {
+        impl<D: MinDigest + MultihashDigest, T> $hasher<D, T>
+        where
+            D::Output: OutputBytes,
+        {
+            /// Finalize the digest as a multihash
+            pub fn finalize_multihash(self) -> Vec<u8> {
+                to_multihash(D::CODE, &self.hasher.finalize_bytes())
+            }
+        }
+    };
+}
+
+finalize_multihash!(WriteHasher);
+finalize_multihash!(ReadHasher);
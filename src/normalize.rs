@@ -0,0 +1,234 @@
+//! Newline and BOM normalization of text, so the same text hashes the same whatever platform
+//! wrote it.
+//!
+//! [`NormalizingWriteHasher`] hashes the normalized text while the raw bytes pass to the inner
+//! writer unchanged. Wrap the inner writer in a [`NormalizingWriter`] to normalize what it
+//! receives as well.
+
+use crate::{MinDigest, WriteHasher};
+
+const BOM: [u8; 3] = [0xEF, 0xBB, 0xBF];
+
+/// Which normalizations to apply
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Normalization {
+    /// Turn every `\r\n` into `\n`, lone `\r`s are kept
+    pub crlf_to_lf: bool,
+    /// End non-empty text with a `\n` if it doesn't already
+    pub trailing_newline: bool,
+    /// Drop a UTF-8 byte order mark at the very start
+    pub strip_bom: bool,
+}
+
+impl Default for Normalization {
+    /// Only `\r\n` to `\n`
+    fn default() -> Self {
+        Self {
+            crlf_to_lf: true,
+            trailing_newline: false,
+            strip_bom: false,
+        }
+    }
+}
+
+/// The streaming state of a normalization, the part of the input that can't be decided yet (a
+/// `\r` or a partial BOM at the end of a chunk) is held back until the next chunk or `finish`
+#[derive(Debug, Clone)]
+pub struct Normalizer {
+    normalization: Normalization,
+    checking_bom: bool,
+    bom_matched: usize,
+    pending_cr: bool,
+    last: Option<u8>,
+}
+
+impl Normalizer {
+    pub fn new(normalization: Normalization) -> Self {
+        Self {
+            normalization,
+            checking_bom: normalization.strip_bom,
+            bom_matched: 0,
+            pending_cr: false,
+            last: None,
+        }
+    }
+
+    fn emit(&mut self, data: &[u8], out: &mut impl FnMut(&[u8])) {
+        if let Some(&last) = data.last() {
+            self.last = Some(last);
+            out(data);
+        }
+    }
+
+    /// Normalize the next chunk of the input, passing the normalized output to `out`
+    pub fn feed(&mut self, mut data: &[u8], mut out: impl FnMut(&[u8])) {
+        while self.checking_bom {
+            let Some((&byte, rest)) = data.split_first() else {
+                return;
+            };
+            if byte == BOM[self.bom_matched] {
+                self.bom_matched += 1;
+                self.checking_bom = self.bom_matched < BOM.len();
+                data = rest;
+            } else {
+                // not a BOM after all, the bytes held back are text
+                self.checking_bom = false;
+                self.emit(&BOM[..self.bom_matched], &mut out);
+            }
+        }
+        if !self.normalization.crlf_to_lf {
+            self.emit(data, &mut out);
+            return;
+        }
+        if self.pending_cr && !data.is_empty() {
+            self.pending_cr = false;
+            // before a \n it's dropped, the \n starts the span below
+            if data[0] != b'\n' {
+                self.emit(b"\r", &mut out);
+            }
+        }
+        let mut start = 0;
+        let mut i = start;
+        while i < data.len() {
+            if data[i] == b'\r' {
+                match data.get(i + 1) {
+                    None => {
+                        self.emit(&data[start..i], &mut out);
+                        self.pending_cr = true;
+                        return;
+                    }
+                    Some(b'\n') => {
+                        // drop the \r, the \n starts the next span
+                        self.emit(&data[start..i], &mut out);
+                        start = i + 1;
+                    }
+                    Some(_) => (),
+                }
+            }
+            i += 1;
+        }
+        self.emit(&data[start..], &mut out);
+    }
+
+    /// Pass whatever was held back and the trailing newline (if enabled) to `out`
+    pub fn finish(&mut self, mut out: impl FnMut(&[u8])) {
+        if self.checking_bom {
+            self.checking_bom = false;
+            self.emit(&BOM[..self.bom_matched], &mut out);
+        }
+        if self.pending_cr {
+            self.pending_cr = false;
+            self.emit(b"\r", &mut out);
+        }
+        if self.normalization.trailing_newline && matches!(self.last, Some(last) if last != b'\n') {
+            self.emit(b"\n", &mut out);
+        }
+    }
+}
+
+/// A hasher that hashes the normalized data, see [`NormalizingWriteHasher`]
+#[derive(Debug, Clone)]
+pub struct Normalize<D> {
+    hasher: D,
+    normalizer: Normalizer,
+}
+
+impl<D> Normalize<D> {
+    pub fn new(hasher: D, normalization: Normalization) -> Self {
+        Self {
+            hasher,
+            normalizer: Normalizer::new(normalization),
+        }
+    }
+}
+
+impl<D: MinDigest> MinDigest for Normalize<D> {
+    type Output = D::Output;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        let hasher = &mut self.hasher;
+        self.normalizer
+            .feed(data.as_ref(), |data| hasher.update(data))
+    }
+    fn finalize(mut self) -> Self::Output {
+        let hasher = &mut self.hasher;
+        self.normalizer.finish(|data| hasher.update(data));
+        self.hasher.finalize()
+    }
+}
+
+/// A [`WriteHasher`] hashing the normalized text written through it, while the inner writer gets
+/// the raw bytes.
+///
+/// ```rust
+/// # #[cfg(all(feature = "stdio", feature = "crc32c"))] {
+/// use std::io::Write;
+/// use write_hasher::{crc32c::Crc32c, MinDigest, Normalization, NormalizingWriteHasher};
+/// let mut windows = NormalizingWriteHasher::new_normalizing(Vec::new(), Crc32c::new(), Normalization::default());
+/// windows.write_all(b"line one\r\nline two\r\n").unwrap();
+/// let mut unix = NormalizingWriteHasher::new_normalizing(Vec::new(), Crc32c::new(), Normalization::default());
+/// unix.write_all(b"line one\nline two\n").unwrap();
+/// assert_eq!(windows.finalize(), unix.finalize());
+/// # }
+/// ```
+pub type NormalizingWriteHasher<D, T> = WriteHasher<Normalize<D>, T>;
+
+impl<D, T> WriteHasher<Normalize<D>, T> {
+    pub fn new_normalizing(inner: T, hasher: D, normalization: Normalization) -> Self {
+        Self::new_with_hasher(inner, Normalize::new(hasher, normalization))
+    }
+}
+
+/// A writer normalizing the text written through it before passing it to the inner writer.
+///
+/// Call [`finish`](Self::finish) at the end of the text to write what was held back (a final
+/// `\r`, or the trailing newline).
+#[cfg(feature = "stdio")]
+#[derive(Debug)]
+pub struct NormalizingWriter<T> {
+    inner: T,
+    normalizer: Normalizer,
+}
+
+#[cfg(feature = "stdio")]
+impl<T: std::io::Write> NormalizingWriter<T> {
+    pub fn new(inner: T, normalization: Normalization) -> Self {
+        Self {
+            inner,
+            normalizer: Normalizer::new(normalization),
+        }
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn finish(&mut self) -> std::io::Result<()> {
+        let mut r = Ok(());
+        let inner = &mut self.inner;
+        self.normalizer.finish(|data| {
+            if r.is_ok() {
+                r = inner.write_all(data);
+            }
+        });
+        r?;
+        self.inner.flush()
+    }
+}
+
+#[cfg(feature = "stdio")]
+impl<T: std::io::Write> std::io::Write for NormalizingWriter<T> {
+    /// The whole of `buf` is always taken, the normalized output is written with `write_all`
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut r = Ok(());
+        let inner = &mut self.inner;
+        self.normalizer.feed(buf, |data| {
+            if r.is_ok() {
+                r = inner.write_all(data);
+            }
+        });
+        r.map(|()| buf.len())
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,475 @@
+//! [`ReadHasher`], hashing everything read through it
+
+#[cfg(any(feature = "futures", feature = "tokio"))]
+use core::{pin::Pin, task::Poll};
+
+use crate::encoding::bytes_eq;
+use crate::helpers::decode_hex;
+use crate::{HashWriterError, MinDigest, OutputBytes};
+
+/// A hasher that will be a wrapper over any Read / AsyncRead object and transparently calculate
+/// hash for any data read from it
+#[cfg_attr(any(feature = "futures", feature = "tokio"), pin_project::pin_project)]
+#[derive(Default)]
+pub struct ReadHasher<D, T> {
+    pub(crate) hasher: D,
+    #[cfg_attr(any(feature = "futures", feature = "tokio"), pin)]
+    pub(crate) inner: T,
+    expected: Option<Expected<D>>,
+    seek: SeekState,
+}
+
+/// What a [`ReadHasher`] does when the consumer seeks the stream
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SeekPolicy {
+    /// Fail any seek that would move the stream position with an
+    /// [`Unsupported`][std::io::ErrorKind::Unsupported] error
+    #[default]
+    Forbid,
+    /// Allow seeking but mark the digest as invalid once the position moved
+    InvalidateDigest,
+    /// Allow seeking and only hash the bytes read beyond the furthest position read so far, so
+    /// re-reading a section after seeking backwards doesn't hash it twice.
+    ///
+    /// Positions are tracked relative to where the inner reader was when it was wrapped, so it
+    /// should be wrapped at the start of the stream. Reading past a section that was skipped by
+    /// seeking forward leaves a gap in the digest and marks it as invalid.
+    HashOnlyFirstPass,
+}
+
+#[derive(Debug, Default)]
+struct SeekState {
+    policy: SeekPolicy,
+    #[cfg(any(feature = "stdio", feature = "tokio", feature = "futures"))]
+    position: u64,
+    #[cfg(any(feature = "stdio", feature = "tokio", feature = "futures"))]
+    hashed_to: u64,
+    /// How much of the buffer last returned by `poll_fill_buf` wasn't consumed yet, these bytes
+    /// are already hashed and counted in `position`
+    #[cfg(any(feature = "stdio", feature = "tokio", feature = "futures"))]
+    buffered: usize,
+    /// Whether a tokio `start_seek` is waiting for `poll_complete`, which is also polled on its
+    /// own to get the position
+    #[cfg(feature = "tokio")]
+    seeking: bool,
+    invalidated: bool,
+}
+
+#[cfg(any(feature = "stdio", feature = "tokio", feature = "futures"))]
+impl SeekState {
+    /// Advance the position over `buf` (read at the current position) and return the part of it
+    /// that should be hashed
+    fn advance<'a>(&mut self, buf: &'a [u8]) -> &'a [u8] {
+        let start = self.position;
+        self.position += buf.len() as u64;
+        if self.policy != SeekPolicy::HashOnlyFirstPass {
+            return buf;
+        }
+        if start > self.hashed_to {
+            self.invalidated = true;
+        }
+        let skip = self.hashed_to.saturating_sub(start).min(buf.len() as u64);
+        self.hashed_to = self.hashed_to.max(self.position);
+        &buf[skip as usize..]
+    }
+
+    /// The position of the consumer, which is behind `position` by what was buffered but not
+    /// consumed yet
+    fn consumed(&self) -> u64 {
+        self.position - self.buffered as u64
+    }
+
+    fn start_seek(&self, pos: std::io::SeekFrom) -> std::io::Result<()> {
+        let stays = matches!(pos, std::io::SeekFrom::Current(0))
+            || pos == std::io::SeekFrom::Start(self.consumed());
+        if self.policy == SeekPolicy::Forbid && !stays {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "seeking is forbidden by the seek policy",
+            ));
+        }
+        Ok(())
+    }
+
+    fn complete_seek(&mut self, position: u64) {
+        if position == self.consumed() {
+            // the buffered bytes come next either way, from the buffer or read again
+            return;
+        }
+        if self.policy == SeekPolicy::InvalidateDigest {
+            self.invalidated = true;
+        }
+        self.position = position;
+        self.buffered = 0;
+    }
+}
+
+#[cfg(any(feature = "futures", feature = "tokio"))]
+impl SeekState {
+    /// Advance over the buffer returned by `poll_fill_buf` and return the part of it that should
+    /// be hashed, skipping what was handed out by an earlier call and not consumed since
+    fn fill<'a>(&mut self, buf: &'a [u8]) -> &'a [u8] {
+        let fresh = &buf[self.buffered.min(buf.len())..];
+        self.buffered = self.buffered.max(buf.len());
+        self.advance(fresh)
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.buffered = self.buffered.saturating_sub(amt);
+    }
+
+    /// Same as [`advance`](Self::advance) for a `poll_read`, which returns what is left in the
+    /// buffer first
+    fn read<'a>(&mut self, buf: &'a [u8]) -> &'a [u8] {
+        let skip = self.buffered.min(buf.len());
+        self.buffered -= skip;
+        self.advance(&buf[skip..])
+    }
+}
+
+/// The digest a [`ReadHasher`] is expected to produce once the inner reader hits EOF
+struct Expected<D> {
+    digest: Vec<u8>,
+    actual: fn(&D) -> Vec<u8>,
+}
+
+impl<D> Expected<D> {
+    fn check(&self, hasher: &D, seek: &SeekState) -> Result<(), HashWriterError> {
+        if seek.invalidated {
+            return Err(HashWriterError::Invalidated);
+        }
+        let actual = (self.actual)(hasher);
+        if bytes_eq(&actual, &self.digest) {
+            Ok(())
+        } else {
+            Err(HashWriterError::Mismatch {
+                expected: self.digest.clone(),
+                actual,
+            })
+        }
+    }
+}
+
+impl<D, T> ReadHasher<D, T> {
+    pub fn new_with_hasher(inner: T, hasher: D) -> Self {
+        Self {
+            hasher,
+            inner,
+            expected: None,
+            seek: SeekState::default(),
+        }
+    }
+
+    pub fn new(inner: T) -> Self
+    where
+        D: Default,
+    {
+        Self {
+            hasher: Default::default(),
+            inner,
+            expected: None,
+            seek: SeekState::default(),
+        }
+    }
+
+    /// Set what happens when the consumer seeks the stream, seeking is forbidden by default
+    pub fn with_seek_policy(mut self, policy: SeekPolicy) -> Self {
+        self.seek.policy = policy;
+        self
+    }
+
+    /// Whether the digest still corresponds to a single linear pass over the stream
+    pub fn is_digest_valid(&self) -> bool {
+        !self.seek.invalidated
+    }
+
+    /// Verify the data read against `expected` once the inner reader reaches EOF.
+    ///
+    /// The read that would return `Ok(0)` returns an [`InvalidData`][std::io::ErrorKind::InvalidData]
+    /// error wrapping a [`HashWriterError`] instead if the digest doesn't match, so `read_to_end` /
+    /// `copy` call sites get the verification for free. Consumers that never read until EOF can
+    /// call [`verify`](Self::verify).
+    ///
+    /// `expected` is compared with the [`OutputBytes`] of the digest, i.e. big-endian bytes for
+    /// the integer checksums.
+    pub fn with_expected(mut self, expected: impl AsRef<[u8]>) -> Self
+    where
+        D: MinDigest + Clone,
+        D::Output: OutputBytes,
+    {
+        self.expected = Some(Expected {
+            digest: expected.as_ref().to_vec(),
+            actual: |hasher| hasher.clone().finalize_bytes(),
+        });
+        self
+    }
+
+    /// Same as [`with_expected`](Self::with_expected) but takes the expected digest as a hex
+    /// string (in any case)
+    pub fn with_expected_hex(self, expected: &str) -> Result<Self, HashWriterError>
+    where
+        D: MinDigest + Clone,
+        D::Output: OutputBytes,
+    {
+        let expected = decode_hex(expected).ok_or_else(|| {
+            HashWriterError::Parse("expected digest is not a valid hex string".into())
+        })?;
+        Ok(self.with_expected(expected))
+    }
+
+    /// Check the digest of the data read so far against the expected digest.
+    ///
+    /// Returns an [`Io`](HashWriterError::Io) error of kind
+    /// [`InvalidInput`][std::io::ErrorKind::InvalidInput] if no expected digest was set with
+    /// [`with_expected`](Self::with_expected).
+    pub fn verify(&self) -> Result<(), HashWriterError> {
+        match &self.expected {
+            Some(expected) => expected.check(&self.hasher, &self.seek),
+            None => Err(HashWriterError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "no expected digest to verify against",
+            ))),
+        }
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Reading from the inner reader directly bypasses the hasher.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<D: MinDigest, T> ReadHasher<D, T> {
+    /// Finalize the hash of the data read so far.
+    ///
+    /// If the consumer stopped reading before reaching EOF the digest only covers that prefix,
+    /// use [`finalize_ensure_eof`](Self::finalize_ensure_eof) to hash the whole stream. When
+    /// reading through `AsyncBufRead` the prefix includes everything `poll_fill_buf` returned,
+    /// consumed or not.
+    pub fn finalize_partial(self) -> D::Output {
+        self.hasher.finalize()
+    }
+}
+
+#[cfg(feature = "stdio")]
+impl<D: MinDigest, T: std::io::Read> ReadHasher<D, T> {
+    /// Read and hash whatever is left in the inner reader and then finalize the hash, so the
+    /// digest always covers the complete stream.
+    pub fn finalize_ensure_eof(mut self) -> std::io::Result<D::Output> {
+        std::io::copy(&mut self, &mut std::io::sink())?;
+        Ok(self.hasher.finalize())
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+impl<D: MinDigest, T: tokio::io::AsyncRead + std::marker::Unpin> ReadHasher<D, T> {
+    /// Async version of [`finalize_ensure_eof`](Self::finalize_ensure_eof)
+    pub async fn finalize_ensure_eof_async(mut self) -> std::io::Result<D::Output> {
+        use tokio::io::AsyncRead;
+        let mut buf = [0u8; 8 * 1024];
+        loop {
+            let mut buf = tokio::io::ReadBuf::new(&mut buf);
+            core::future::poll_fn(|cx| Pin::new(&mut self).poll_read(cx, &mut buf)).await?;
+            if buf.filled().is_empty() {
+                break;
+            }
+        }
+        Ok(self.hasher.finalize())
+    }
+}
+
+impl<MD: MinDigest, T> MinDigest for ReadHasher<MD, T> {
+    type Output = MD::Output;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        self.hasher.update(data)
+    }
+    fn finalize(self) -> MD::Output {
+        self.hasher.finalize()
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+impl<D: MinDigest, T: tokio::io::AsyncRead + std::marker::Unpin> tokio::io::AsyncRead
+    for ReadHasher<D, T>
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        let ah = self.project();
+        let before = buf.filled().len();
+        let r = ah.inner.poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = r {
+            let read = &buf.filled()[before..];
+            if read.is_empty() && buf.remaining() > 0 {
+                if let Some(expected) = ah.expected {
+                    return Poll::Ready(expected.check(ah.hasher, ah.seek).map_err(Into::into));
+                }
+            }
+            ah.hasher.update(ah.seek.read(read));
+        }
+        r
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<D: MinDigest, T: futures::io::AsyncRead + std::marker::Unpin> futures::io::AsyncRead
+    for ReadHasher<D, T>
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut [u8],
+    ) -> std::task::Poll<futures::io::Result<usize>> {
+        let ah = self.project();
+        let r = ah.inner.poll_read(cx, buf);
+        match r {
+            Poll::Ready(Ok(0)) if !buf.is_empty() => {
+                if let Some(expected) = ah.expected {
+                    expected.check(ah.hasher, ah.seek)?;
+                }
+            }
+            Poll::Ready(Ok(n)) => ah.hasher.update(ah.seek.read(&buf[..n])),
+            _ => (),
+        }
+        r
+    }
+}
+
+/// Bytes are hashed the first time `poll_fill_buf` returns them and `consume` only keeps track of
+/// how much of that buffer is left, so whatever was buffered is hashed even if it's never consumed
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+impl<D: MinDigest, T: tokio::io::AsyncBufRead + std::marker::Unpin> tokio::io::AsyncBufRead
+    for ReadHasher<D, T>
+{
+    fn poll_fill_buf(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<&[u8]>> {
+        let ah = self.project();
+        let buf = core::task::ready!(ah.inner.poll_fill_buf(cx))?;
+        if buf.is_empty() {
+            if let Some(expected) = ah.expected {
+                expected.check(ah.hasher, ah.seek)?;
+            }
+        }
+        ah.hasher.update(ah.seek.fill(buf));
+        Poll::Ready(Ok(buf))
+    }
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        let ah = self.project();
+        ah.seek.consume(amt);
+        ah.inner.consume(amt)
+    }
+}
+
+/// Bytes are hashed the first time `poll_fill_buf` returns them and `consume` only keeps track of
+/// how much of that buffer is left, so whatever was buffered is hashed even if it's never consumed
+#[cfg(feature = "futures")]
+impl<D: MinDigest, T: futures::io::AsyncBufRead + std::marker::Unpin> futures::io::AsyncBufRead
+    for ReadHasher<D, T>
+{
+    fn poll_fill_buf(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<futures::io::Result<&[u8]>> {
+        let ah = self.project();
+        let buf = core::task::ready!(ah.inner.poll_fill_buf(cx))?;
+        if buf.is_empty() {
+            if let Some(expected) = ah.expected {
+                expected.check(ah.hasher, ah.seek)?;
+            }
+        }
+        ah.hasher.update(ah.seek.fill(buf));
+        Poll::Ready(Ok(buf))
+    }
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        let ah = self.project();
+        ah.seek.consume(amt);
+        ah.inner.consume(amt)
+    }
+}
+
+#[cfg(feature = "stdio")]
+impl<D: MinDigest, T: std::io::Read> std::io::Read for ReadHasher<D, T> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let r = std::io::Read::read(&mut self.inner, buf);
+        match r {
+            Ok(0) if !buf.is_empty() => {
+                if let Some(expected) = &self.expected {
+                    expected.check(&self.hasher, &self.seek)?;
+                }
+            }
+            Ok(n) => MinDigest::update(&mut self.hasher, self.seek.advance(&buf[..n])),
+            Err(_) => (),
+        }
+        r
+    }
+}
+
+#[cfg(feature = "stdio")]
+impl<D, T: std::io::Seek> std::io::Seek for ReadHasher<D, T> {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        self.seek.start_seek(pos)?;
+        let position = self.inner.seek(pos)?;
+        self.seek.complete_seek(position);
+        Ok(position)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+impl<D, T: tokio::io::AsyncSeek + std::marker::Unpin> tokio::io::AsyncSeek for ReadHasher<D, T> {
+    fn start_seek(self: Pin<&mut Self>, position: std::io::SeekFrom) -> std::io::Result<()> {
+        let ah = self.project();
+        ah.seek.start_seek(position)?;
+        ah.inner.start_seek(position)?;
+        ah.seek.seeking = true;
+        Ok(())
+    }
+    fn poll_complete(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<u64>> {
+        let ah = self.project();
+        let r = ah.inner.poll_complete(cx);
+        if let Poll::Ready(result) = &r {
+            if core::mem::take(&mut ah.seek.seeking) {
+                if let Ok(position) = result {
+                    ah.seek.complete_seek(*position);
+                }
+            }
+        }
+        r
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<D, T: futures::io::AsyncSeek + std::marker::Unpin> futures::io::AsyncSeek
+    for ReadHasher<D, T>
+{
+    fn poll_seek(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        pos: std::io::SeekFrom,
+    ) -> std::task::Poll<futures::io::Result<u64>> {
+        let ah = self.project();
+        ah.seek.start_seek(pos)?;
+        let r = ah.inner.poll_seek(cx, pos);
+        if let Poll::Ready(Ok(position)) = r {
+            ah.seek.complete_seek(position);
+        }
+        r
+    }
+}
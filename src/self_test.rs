@@ -0,0 +1,298 @@
+//! Known-answer self-tests of the hash implementations, for environments that require testing
+//! each algorithm before use.
+//!
+//! [`run_all`] checks every algorithm enabled by the crate features, [`self_test`] checks a single
+//! one. Every vector is hashed through a [`WriteHasher`] both in a single write and one byte at a
+//! time.
+
+use std::io::Write;
+
+use crate::helpers::{decode_hex, encode_hex};
+use crate::{MinDigest, OutputBytes, WriteHasher};
+
+/// An input and its hex encoded digest
+#[derive(Debug, Clone, Copy)]
+pub struct Vector {
+    pub input: &'static [u8],
+    pub expected: &'static str,
+}
+
+/// A hasher with embedded known-answer vectors
+pub trait KnownAnswers: MinDigest + Sized {
+    /// The name of the algorithm reported on failure
+    const NAME: &'static str;
+    const VECTORS: &'static [Vector];
+    /// A fresh hasher to run a vector through
+    fn new_hasher() -> Self;
+}
+
+/// The algorithm and vector that didn't produce the expected digest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfTestFailure {
+    pub algorithm: &'static str,
+    pub input: &'static [u8],
+    /// Whether the input was written one byte at a time instead of all at once
+    pub chunked: bool,
+    pub expected: Vec<u8>,
+    pub actual: Vec<u8>,
+}
+
+impl core::fmt::Display for SelfTestFailure {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{} self-test failed on {:?} ({}): expected {}, got {}",
+            self.algorithm,
+            String::from_utf8_lossy(self.input),
+            if self.chunked {
+                "chunked"
+            } else {
+                "single-shot"
+            },
+            encode_hex(&self.expected),
+            encode_hex(&self.actual)
+        )
+    }
+}
+
+impl std::error::Error for SelfTestFailure {}
+
+/// Run the known-answer vectors of `D`
+pub fn self_test<D: KnownAnswers>() -> Result<(), SelfTestFailure>
+where
+    D::Output: OutputBytes,
+{
+    for vector in D::VECTORS {
+        let expected = decode_hex(vector.expected).expect("known answers are valid hex");
+        for chunked in [false, true] {
+            let mut hasher = WriteHasher::new_with_hasher(std::io::sink(), D::new_hasher());
+            if chunked {
+                for byte in vector.input.chunks(1) {
+                    hasher
+                        .write_all(byte)
+                        .expect("writing to a sink can't fail");
+                }
+            } else {
+                hasher
+                    .write_all(vector.input)
+                    .expect("writing to a sink can't fail");
+            }
+            let actual = hasher.finalize_bytes();
+            if actual != expected {
+                return Err(SelfTestFailure {
+                    algorithm: D::NAME,
+                    input: vector.input,
+                    chunked,
+                    expected,
+                    actual,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+const EMPTY: &[u8] = b"";
+const ABC: &[u8] = b"abc";
+const FOX: &[u8] = b"The quick brown fox jumps over the lazy dog";
+
+/// Implement [`KnownAnswers`] with the digests of "", "abc" and the quick brown fox, and add the
+/// type to [`run_all`]
+macro_rules! known_answers {
+    ($($(#[$cfg:meta])* $ty:ty => $name:literal, $new:expr, [$empty:literal, $abc:literal, $fox:literal $(,)?];)*) => {
+        $(
+            $(#[$cfg])*
+            impl KnownAnswers for $ty {
+                const NAME: &'static str = $name;
+                const VECTORS: &'static [Vector] = &[
+                    Vector { input: EMPTY, expected: $empty },
+                    Vector { input: ABC, expected: $abc },
+                    Vector { input: FOX, expected: $fox },
+                ];
+                fn new_hasher() -> Self {
+                    $new
+                }
+            }
+        )*
+
+        /// Run the known-answer vectors of every algorithm enabled by the crate features, stopping
+        /// at the first failure
+        pub fn run_all() -> Result<(), SelfTestFailure> {
+            $(
+                $(#[$cfg])*
+                self_test::<$ty>()?;
+            )*
+            Ok(())
+        }
+    };
+}
+
+known_answers! {
+    #[cfg(feature = "crc32c")]
+    crate::crc32c::Crc32c => "CRC-32C", Default::default(), [
+        "00000000",
+        "364b3fb7",
+        "22620404",
+    ];
+    crate::crc24::Crc24 => "CRC-24", Default::default(), [
+        "00b704ce",
+        "00ba1c7b",
+        "00a2618c",
+    ];
+    #[cfg(feature = "adler")]
+    crate::adler32::Adler32 => "Adler-32", Default::default(), [
+        "00000001",
+        "024d0127",
+        "5bdc0fda",
+    ];
+    #[cfg(feature = "crc64")]
+    crate::crc64::Crc64Xz => "CRC-64/XZ", Default::default(), [
+        "0000000000000000",
+        "2cd8094a1a277627",
+        "5b5eb8c2e54aa1c4",
+    ];
+    #[cfg(feature = "crc64")]
+    crate::crc64::Crc64Nvme => "CRC-64/NVME", Default::default(), [
+        "0000000000000000",
+        "05e5cabb3fc1faeb",
+        "d76c54054954c143",
+    ];
+    #[cfg(feature = "crc32fast")]
+    crc32fast::Hasher => "CRC-32", Default::default(), [
+        "00000000",
+        "352441c2",
+        "414fa339",
+    ];
+    #[cfg(feature = "xxh3")]
+    crate::xxh3::Xxh3_64 => "XXH3-64", Default::default(), [
+        "2d06800538d394c2",
+        "78af5f94892f3950",
+        "ce7d19a5418fb365",
+    ];
+    #[cfg(feature = "xxh3")]
+    crate::xxh3::Xxh3_128 => "XXH3-128", Default::default(), [
+        "99aa06d3014798d86001c324468d497f",
+        "06b05ab6733a618578af5f94892f3950",
+        "ddd650205ca3e7fa24a1cc2e3a8a7651",
+    ];
+    #[cfg(feature = "xxh64")]
+    crate::xxh64::Xxh64 => "XXH64", Default::default(), [
+        "ef46db3751d8e999",
+        "44bc2cf5ad770999",
+        "0b242d361fda71bc",
+    ];
+    #[cfg(feature = "md2")]
+    md2::Md2 => "MD2", Default::default(), [
+        "8350e5a3e24c153df2275c9f80692773",
+        "da853b0d3f88d99b30283a69e6ded6bb",
+        "03d85a0d629d2c442e987525319fc471",
+    ];
+    #[cfg(feature = "md4")]
+    md4::Md4 => "MD4", Default::default(), [
+        "31d6cfe0d16ae931b73c59d7e0c089c0",
+        "a448017aaf21d8525fc10ae87aa6729d",
+        "1bee69a46ba811185c194762abaeae90",
+    ];
+    #[cfg(feature = "md5")]
+    md5::Context => "MD5", md5::Context::new(), [
+        "d41d8cd98f00b204e9800998ecf8427e",
+        "900150983cd24fb0d6963f7d28e17f72",
+        "9e107d9d372bb6826bd81d3542a419d6",
+    ];
+    #[cfg(feature = "md-5")]
+    md_5::Md5 => "MD5 (md-5)", Default::default(), [
+        "d41d8cd98f00b204e9800998ecf8427e",
+        "900150983cd24fb0d6963f7d28e17f72",
+        "9e107d9d372bb6826bd81d3542a419d6",
+    ];
+    #[cfg(feature = "sha1")]
+    sha1::Sha1 => "SHA-1", Default::default(), [
+        "da39a3ee5e6b4b0d3255bfef95601890afd80709",
+        "a9993e364706816aba3e25717850c26c9cd0d89d",
+        "2fd4e1c67a2d28fced849ee1bb76e7391b93eb12",
+    ];
+    #[cfg(feature = "sha2")]
+    sha2::Sha224 => "SHA-224", Default::default(), [
+        "d14a028c2a3a2bc9476102bb288234c415a2b01f828ea62ac5b3e42f",
+        "23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7",
+        "730e109bd7a8a32b1cb9d9a09aa2325d2430587ddbc0c38bad911525",
+    ];
+    #[cfg(feature = "sha2")]
+    sha2::Sha256 => "SHA-256", Default::default(), [
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        "d7a8fbb307d7809469ca9abcb0082e4f8d5651e46d3cdb762d02d0bf37c9e592",
+    ];
+    #[cfg(feature = "sha2")]
+    sha2::Sha384 => "SHA-384", Default::default(), [
+        "38b060a751ac96384cd9327eb1b1e36a21fdb71114be07434c0cc7bf63f6e1da274edebfe76f65fbd51ad2f14898b95b",
+        "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7",
+        "ca737f1014a48f4c0b6dd43cb177b0afd9e5169367544c494011e3317dbf9a509cb1e5dc1e85a941bbee3d7f2afbc9b1",
+    ];
+    #[cfg(feature = "sha2")]
+    sha2::Sha512 => "SHA-512", Default::default(), [
+        "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e",
+        "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+        "07e547d9586f6a73f73fbac0435ed76951218fb7d0c8d788a309d785436bbb642e93a252a954f23912547d1e8a3b5ed6e1bfd7097821233fa0538f3db854fee6",
+    ];
+    #[cfg(feature = "sha2")]
+    sha2::Sha512_224 => "SHA-512/224", Default::default(), [
+        "6ed0dd02806fa89e25de060c19d3ac86cabb87d6a0ddd05c333b84f4",
+        "4634270f707b6a54daae7530460842e20e37ed265ceee9a43e8924aa",
+        "944cd2847fb54558d4775db0485a50003111c8e5daa63fe722c6aa37",
+    ];
+    #[cfg(feature = "sha2")]
+    sha2::Sha512_256 => "SHA-512/256", Default::default(), [
+        "c672b8d1ef56ed28ab87c3622c5114069bdd3ad7b8f9737498d0c01ecef0967a",
+        "53048e2681941ef99b2e29b76b4c7dabe4c2d0c634fc6d46e0e2f13107e7af23",
+        "dd9d67b371519c339ed8dbd25af90e976a1eeefd4ad3d889005e532fc5bef04d",
+    ];
+    #[cfg(feature = "blake2")]
+    blake2::Blake2b512 => "BLAKE2b-512", Default::default(), [
+        "786a02f742015903c6c6fd852552d272912f4740e15847618a86e217f71f5419d25e1031afee585313896444934eb04b903a685b1448b755d56f701afe9be2ce",
+        "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+        "a8add4bdddfd93e4877d2746e62817b116364a1fa7bc148d95090bc7333b3673f82401cf7aa2e4cb1ecd90296e3f14cb5413f8ed77be73045b13914cdcd6a918",
+    ];
+    #[cfg(feature = "ripemd")]
+    ripemd::Ripemd160 => "RIPEMD-160", Default::default(), [
+        "9c1185a5c5e9fc54612808977ee8f548b2258d31",
+        "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc",
+        "37f332f68db77bd9d7edd4969571ad671cf9dd3b",
+    ];
+    #[cfg(feature = "blake2")]
+    blake2::Blake2s256 => "BLAKE2s-256", Default::default(), [
+        "69217a3079908094e11121d042354a7c1f55b6482ca1a51e1b250dfd1ed0eef9",
+        "508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982",
+        "606beeec743ccbeff6cbcdf5d5302aa855c256c29b88c8ed331ea1a6bf3c8812",
+    ];
+    #[cfg(feature = "blake3")]
+    blake3::Hasher => "BLAKE3", blake3::Hasher::new(), [
+        "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262",
+        "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85",
+        "2f1514181aadccd913abd94cfa592701a5686ab23f8df1dff1b74710febc6d4a",
+    ];
+    #[cfg(feature = "skein")]
+    skein::Skein256<digest::consts::U32> => "Skein-256-256", Default::default(), [
+        "c8877087da56e072870daa843f176e9453115929094c3a40c463a196c29bf7ba",
+        "258bdec343b9fde1639221a5ae0144a96e552e5288753c5fec76c05fc2fc1870",
+        "c0fbd7d779b20f0a4614a66697f9e41859eaf382f14bf857e8cdb210adb9b3fe",
+    ];
+    #[cfg(feature = "skein")]
+    skein::Skein512<digest::consts::U64> => "Skein-512-512", Default::default(), [
+        "bc5b4c50925519c290cc634277ae3d6257212395cba733bbad37a4af0fa06af41fca7903d06564fea7a2d3730dbdb80c1f85562dfcc070334ea4d1d9e72cba7a",
+        "8f5dd9ec798152668e35129496b029a960c9a9b88662f7f9482f110b31f9f93893ecfb25c009baad9e46737197d5630379816a886aa05526d3a70df272d96e75",
+        "94c2ae036dba8783d0b3f7d6cc111ff810702f5c77707999be7e1c9486ff238a7044de734293147359b4ac7e1d09cd247c351d69826b78dcddd951f0ef912713",
+    ];
+    #[cfg(feature = "skein")]
+    skein::Skein1024<digest::consts::U128> => "Skein-1024-1024", Default::default(), [
+        "0fff9563bb3279289227ac77d319b6fff8d7e9f09da1247b72a0a265cd6d2a62645ad547ed8193db48cff847c06494a03f55666d3b47eb4c20456c9373c86297d630d5578ebd34cb40991578f9f52b18003efa35d3da6553ff35db91b81ab890bec1b189b7f52cb2a783ebb7d823d725b0b4a71f6824e88f68f982eefc6d19c6",
+        "35a599a0f91abcdb4cb73c19b8cb8d947742d82c309137a7caed29e8e0a2ca7a9ff9a90c34c1908cc7e7fd99bb15032fb86e76df21b72628399b5f7c3cc209d7bb31c99cd4e19465622a049afbb87c03b5ce3888d17e6e667279ec0aa9b3e2712624c01b5f5bbe1a564220bdcf6990af0c2539019f313fdd7406cca3892a1f1f",
+        "4cf6152f1a7e598098d28f04e13d7742ba39b7fadbbcf2167bda4e1615d551f3f6b4edbbb391ffa09e6cc0a4af1eb366b30b5f107b437e2ea5cb586afb0341bd97dabe7cc46e7be3a054aa605395e43b243654c01ffc14c8b5443488f35d80b504a612f3d29d767106d0d9249aaa4fd99b67a94fb8661a3520004501192d84fa",
+    ];
+    #[cfg(feature = "ascon")]
+    ascon_hash::AsconHash256 => "Ascon-Hash256", Default::default(), [
+        "0b3be5850f2f6b98caf29f8fdea89b64a1fa70aa249b8f839bd53baa304d92b2",
+        "45aa03431c3c829b3b066f33e844b0cc4d20a45af92d3dcfdf34f40fc20935cf",
+        "23414503bf4bde7ad0e85aec94c22ae2d7cd807996b537f9564fc2974053f139",
+    ];
+}
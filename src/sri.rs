@@ -0,0 +1,160 @@
+//! [Subresource Integrity](https://www.w3.org/TR/SRI/) metadata, `<alg>-<base64 digest>` as used
+//! in `integrity` attributes
+//!
+//! Only SHA-256, SHA-384 and SHA-512 are valid SRI algorithms. [`WriteHasher::finalize_sri`] only
+//! compiles for those, [`AnyDigest::to_sri`] fails at runtime for any other algorithm.
+//!
+//! ```rust
+//! extern crate sha2;
+//! use std::io::Write;
+//! use write_hasher::{sri, WriteHasher};
+//! let mut writer = WriteHasher::<sha2::Sha384, _>::new(std::io::sink());
+//! writer.write_all(b"alert('Hello, world.');").unwrap();
+//! let integrity = writer.finalize_sri();
+//! assert_eq!(
+//!     integrity,
+//!     "sha384-H8BRh8j48O9oYatfu5AZzq6A9RINhZO5H16dQZngK7T62em8MUt1FLm52t+eX6xO"
+//! );
+//! let (algorithm, digest) = sri::parse_sri(&integrity).unwrap();
+//! assert_eq!(algorithm, sri::SriAlgorithm::Sha384);
+//! assert_eq!(digest.len(), 48);
+//! ```
+
+use crate::encoding::{decode_base64, encode_base64};
+use crate::{AnyDigest, HashWriterError, MinDigest, OutputBytes, ReadHasher, WriteHasher};
+
+/// The hash algorithms allowed in SRI metadata
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SriAlgorithm {
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl SriAlgorithm {
+    /// The prefix of the metadata, e.g. "sha384"
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Sha256 => "sha256",
+            Self::Sha384 => "sha384",
+            Self::Sha512 => "sha512",
+        }
+    }
+
+    /// The length of the digest in bytes
+    pub fn digest_len(self) -> usize {
+        match self {
+            Self::Sha256 => 32,
+            Self::Sha384 => 48,
+            Self::Sha512 => 64,
+        }
+    }
+}
+
+impl core::str::FromStr for SriAlgorithm {
+    type Err = HashWriterError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // the spec's algorithm tokens are case sensitive
+        match s {
+            "sha256" => Ok(Self::Sha256),
+            "sha384" => Ok(Self::Sha384),
+            "sha512" => Ok(Self::Sha512),
+            _ => Err(HashWriterError::Parse(format!(
+                "{:?} is not an SRI hash algorithm",
+                s
+            ))),
+        }
+    }
+}
+
+/// A hasher of one of the [`SriAlgorithm`]s
+///
+/// ```compile_fail
+/// use write_hasher::{crc32c::Crc32c, WriteHasher};
+/// // CRC-32C isn't an SRI algorithm
+/// let _ = WriteHasher::<Crc32c, _>::new(std::io::sink()).finalize_sri();
+/// ```
+pub trait SriDigest {
+    const ALGORITHM: SriAlgorithm;
+}
+
+impl SriDigest for sha2::Sha256 {
+    const ALGORITHM: SriAlgorithm = SriAlgorithm::Sha256;
+}
+
+impl SriDigest for sha2::Sha384 {
+    const ALGORITHM: SriAlgorithm = SriAlgorithm::Sha384;
+}
+
+impl SriDigest for sha2::Sha512 {
+    const ALGORITHM: SriAlgorithm = SriAlgorithm::Sha512;
+}
+
+/// Format a digest as SRI metadata, e.g. `sha384-H8BR...`
+///
+/// Fails if `digest` doesn't have the length of the algorithm's digests.
+pub fn format_sri(algorithm: SriAlgorithm, digest: &[u8]) -> Result<String, HashWriterError> {
+    if digest.len() != algorithm.digest_len() {
+        return Err(HashWriterError::Length {
+            expected: algorithm.digest_len() as u64,
+            actual: digest.len() as u64,
+        });
+    }
+    Ok(format!(
+        "{}-{}",
+        algorithm.name(),
+        encode_base64(digest, true)
+    ))
+}
+
+/// Parse a single SRI hash expression into its algorithm and digest, for verifying data against
+/// it, e.g. with [`WriteHasher::with_expected`]
+///
+/// Surrounding whitespace and options (`?...` after the digest) are ignored, the digest has to be
+/// standard base64 of the algorithm's digest length.
+pub fn parse_sri(metadata: &str) -> Result<(SriAlgorithm, Vec<u8>), HashWriterError> {
+    let metadata = metadata.trim();
+    let (algorithm, rest) = metadata
+        .split_once('-')
+        .ok_or_else(|| HashWriterError::Parse(format!("{:?} is not SRI metadata", metadata)))?;
+    let algorithm: SriAlgorithm = algorithm.parse()?;
+    let encoded = rest
+        .split_once('?')
+        .map_or(rest, |(digest, _options)| digest);
+    let digest = decode_base64(encoded)
+        .filter(|digest| digest.len() == algorithm.digest_len())
+        .ok_or_else(|| {
+            HashWriterError::Parse(format!(
+                "{:?} is not a base64 {} digest",
+                encoded,
+                algorithm.name()
+            ))
+        })?;
+    Ok((algorithm, digest))
+}
+
+macro_rules! finalize_sri {
+    ($hasher:ident) => {
+        impl<D: MinDigest + SriDigest, T> $hasher<D, T>
+        where
+            D::Output: OutputBytes,
+        {
+            /// Finalize the digest as SRI metadata, e.g. `sha384-H8BR...`
+            pub fn finalize_sri(self) -> String {
+                let digest = self.hasher.finalize_bytes();
+                format!("{}-{}", D::ALGORITHM.name(), encode_base64(&digest, true))
+            }
+        }
+    };
+}
+
+finalize_sri!(WriteHasher);
+finalize_sri!(ReadHasher);
+
+impl AnyDigest {
+    /// The digest as SRI metadata, failing with [`HashWriterError::Parse`] for an algorithm that
+    /// isn't one of the [`SriAlgorithm`]s
+    pub fn to_sri(&self) -> Result<String, HashWriterError> {
+        format_sri(self.algorithm.parse()?, &self.bytes)
+    }
+}
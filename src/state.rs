@@ -0,0 +1,116 @@
+//! Saving the state of a [`WriteHasher`] to resume hashing later, e.g. to continue an interrupted
+//! upload without hashing what was already sent again
+//!
+//! Only hashers whose whole state is cheap to get at implement [`ResumableDigest`], the `digest`
+//! 0.10 hashers don't expose theirs.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{MinDigest, WriteHasher};
+
+/// The layout version of [`StateBlob`]s, restoring blobs of another version fails
+pub const STATE_VERSION: u32 = 1;
+
+/// A hasher whose state can be saved into a [`StateBlob`] and restored from it
+pub trait ResumableDigest: MinDigest + Sized {
+    /// The name of the algorithm in its [`StateBlob`]s, restoring checks it
+    const ALGORITHM: &'static str;
+
+    /// The internal state
+    fn save_state(&self) -> Vec<u8>;
+
+    /// The hasher for a state returned by [`save_state`](Self::save_state), `None` if `state`
+    /// isn't one
+    fn restore_state(state: &[u8]) -> Option<Self>;
+}
+
+/// The saved state of a [`WriteHasher`], tagged with its algorithm and a layout version
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateBlob {
+    pub version: u32,
+    pub algorithm: String,
+    /// The number of bytes hashed when the state was saved
+    pub written: u64,
+    pub state: Vec<u8>,
+}
+
+/// Why a [`StateBlob`] couldn't be restored
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateError {
+    /// The blob was saved with another layout version
+    Version(u32),
+    /// The blob was saved by another algorithm
+    Algorithm {
+        expected: &'static str,
+        found: String,
+    },
+    /// The state isn't one the algorithm produces
+    Malformed,
+}
+
+impl core::fmt::Display for StateError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Version(version) => write!(
+                f,
+                "unsupported hasher state version {}, expected {}",
+                version, STATE_VERSION
+            ),
+            Self::Algorithm { expected, found } => {
+                write!(f, "hasher state is for {}, expected {}", found, expected)
+            }
+            Self::Malformed => f.write_str("malformed hasher state"),
+        }
+    }
+}
+
+impl std::error::Error for StateError {}
+
+impl<D: ResumableDigest, T> WriteHasher<D, T> {
+    /// Save the state of the hasher along with the number of bytes written so far
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "crc32c")] {
+    /// use std::io::Write;
+    /// use write_hasher::{crc32c::Crc32c, MinDigest, WriteHasher};
+    /// let mut hasher = WriteHasher::<Crc32c, _>::new(std::io::sink());
+    /// hasher.write_all(b"first half, ").unwrap();
+    /// let blob = hasher.serialize_state();
+    ///
+    /// let mut hasher = WriteHasher::<Crc32c, _>::new_from_state(std::io::sink(), &blob).unwrap();
+    /// hasher.write_all(b"second half").unwrap();
+    /// assert_eq!(hasher.bytes_written(), 23);
+    /// assert_eq!(
+    ///     hasher.finalize(),
+    ///     write_hasher::hash_bytes::<Crc32c>(b"first half, second half")
+    /// );
+    /// # }
+    /// ```
+    pub fn serialize_state(&self) -> StateBlob {
+        StateBlob {
+            version: STATE_VERSION,
+            algorithm: D::ALGORITHM.to_owned(),
+            written: self.hashed,
+            state: self.hasher.save_state(),
+        }
+    }
+
+    /// Continue hashing from a [`serialize_state`](Self::serialize_state) blob, writing the rest
+    /// of the data to `inner`
+    pub fn new_from_state(inner: T, blob: &StateBlob) -> Result<Self, StateError> {
+        if blob.version != STATE_VERSION {
+            return Err(StateError::Version(blob.version));
+        }
+        if blob.algorithm != D::ALGORITHM {
+            return Err(StateError::Algorithm {
+                expected: D::ALGORITHM,
+                found: blob.algorithm.clone(),
+            });
+        }
+        let hasher = D::restore_state(&blob.state).ok_or(StateError::Malformed)?;
+        let mut hasher = Self::new_with_hasher(inner, hasher);
+        hasher.written = blob.written;
+        hasher.hashed = blob.written;
+        Ok(hasher)
+    }
+}
@@ -0,0 +1,539 @@
+//! Misbehaving readers and writers for testing code that deals with short reads / writes,
+//! `Pending`, interruptions and failures.
+//!
+//! All of them wrap an inner reader / writer and implement whichever of the std, tokio and
+//! futures IO traits the inner type implements (`Pending` is only meaningful for the async
+//! traits and shows up as [`WouldBlock`][std::io::ErrorKind::WouldBlock] for the std ones).
+
+#[cfg(any(feature = "futures", feature = "tokio"))]
+use core::{pin::Pin, task::Poll};
+
+/// What a [`Misbehaving`] reader / writer does on the next call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Forward at most this many bytes to the inner reader / writer
+    Forward(usize),
+    /// Return `Pending` (or `WouldBlock`) after waking the task
+    Pending,
+    /// Return an [`Interrupted`][std::io::ErrorKind::Interrupted] error
+    Interrupt,
+    /// Return an error
+    Fail,
+    /// Return `Ok(0)`
+    Zero,
+}
+
+pub trait Misbehavior {
+    /// Decide what to do with a call for `len` bytes
+    fn next(&mut self, len: usize) -> Action;
+    /// Called with the number of bytes the inner reader / writer actually accepted
+    fn accepted(&mut self, _n: usize) {}
+}
+
+/// Accept at most `max` bytes per call
+#[derive(Debug, Clone)]
+pub struct Short {
+    max: usize,
+}
+
+impl Misbehavior for Short {
+    fn next(&mut self, _len: usize) -> Action {
+        Action::Forward(self.max)
+    }
+}
+
+/// Return `Pending` on every other call, starting with the first one
+#[derive(Debug, Clone, Default)]
+pub struct Pending {
+    ready: bool,
+}
+
+impl Misbehavior for Pending {
+    fn next(&mut self, len: usize) -> Action {
+        self.ready = !self.ready;
+        if self.ready {
+            Action::Pending
+        } else {
+            Action::Forward(len)
+        }
+    }
+}
+
+/// Return an `Interrupted` error on every other call, starting with the first one
+#[derive(Debug, Clone, Default)]
+pub struct Interrupting {
+    ready: bool,
+}
+
+impl Misbehavior for Interrupting {
+    fn next(&mut self, len: usize) -> Action {
+        self.ready = !self.ready;
+        if self.ready {
+            Action::Interrupt
+        } else {
+            Action::Forward(len)
+        }
+    }
+}
+
+/// Forward `remaining` bytes and then fail every call
+#[derive(Debug, Clone)]
+pub struct Fail {
+    remaining: usize,
+}
+
+impl Misbehavior for Fail {
+    fn next(&mut self, _len: usize) -> Action {
+        match self.remaining {
+            0 => Action::Fail,
+            n => Action::Forward(n),
+        }
+    }
+    fn accepted(&mut self, n: usize) {
+        self.remaining -= n;
+    }
+}
+
+/// Forward `remaining` bytes and then return `Ok(0)` on every call
+#[derive(Debug, Clone)]
+pub struct Zero {
+    remaining: usize,
+}
+
+impl Misbehavior for Zero {
+    fn next(&mut self, _len: usize) -> Action {
+        match self.remaining {
+            0 => Action::Zero,
+            n => Action::Forward(n),
+        }
+    }
+    fn accepted(&mut self, n: usize) {
+        self.remaining -= n;
+    }
+}
+
+/// A reader / writer wrapping `T` that misbehaves according to `B`
+#[derive(Debug, Clone)]
+pub struct Misbehaving<T, B> {
+    inner: T,
+    behavior: B,
+}
+
+pub type ShortWriter<T> = Misbehaving<T, Short>;
+pub type ShortReader<T> = Misbehaving<T, Short>;
+pub type PendingWriter<T> = Misbehaving<T, Pending>;
+pub type PendingReader<T> = Misbehaving<T, Pending>;
+pub type InterruptingWriter<T> = Misbehaving<T, Interrupting>;
+pub type InterruptingReader<T> = Misbehaving<T, Interrupting>;
+pub type FailAfter<T> = Misbehaving<T, Fail>;
+pub type ZeroAfter<T> = Misbehaving<T, Zero>;
+
+impl<T, B: Misbehavior> Misbehaving<T, B> {
+    pub fn new_with_behavior(inner: T, behavior: B) -> Self {
+        Self { inner, behavior }
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> Misbehaving<T, Short> {
+    pub fn new(inner: T, max: usize) -> Self {
+        Self::new_with_behavior(inner, Short { max })
+    }
+}
+
+impl<T> Misbehaving<T, Pending> {
+    pub fn new(inner: T) -> Self {
+        Self::new_with_behavior(inner, Pending::default())
+    }
+}
+
+impl<T> Misbehaving<T, Interrupting> {
+    pub fn new(inner: T) -> Self {
+        Self::new_with_behavior(inner, Interrupting::default())
+    }
+}
+
+impl<T> Misbehaving<T, Fail> {
+    pub fn new(inner: T, after: usize) -> Self {
+        Self::new_with_behavior(inner, Fail { remaining: after })
+    }
+}
+
+impl<T> Misbehaving<T, Zero> {
+    pub fn new(inner: T, after: usize) -> Self {
+        Self::new_with_behavior(inner, Zero { remaining: after })
+    }
+}
+
+fn action_error(action: Action) -> std::io::Error {
+    match action {
+        Action::Pending => std::io::ErrorKind::WouldBlock.into(),
+        Action::Interrupt => std::io::ErrorKind::Interrupted.into(),
+        _ => std::io::Error::other("misbehaving reader / writer failed"),
+    }
+}
+
+/// The first `max` bytes of `bufs`
+fn truncate_slices<'a>(
+    bufs: &'a [std::io::IoSlice<'_>],
+    mut max: usize,
+) -> Vec<std::io::IoSlice<'a>> {
+    let mut truncated = Vec::new();
+    for buf in bufs {
+        if max == 0 {
+            break;
+        }
+        let len = buf.len().min(max);
+        truncated.push(std::io::IoSlice::new(&buf[..len]));
+        max -= len;
+    }
+    truncated
+}
+
+impl<T: std::io::Write, B: Misbehavior> std::io::Write for Misbehaving<T, B> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self.behavior.next(buf.len()) {
+            Action::Forward(max) => {
+                let n = self.inner.write(&buf[..max.min(buf.len())])?;
+                self.behavior.accepted(n);
+                Ok(n)
+            }
+            Action::Zero => Ok(0),
+            action => Err(action_error(action)),
+        }
+    }
+    fn write_vectored(&mut self, bufs: &[std::io::IoSlice<'_>]) -> std::io::Result<usize> {
+        let len = bufs.iter().map(|buf| buf.len()).sum();
+        match self.behavior.next(len) {
+            Action::Forward(max) => {
+                let n = self.inner.write_vectored(&truncate_slices(bufs, max))?;
+                self.behavior.accepted(n);
+                Ok(n)
+            }
+            Action::Zero => Ok(0),
+            action => Err(action_error(action)),
+        }
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<T: std::io::Read, B: Misbehavior> std::io::Read for Misbehaving<T, B> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self.behavior.next(buf.len()) {
+            Action::Forward(max) => {
+                let len = max.min(buf.len());
+                let n = self.inner.read(&mut buf[..len])?;
+                self.behavior.accepted(n);
+                Ok(n)
+            }
+            Action::Zero => Ok(0),
+            action => Err(action_error(action)),
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<T, B> tokio::io::AsyncWrite for Misbehaving<T, B>
+where
+    T: tokio::io::AsyncWrite + std::marker::Unpin,
+    B: Misbehavior + std::marker::Unpin,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        match self.behavior.next(buf.len()) {
+            Action::Forward(max) => {
+                let n = core::task::ready!(
+                    Pin::new(&mut self.inner).poll_write(cx, &buf[..max.min(buf.len())])
+                )?;
+                self.behavior.accepted(n);
+                Poll::Ready(Ok(n))
+            }
+            Action::Pending => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            Action::Zero => Poll::Ready(Ok(0)),
+            action => Poll::Ready(Err(action_error(action))),
+        }
+    }
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        bufs: &[std::io::IoSlice<'_>],
+    ) -> Poll<std::io::Result<usize>> {
+        let len = bufs.iter().map(|buf| buf.len()).sum();
+        match self.behavior.next(len) {
+            Action::Forward(max) => {
+                let bufs = truncate_slices(bufs, max);
+                let n =
+                    core::task::ready!(Pin::new(&mut self.inner).poll_write_vectored(cx, &bufs))?;
+                self.behavior.accepted(n);
+                Poll::Ready(Ok(n))
+            }
+            Action::Pending => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            Action::Zero => Poll::Ready(Ok(0)),
+            action => Poll::Ready(Err(action_error(action))),
+        }
+    }
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+    fn poll_flush(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+    fn poll_shutdown(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<T, B> tokio::io::AsyncRead for Misbehaving<T, B>
+where
+    T: tokio::io::AsyncRead + std::marker::Unpin,
+    B: Misbehavior + std::marker::Unpin,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        match self.behavior.next(buf.remaining()) {
+            Action::Forward(max) => {
+                let mut limited = vec![0; max.min(buf.remaining())];
+                let mut limited = tokio::io::ReadBuf::new(&mut limited);
+                core::task::ready!(Pin::new(&mut self.inner).poll_read(cx, &mut limited))?;
+                self.behavior.accepted(limited.filled().len());
+                buf.put_slice(limited.filled());
+                Poll::Ready(Ok(()))
+            }
+            Action::Pending => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            Action::Zero => Poll::Ready(Ok(())),
+            action => Poll::Ready(Err(action_error(action))),
+        }
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<T, B> futures::io::AsyncWrite for Misbehaving<T, B>
+where
+    T: futures::io::AsyncWrite + std::marker::Unpin,
+    B: Misbehavior + std::marker::Unpin,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        match self.behavior.next(buf.len()) {
+            Action::Forward(max) => {
+                let n = core::task::ready!(
+                    Pin::new(&mut self.inner).poll_write(cx, &buf[..max.min(buf.len())])
+                )?;
+                self.behavior.accepted(n);
+                Poll::Ready(Ok(n))
+            }
+            Action::Pending => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            Action::Zero => Poll::Ready(Ok(0)),
+            action => Poll::Ready(Err(action_error(action))),
+        }
+    }
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        bufs: &[std::io::IoSlice<'_>],
+    ) -> Poll<std::io::Result<usize>> {
+        let len = bufs.iter().map(|buf| buf.len()).sum();
+        match self.behavior.next(len) {
+            Action::Forward(max) => {
+                let bufs = truncate_slices(bufs, max);
+                let n =
+                    core::task::ready!(Pin::new(&mut self.inner).poll_write_vectored(cx, &bufs))?;
+                self.behavior.accepted(n);
+                Poll::Ready(Ok(n))
+            }
+            Action::Pending => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            Action::Zero => Poll::Ready(Ok(0)),
+            action => Poll::Ready(Err(action_error(action))),
+        }
+    }
+    fn poll_flush(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+    fn poll_close(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_close(cx)
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<T, B> futures::io::AsyncRead for Misbehaving<T, B>
+where
+    T: futures::io::AsyncRead + std::marker::Unpin,
+    B: Misbehavior + std::marker::Unpin,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        match self.behavior.next(buf.len()) {
+            Action::Forward(max) => {
+                let len = max.min(buf.len());
+                let n =
+                    core::task::ready!(Pin::new(&mut self.inner).poll_read(cx, &mut buf[..len]))?;
+                self.behavior.accepted(n);
+                Poll::Ready(Ok(n))
+            }
+            Action::Pending => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            Action::Zero => Poll::Ready(Ok(0)),
+            action => Poll::Ready(Err(action_error(action))),
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<T, B> tokio::io::AsyncBufRead for Misbehaving<T, B>
+where
+    T: tokio::io::AsyncBufRead + std::marker::Unpin,
+    B: Misbehavior + std::marker::Unpin,
+{
+    fn poll_fill_buf(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<std::io::Result<&[u8]>> {
+        let this = self.get_mut();
+        match this.behavior.next(usize::MAX) {
+            Action::Forward(max) => {
+                let buf = core::task::ready!(Pin::new(&mut this.inner).poll_fill_buf(cx))?;
+                Poll::Ready(Ok(&buf[..max.min(buf.len())]))
+            }
+            Action::Pending => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            Action::Zero => Poll::Ready(Ok(&[])),
+            action => Poll::Ready(Err(action_error(action))),
+        }
+    }
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        let this = self.get_mut();
+        this.behavior.accepted(amt);
+        Pin::new(&mut this.inner).consume(amt)
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<T, B> futures::io::AsyncBufRead for Misbehaving<T, B>
+where
+    T: futures::io::AsyncBufRead + std::marker::Unpin,
+    B: Misbehavior + std::marker::Unpin,
+{
+    fn poll_fill_buf(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<std::io::Result<&[u8]>> {
+        let this = self.get_mut();
+        match this.behavior.next(usize::MAX) {
+            Action::Forward(max) => {
+                let buf = core::task::ready!(Pin::new(&mut this.inner).poll_fill_buf(cx))?;
+                Poll::Ready(Ok(&buf[..max.min(buf.len())]))
+            }
+            Action::Pending => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            Action::Zero => Poll::Ready(Ok(&[])),
+            action => Poll::Ready(Err(action_error(action))),
+        }
+    }
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        let this = self.get_mut();
+        this.behavior.accepted(amt);
+        Pin::new(&mut this.inner).consume(amt)
+    }
+}
+
+/// An [`AsyncMinDigest`](crate::AsyncMinDigest) backend running a local hasher, returning
+/// `Pending` (and waking itself) `delay` times before each update and the finalize, like a
+/// backend that goes through a queue or the network would
+#[cfg(feature = "async-digest")]
+#[derive(Debug, Clone, Default)]
+pub struct Loopback<D> {
+    hasher: D,
+    delay: usize,
+}
+
+#[cfg(feature = "async-digest")]
+impl<D> Loopback<D> {
+    pub fn new(hasher: D, delay: usize) -> Self {
+        Self { hasher, delay }
+    }
+}
+
+#[cfg(feature = "async-digest")]
+async fn yield_times(mut n: usize) {
+    core::future::poll_fn(|cx| {
+        if n == 0 {
+            return Poll::Ready(());
+        }
+        n -= 1;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    })
+    .await
+}
+
+#[cfg(feature = "async-digest")]
+impl<D> crate::AsyncMinDigest for Loopback<D>
+where
+    D: crate::MinDigest + Send,
+    D::Output: Send,
+{
+    type Output = D::Output;
+    async fn update(&mut self, data: bytes::Bytes) {
+        yield_times(self.delay).await;
+        self.hasher.update(data)
+    }
+    async fn finalize(self) -> D::Output {
+        yield_times(self.delay).await;
+        self.hasher.finalize()
+    }
+}
@@ -0,0 +1,227 @@
+//! [`VerifyingWriteHasher`], checking the data written against an expected digest
+
+#[cfg(any(feature = "futures", feature = "tokio"))]
+use core::{pin::Pin, task::Poll};
+
+use crate::encoding::bytes_eq;
+use crate::helpers::decode_hex;
+use crate::{HashWriterError, MinDigest, OutputBytes, WriteHasher};
+
+/// A [`WriteHasher`] that knows the digest the data is expected to have, e.g. for "download and
+/// verify". Created with [`WriteHasher::with_expected`].
+///
+/// [`finalize_verify`](Self::finalize_verify) hands back the inner writer only if the digest
+/// matches. With [`verify_on_shutdown`](Self::verify_on_shutdown), shutting down (closing) the
+/// async writers checks the digest as well.
+///
+/// ```rust
+/// # #[cfg(all(feature = "stdio", feature = "crc32c"))] {
+/// use std::io::Write;
+/// use write_hasher::{crc32c::Crc32c, HashWriterError, WriteHasher};
+/// let mut writer = WriteHasher::<Crc32c, _>::new(Vec::new())
+///     .with_expected_hex("e3069283")
+///     .unwrap();
+/// writer.write_all(b"123456789").unwrap();
+/// assert_eq!(writer.finalize_verify().unwrap(), b"123456789");
+///
+/// let mut writer = WriteHasher::<Crc32c, _>::new(Vec::new()).with_expected([0; 4]);
+/// writer.write_all(b"123456789").unwrap();
+/// assert!(matches!(writer.finalize_verify(), Err(HashWriterError::Mismatch { .. })));
+/// # }
+/// ```
+#[cfg_attr(any(feature = "futures", feature = "tokio"), pin_project::pin_project)]
+pub struct VerifyingWriteHasher<D, T> {
+    #[cfg_attr(any(feature = "futures", feature = "tokio"), pin)]
+    writer: WriteHasher<D, T>,
+    expected: Vec<u8>,
+    /// The digest so far, if shutting down verifies it
+    on_shutdown: Option<fn(&D) -> Vec<u8>>,
+}
+
+impl<D, T> WriteHasher<D, T> {
+    /// Verify the data written against `expected`, compared with the [`OutputBytes`] of the
+    /// digest, i.e. big-endian bytes for the integer checksums
+    pub fn with_expected(self, expected: impl AsRef<[u8]>) -> VerifyingWriteHasher<D, T> {
+        VerifyingWriteHasher {
+            writer: self,
+            expected: expected.as_ref().to_vec(),
+            on_shutdown: None,
+        }
+    }
+
+    /// Same as [`with_expected`](Self::with_expected) but takes the expected digest as a hex
+    /// string (in any case)
+    pub fn with_expected_hex(
+        self,
+        expected: &str,
+    ) -> Result<VerifyingWriteHasher<D, T>, HashWriterError> {
+        let expected = decode_hex(expected).ok_or_else(|| {
+            HashWriterError::Parse("expected digest is not a valid hex string".into())
+        })?;
+        Ok(self.with_expected(expected))
+    }
+}
+
+impl<D, T> VerifyingWriteHasher<D, T> {
+    /// Make `poll_shutdown` / `poll_close` of the async writers fail with an
+    /// [`InvalidData`](std::io::ErrorKind::InvalidData) error wrapping a [`HashWriterError`] if
+    /// the digest doesn't match once the inner writer is shut down
+    pub fn verify_on_shutdown(mut self) -> Self
+    where
+        D: MinDigest + Clone,
+        D::Output: OutputBytes,
+    {
+        self.on_shutdown = Some(|hasher| hasher.clone().finalize_bytes());
+        self
+    }
+
+    /// The expected digest
+    pub fn expected(&self) -> &[u8] {
+        &self.expected
+    }
+
+    pub fn get_ref(&self) -> &T {
+        self.writer.get_ref()
+    }
+
+    /// Writing to the inner writer directly bypasses the hasher.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.writer.get_mut()
+    }
+
+    /// The [`WriteHasher`], dropping the expected digest
+    pub fn into_write_hasher(self) -> WriteHasher<D, T> {
+        self.writer
+    }
+}
+
+impl<D: MinDigest, T> VerifyingWriteHasher<D, T>
+where
+    D::Output: OutputBytes,
+{
+    /// Finalize the hash and return the inner writer if it matches the expected digest.
+    ///
+    /// Fails with [`HashWriterError::Mismatch`] (whose `Display` shows both digests in hex) if
+    /// it doesn't, including when the expected digest has a different length, and with
+    /// [`HashWriterError::Invalidated`] if the digest isn't
+    /// [linear](WriteHasher::is_digest_linear) anymore. The comparison is constant time with the
+    /// `subtle` feature.
+    pub fn finalize_verify(self) -> Result<T, HashWriterError> {
+        if !self.writer.is_digest_linear() {
+            return Err(HashWriterError::Invalidated);
+        }
+        let (actual, inner) = self.writer.finalize_into_parts();
+        check(&self.expected, actual.to_bytes().into_owned())?;
+        Ok(inner)
+    }
+}
+
+fn check(expected: &[u8], actual: Vec<u8>) -> Result<(), HashWriterError> {
+    if bytes_eq(&actual, expected) {
+        Ok(())
+    } else {
+        Err(HashWriterError::Mismatch {
+            expected: expected.to_vec(),
+            actual,
+        })
+    }
+}
+
+/// Check the digest once the inner writer shut down, if enabled
+#[cfg(any(feature = "futures", feature = "tokio"))]
+fn check_on_shutdown<D, T>(
+    writer: &WriteHasher<D, T>,
+    expected: &[u8],
+    on_shutdown: Option<fn(&D) -> Vec<u8>>,
+) -> std::io::Result<()> {
+    let Some(actual) = on_shutdown else {
+        return Ok(());
+    };
+    if !writer.is_digest_linear() {
+        return Err(HashWriterError::Invalidated.into());
+    }
+    Ok(check(expected, actual(writer.hasher_ref()))?)
+}
+
+#[cfg(feature = "stdio")]
+impl<D: MinDigest, T: std::io::Write> std::io::Write for VerifyingWriteHasher<D, T> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.writer.write(buf)
+    }
+    fn write_vectored(&mut self, bufs: &[std::io::IoSlice<'_>]) -> std::io::Result<usize> {
+        self.writer.write_vectored(bufs)
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+impl<D: MinDigest, T: tokio::io::AsyncWrite> tokio::io::AsyncWrite for VerifyingWriteHasher<D, T> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        self.project().writer.poll_write(cx, buf)
+    }
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        bufs: &[std::io::IoSlice<'_>],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        self.project().writer.poll_write_vectored(cx, bufs)
+    }
+    fn is_write_vectored(&self) -> bool {
+        self.writer.is_write_vectored()
+    }
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        self.project().writer.poll_flush(cx)
+    }
+    fn poll_shutdown(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        let mut ah = self.project();
+        core::task::ready!(ah.writer.as_mut().poll_shutdown(cx))?;
+        Poll::Ready(check_on_shutdown(&ah.writer, ah.expected, *ah.on_shutdown))
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<D: MinDigest, T: futures::io::AsyncWrite> futures::io::AsyncWrite
+    for VerifyingWriteHasher<D, T>
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<futures::io::Result<usize>> {
+        self.project().writer.poll_write(cx, buf)
+    }
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        bufs: &[std::io::IoSlice<'_>],
+    ) -> std::task::Poll<futures::io::Result<usize>> {
+        self.project().writer.poll_write_vectored(cx, bufs)
+    }
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<futures::io::Result<()>> {
+        self.project().writer.poll_flush(cx)
+    }
+    fn poll_close(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<futures::io::Result<()>> {
+        let mut ah = self.project();
+        core::task::ready!(ah.writer.as_mut().poll_close(cx))?;
+        Poll::Ready(check_on_shutdown(&ah.writer, ah.expected, *ah.on_shutdown))
+    }
+}
@@ -0,0 +1,1464 @@
+//! [`WriteHasher`], hashing everything written through it
+
+#[cfg(any(feature = "futures", feature = "tokio", feature = "stdio"))]
+use core::pin::Pin;
+#[cfg(any(feature = "futures", feature = "tokio"))]
+use core::task::Poll;
+#[cfg(feature = "digest")]
+use digest::Digest;
+
+use crate::combinators::{Checkpoint, Inspect};
+use crate::{HashWriterError, MinDigest, MinDigestReset};
+
+/// A hasher that will be a wrapper over any Write / AsyncWrite object and transparently calculate
+/// hash for any data written to it
+#[cfg_attr(any(feature = "futures", feature = "tokio"), pin_project::pin_project)]
+#[derive(Default)]
+pub struct WriteHasher<D, T> {
+    pub(crate) hasher: D,
+    #[cfg_attr(any(feature = "futures", feature = "tokio"), pin)]
+    pub(crate) inner: T,
+    pub(crate) written: u64,
+    pub(crate) hashed: u64,
+    filter: HashFilter,
+    seek: SeekTracker,
+}
+
+/// Which of the written bytes are hashed: none while paused, otherwise the ones at the offsets
+/// selected with [`WriteHasher::with_hash_ranges`] (all of them by default)
+#[derive(Debug, Default, Clone)]
+struct HashFilter {
+    paused: bool,
+    #[cfg(any(feature = "stdio", feature = "tokio", feature = "futures"))]
+    ranges: Option<Vec<core::ops::Range<u64>>>,
+    /// The first range that doesn't end before `offset`
+    #[cfg(any(feature = "stdio", feature = "tokio", feature = "futures"))]
+    next: usize,
+    /// The offset in the stream written through the hasher, paused writes included
+    #[cfg(any(feature = "stdio", feature = "tokio", feature = "futures"))]
+    offset: u64,
+}
+
+#[cfg(any(feature = "stdio", feature = "tokio", feature = "futures"))]
+impl HashFilter {
+    /// Advance over `buf`, written at the current offset, calling `hash` with the parts of it
+    /// that are hashed (a write can straddle any number of ranges)
+    fn select<'a>(&mut self, buf: &'a [u8], mut hash: impl FnMut(&'a [u8])) {
+        let start = self.offset;
+        let end = start + buf.len() as u64;
+        self.offset = end;
+        if self.paused || buf.is_empty() {
+            return;
+        }
+        let Some(ranges) = &self.ranges else {
+            return hash(buf);
+        };
+        while let Some(range) = ranges.get(self.next) {
+            if range.start >= end {
+                break;
+            }
+            let (from, to) = (range.start.max(start), range.end.min(end));
+            if from < to {
+                hash(&buf[(from - start) as usize..(to - start) as usize]);
+            }
+            if range.end > end {
+                break;
+            }
+            self.next += 1;
+        }
+    }
+}
+
+/// Hash the parts of `buf`, which was just written, that `filter` selects
+#[cfg(any(feature = "stdio", feature = "tokio", feature = "futures"))]
+fn hash_written<D: MinDigest>(
+    hasher: &mut D,
+    hashed: &mut u64,
+    filter: &mut HashFilter,
+    buf: &[u8],
+) {
+    filter.select(buf, |chunk| {
+        hasher.update(chunk);
+        *hashed += chunk.len() as u64;
+    });
+}
+
+/// Whether seeking the inner writer moved it away from where the hashed data ends
+#[derive(Debug, Default, Clone, Copy)]
+struct SeekTracker {
+    /// The position the hashed data started at, if known from a seek
+    #[cfg(any(feature = "stdio", feature = "tokio"))]
+    start: Option<u64>,
+    #[cfg(feature = "tokio")]
+    pending: Option<std::io::SeekFrom>,
+    moved: bool,
+}
+
+#[cfg(any(feature = "stdio", feature = "tokio"))]
+impl SeekTracker {
+    /// A seek to `pos` (`None` if there was no seek, only the position) ended up at `position`
+    fn complete(&mut self, pos: Option<std::io::SeekFrom>, position: u64, written: u64) {
+        let moving = matches!(pos, Some(pos) if pos != std::io::SeekFrom::Current(0));
+        if moving && written > 0 && self.start.map(|start| start + written) != Some(position) {
+            self.moved = true;
+        }
+        self.start = position.checked_sub(written);
+    }
+}
+
+impl<D, T> WriteHasher<D, T> {
+    pub fn new_with_hasher(inner: T, hasher: D) -> Self {
+        Self {
+            hasher,
+            inner,
+            written: 0,
+            hashed: 0,
+            filter: HashFilter::default(),
+            seek: SeekTracker::default(),
+        }
+    }
+
+    pub fn new(inner: T) -> Self
+    where
+        D: Default,
+    {
+        Self {
+            hasher: Default::default(),
+            inner,
+            written: 0,
+            hashed: 0,
+            filter: HashFilter::default(),
+            seek: SeekTracker::default(),
+        }
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Writing to the inner writer directly bypasses the hasher, e.g. to write a header that
+    /// shouldn't be part of the digest.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// The inner writer, dropping the hasher
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// The number of bytes the inner writer accepted, including those written while hashing was
+    /// [paused](Self::pause_hashing)
+    pub fn bytes_written(&self) -> u64 {
+        self.written
+    }
+
+    /// The number of written bytes that were hashed, i.e. the length of the hashed data (not
+    /// counting what was fed to the hasher through [`hasher_mut`](Self::hasher_mut))
+    pub fn bytes_hashed(&self) -> u64 {
+        self.hashed
+    }
+
+    /// Keep writing to the inner writer but leave the data out of the digest until
+    /// [`resume_hashing`](Self::resume_hashing), e.g. for a header that will hold the digest of
+    /// the payload after it.
+    ///
+    /// Unlike writing through [`get_mut`](Self::get_mut) the bytes still count towards
+    /// [`bytes_written`](Self::bytes_written) and seeks past them are tracked.
+    ///
+    /// ```rust
+    /// # #[cfg(all(feature = "stdio", feature = "crc32c"))] {
+    /// use std::io::Write;
+    /// use write_hasher::{crc32c::Crc32c, hash_bytes, MinDigest, WriteHasher};
+    /// let mut hasher = WriteHasher::<Crc32c, _>::new(Vec::new());
+    /// hasher.pause_hashing();
+    /// hasher.write_all(b"HDR\0").unwrap();
+    /// hasher.resume_hashing();
+    /// hasher.write_all(b"123456789").unwrap();
+    /// assert_eq!((hasher.bytes_written(), hasher.bytes_hashed()), (13, 9));
+    /// assert_eq!(hasher.finalize(), hash_bytes::<Crc32c>(b"123456789"));
+    /// # }
+    /// ```
+    pub fn pause_hashing(&mut self) {
+        self.filter.paused = true;
+    }
+
+    /// Hash what is written from here on again, see [`pause_hashing`](Self::pause_hashing)
+    pub fn resume_hashing(&mut self) {
+        self.filter.paused = false;
+    }
+
+    pub fn is_hashing_paused(&self) -> bool {
+        self.filter.paused
+    }
+
+    /// Only hash the data at the offsets in `ranges`, e.g. to leave out a mutable metadata
+    /// region of a file.
+    ///
+    /// Offsets are positions in the stream written through this hasher from its creation on,
+    /// the data written while hashing is [paused](Self::pause_hashing) included. Data fed to the
+    /// hasher directly through [`hasher_mut`](Self::hasher_mut) doesn't move them, and neither
+    /// does [`finalize_reset`](Self::finalize_reset).
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "crc32c")] {
+    /// use std::io::Write;
+    /// use write_hasher::{crc32c::Crc32c, hash_bytes, MinDigest, WriteHasher};
+    /// let mut hasher = WriteHasher::<Crc32c, _>::new(std::io::sink()).with_hash_ranges(vec![0..4, 8..13]);
+    /// hasher.write_all(b"1234xxxx56789xxxx").unwrap();
+    /// assert_eq!(hasher.finalize(), hash_bytes::<Crc32c>(b"123456789"));
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if a range is empty or the ranges aren't sorted and non-overlapping
+    #[cfg(any(feature = "stdio", feature = "tokio", feature = "futures"))]
+    pub fn with_hash_ranges(mut self, ranges: Vec<core::ops::Range<u64>>) -> Self {
+        assert!(
+            ranges.iter().all(|range| range.start < range.end),
+            "hash ranges must not be empty"
+        );
+        assert!(
+            ranges.windows(2).all(|pair| pair[0].end <= pair[1].start),
+            "hash ranges must be sorted and non-overlapping"
+        );
+        self.filter.ranges = Some(ranges);
+        self.filter.next = 0;
+        self
+    }
+
+    /// Leave the first `n` bytes of the stream out of the digest, e.g. a fixed size header that
+    /// is filled in later, see [`with_hash_ranges`](Self::with_hash_ranges)
+    #[cfg(any(feature = "stdio", feature = "tokio", feature = "futures"))]
+    pub fn skip_prefix(self, n: u64) -> Self {
+        let rest = n..u64::MAX;
+        self.with_hash_ranges(core::iter::once(rest).filter(|r| !r.is_empty()).collect())
+    }
+
+    /// Whether the digest still covers the data as it ends up in the inner writer, i.e. the
+    /// inner writer wasn't seeked away from where the last write ended.
+    ///
+    /// Position queries (`SeekFrom::Current(0)`) and seeks before anything was written don't
+    /// count. Seeking back to the end is only recognized once the position is known from an
+    /// earlier seek.
+    pub fn is_digest_linear(&self) -> bool {
+        !self.seek.moved
+    }
+
+    pub fn hasher_ref(&self) -> &D {
+        &self.hasher
+    }
+
+    /// Updating the hasher directly mixes that data into the digest without writing it, e.g. a
+    /// domain separation prefix. Algorithm specific methods can be called through it as well.
+    pub fn hasher_mut(&mut self) -> &mut D {
+        &mut self.hasher
+    }
+
+    /// The hasher, dropping the inner writer
+    pub fn into_hasher(self) -> D {
+        self.hasher
+    }
+}
+
+/// Bridging between the tokio and futures `AsyncWrite` traits with [`tokio_util::compat::Compat`],
+/// the hasher implements the trait of the ecosystem it's used from
+#[cfg_attr(docsrs, doc(cfg(feature = "compat")))]
+#[cfg(feature = "compat")]
+impl<D, W> WriteHasher<D, tokio_util::compat::Compat<W>> {
+    /// Hash writes made through tokio's `AsyncWrite` into a futures `AsyncWrite`
+    pub fn new_tokio_from_futures(inner: W) -> Self
+    where
+        D: Default,
+        W: futures::io::AsyncWrite,
+    {
+        Self::new(tokio_util::compat::FuturesAsyncWriteCompatExt::compat_write(inner))
+    }
+
+    /// Hash writes made through the futures `AsyncWrite` into a tokio `AsyncWrite`
+    pub fn new_futures_from_tokio(inner: W) -> Self
+    where
+        D: Default,
+        W: tokio::io::AsyncWrite,
+    {
+        Self::new(tokio_util::compat::TokioAsyncWriteCompatExt::compat_write(
+            inner,
+        ))
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "compat")))]
+#[cfg(feature = "compat")]
+impl<D, T> WriteHasher<D, T> {
+    /// Keep hashing into the same digest, with writes made through the futures `AsyncWrite`
+    pub fn into_futures_compat(self) -> WriteHasher<D, tokio_util::compat::Compat<T>>
+    where
+        T: tokio::io::AsyncWrite,
+    {
+        WriteHasher {
+            hasher: self.hasher,
+            inner: tokio_util::compat::TokioAsyncWriteCompatExt::compat_write(self.inner),
+            written: self.written,
+            hashed: self.hashed,
+            filter: self.filter,
+            seek: self.seek,
+        }
+    }
+
+    /// Keep hashing into the same digest, with writes made through tokio's `AsyncWrite`
+    pub fn into_tokio_compat(self) -> WriteHasher<D, tokio_util::compat::Compat<T>>
+    where
+        T: futures::io::AsyncWrite,
+    {
+        WriteHasher {
+            hasher: self.hasher,
+            inner: tokio_util::compat::FuturesAsyncWriteCompatExt::compat_write(self.inner),
+            written: self.written,
+            hashed: self.hashed,
+            filter: self.filter,
+            seek: self.seek,
+        }
+    }
+}
+
+// #[cfg(feature = "digest")]
+// impl<D: Digest, T> WriteHasher<D, T> {
+//     pub fn new(inner: T) -> Self {
+//         Self {
+//             hasher: D::new(),
+//             inner,
+//         }
+//     }
+// }
+
+impl<D: MinDigest, T> WriteHasher<D, T> {
+    /// Finalize the hash of the data written so far, handing back the inner writer as well,
+    /// e.g. to persist the file the digest was computed for
+    pub fn finalize_into_parts(self) -> (D::Output, T) {
+        (self.hasher.finalize(), self.inner)
+    }
+
+    /// Finalize the hash like [`finalize`](MinDigest::finalize), failing with
+    /// [`HashWriterError::Invalidated`] if the digest isn't
+    /// [linear](Self::is_digest_linear) anymore
+    pub fn try_finalize(self) -> Result<D::Output, HashWriterError> {
+        if self.seek.moved {
+            return Err(HashWriterError::Invalidated);
+        }
+        Ok(self.hasher.finalize())
+    }
+
+    /// The digest of the data written so far, finalizing a clone of the hasher so hashing
+    /// carries on unaffected
+    pub fn digest_so_far(&self) -> D::Output
+    where
+        D: Clone,
+    {
+        self.hasher.clone().finalize()
+    }
+
+    /// Call `inspect` with every chunk that is hashed, i.e. the part of each write the inner
+    /// writer accepted (one call per slice for vectored writes).
+    ///
+    /// The callback only gets to look at the data, the digest is unaffected.
+    pub fn with_inspect<F: FnMut(&[u8]) + Send>(self, inspect: F) -> WriteHasher<Inspect<D, F>, T> {
+        WriteHasher {
+            hasher: Inspect::new(self.hasher, inspect),
+            inner: self.inner,
+            written: self.written,
+            hashed: self.hashed,
+            filter: self.filter,
+            seek: self.seek,
+        }
+    }
+
+    /// Call `progress` with the number of bytes the inner writer accepted and the total written
+    /// so far after every write, e.g. to drive a progress bar. Writes made while hashing is
+    /// [paused](Self::pause_hashing) are reported too, empty and failed writes aren't, and a
+    /// vectored write is reported once.
+    ///
+    /// The writer is only `Send` if `progress` is.
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "crc32c")] {
+    /// use std::io::Write;
+    /// use write_hasher::{crc32c::Crc32c, MinDigest, WriteHasher};
+    /// let mut reports = Vec::new();
+    /// let mut hasher = WriteHasher::<Crc32c, _>::new(std::io::sink())
+    ///     .with_progress(|len, total| reports.push((len, total)));
+    /// hasher.write_all(b"1234").unwrap();
+    /// hasher.write_all(b"").unwrap();
+    /// hasher.write_all(b"56789").unwrap();
+    /// assert_eq!(hasher.finalize(), 0xe3069283);
+    /// assert_eq!(reports, [(4, 4), (5, 9)]);
+    /// # }
+    /// ```
+    #[cfg(any(feature = "stdio", feature = "tokio", feature = "futures"))]
+    pub fn with_progress<F: FnMut(u64, u64)>(
+        self,
+        progress: F,
+    ) -> WriteHasher<D, ProgressWriter<T, F>> {
+        WriteHasher {
+            hasher: self.hasher,
+            inner: ProgressWriter {
+                inner: self.inner,
+                // the total counts what was written before as well
+                total: self.written,
+                progress,
+            },
+            written: self.written,
+            hashed: self.hashed,
+            filter: self.filter,
+            seek: self.seek,
+        }
+    }
+}
+
+impl<D: MinDigest + Clone, T> WriteHasher<D, T> {
+    /// Call `checkpoint` with the offset and the digest of the data up to it every time the
+    /// bytes hashed reach a multiple of `interval`. Writes straddling a boundary are hashed in
+    /// two parts, so each digest covers exactly `k * interval` bytes.
+    ///
+    /// The offsets count the hashed bytes, which are the bytes written unless hashing is
+    /// [paused](Self::pause_hashing) or limited to [ranges](Self::with_hash_ranges): the bytes
+    /// left out of the digest don't move them, while data fed to the hasher through
+    /// [`hasher_mut`](Self::hasher_mut) does.
+    ///
+    /// ```rust
+    /// # #[cfg(all(feature = "stdio", feature = "crc32c"))] {
+    /// use std::io::Write;
+    /// use write_hasher::{crc32c::Crc32c, hash_bytes, MinDigest, WriteHasher};
+    /// let mut checkpoints = Vec::new();
+    /// let mut hasher = WriteHasher::<Crc32c, _>::new(std::io::sink())
+    ///     .with_checkpoint(4, |offset, crc| checkpoints.push((offset, crc)));
+    /// hasher.write_all(b"123456789").unwrap();
+    /// hasher.finalize();
+    /// assert_eq!(
+    ///     checkpoints,
+    ///     [(4, hash_bytes::<Crc32c>(b"1234")), (8, hash_bytes::<Crc32c>(b"12345678"))]
+    /// );
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `interval` is 0
+    pub fn with_checkpoint<F: FnMut(u64, D::Output)>(
+        self,
+        interval: u64,
+        checkpoint: F,
+    ) -> WriteHasher<Checkpoint<D, F>, T> {
+        let mut hasher = Checkpoint::new(self.hasher, interval, checkpoint);
+        // offsets count what was written before as well
+        hasher.hashed = self.hashed;
+        WriteHasher {
+            hasher,
+            inner: self.inner,
+            written: self.written,
+            hashed: self.hashed,
+            filter: self.filter,
+            seek: self.seek,
+        }
+    }
+}
+
+/// The first `n` bytes of `bufs`, the part of a vectored write that was written
+#[cfg(any(feature = "stdio", feature = "tokio", feature = "futures"))]
+fn written_slices<'a>(
+    bufs: &'a [std::io::IoSlice<'_>],
+    mut n: usize,
+) -> impl Iterator<Item = &'a [u8]> {
+    bufs.iter().map_while(move |buf| {
+        let len = buf.len().min(n);
+        n -= len;
+        (len > 0 || n > 0).then(|| &buf[..len])
+    })
+}
+
+/// Hash the first `n` bytes of `bufs`, the part of a vectored write that was written
+#[cfg(any(feature = "stdio", feature = "tokio", feature = "futures"))]
+pub(crate) fn update_vectored<D: MinDigest>(
+    hasher: &mut D,
+    bufs: &[std::io::IoSlice<'_>],
+    n: usize,
+) {
+    for buf in written_slices(bufs, n).filter(|buf| !buf.is_empty()) {
+        hasher.update(buf);
+    }
+}
+
+#[cfg(feature = "digest")]
+impl<D: Digest + digest::Reset, T> WriteHasher<D, T> {
+    pub fn reset(&mut self) {
+        <D as Digest>::reset(&mut self.hasher);
+        self.restart();
+    }
+}
+
+impl<D: MinDigestReset, T> WriteHasher<D, T> {
+    /// Finalize the hash of the data written since the last reset and start over, e.g. to get
+    /// the digest of every file written into one archive stream
+    pub fn finalize_reset(&mut self) -> D::Output {
+        let output = self.hasher.finalize_reset();
+        self.restart();
+        output
+    }
+}
+
+impl<D, T> WriteHasher<D, T> {
+    /// The hashed data starts over where the inner writer is now
+    fn restart(&mut self) {
+        #[cfg(any(feature = "stdio", feature = "tokio"))]
+        {
+            self.seek.start = self.seek.start.map(|start| start + self.written);
+        }
+        self.seek.moved = false;
+        self.written = 0;
+        self.hashed = 0;
+    }
+}
+
+impl<MD: MinDigest, T> MinDigest for WriteHasher<MD, T> {
+    type Output = MD::Output;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        self.hasher.update(data)
+    }
+    fn finalize(self) -> MD::Output {
+        self.hasher.finalize()
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+impl<D: MinDigest, T: tokio::io::AsyncWrite> tokio::io::AsyncWrite for WriteHasher<D, T> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        let ah = self.project();
+        let r = ah.inner.poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = r {
+            hash_written(ah.hasher, ah.hashed, ah.filter, &buf[..n]);
+            *ah.written += n as u64;
+        }
+        r
+    }
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        bufs: &[std::io::IoSlice<'_>],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        let ah = self.project();
+        let r = ah.inner.poll_write_vectored(cx, bufs);
+        if let Poll::Ready(Ok(n)) = r {
+            for buf in written_slices(bufs, n) {
+                hash_written(ah.hasher, ah.hashed, ah.filter, buf);
+            }
+            *ah.written += n as u64;
+        }
+        r
+    }
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        let ah = self.project();
+        ah.inner.poll_flush(cx)
+    }
+    fn poll_shutdown(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        let ah = self.project();
+        ah.inner.poll_shutdown(cx)
+    }
+}
+
+/// Seeking is passed through, a seek moving the inner writer away from the end of the hashed
+/// data makes the digest [non linear](WriteHasher::is_digest_linear)
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+impl<D, T: tokio::io::AsyncSeek> tokio::io::AsyncSeek for WriteHasher<D, T> {
+    fn start_seek(self: Pin<&mut Self>, position: std::io::SeekFrom) -> std::io::Result<()> {
+        let ah = self.project();
+        ah.inner.start_seek(position)?;
+        ah.seek.pending = Some(position);
+        Ok(())
+    }
+    fn poll_complete(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<u64>> {
+        let ah = self.project();
+        let r = ah.inner.poll_complete(cx);
+        if let Poll::Ready(Ok(position)) = r {
+            // also polled without a seek in flight, which doesn't move anything
+            let pos = ah.seek.pending.take();
+            ah.seek.complete(pos, position, *ah.written);
+        }
+        r
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<D: MinDigest, T: futures::io::AsyncWrite> futures::io::AsyncWrite for WriteHasher<D, T> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<futures::io::Result<usize>> {
+        let ah = self.project();
+        let r = ah.inner.poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = r {
+            hash_written(ah.hasher, ah.hashed, ah.filter, &buf[..n]);
+            *ah.written += n as u64;
+        }
+        r
+    }
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        bufs: &[std::io::IoSlice<'_>],
+    ) -> std::task::Poll<futures::io::Result<usize>> {
+        let ah = self.project();
+        let r = ah.inner.poll_write_vectored(cx, bufs);
+        if let Poll::Ready(Ok(n)) = r {
+            for buf in written_slices(bufs, n) {
+                hash_written(ah.hasher, ah.hashed, ah.filter, buf);
+            }
+            *ah.written += n as u64;
+        }
+        r
+    }
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<futures::io::Result<()>> {
+        let ah = self.project();
+        ah.inner.poll_flush(cx)
+    }
+    fn poll_close(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<futures::io::Result<()>> {
+        let ah = self.project();
+        ah.inner.poll_close(cx)
+    }
+}
+
+#[cfg(feature = "stdio")]
+impl<D: MinDigest, T: std::io::Write> std::io::Write for WriteHasher<D, T> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let r = std::io::Write::write(&mut self.inner, buf);
+        if let Ok(n) = r {
+            hash_written(
+                &mut self.hasher,
+                &mut self.hashed,
+                &mut self.filter,
+                &buf[..n],
+            );
+            self.written += n as u64;
+        }
+        r
+    }
+    fn write_vectored(&mut self, bufs: &[std::io::IoSlice<'_>]) -> std::io::Result<usize> {
+        let r = std::io::Write::write_vectored(&mut self.inner, bufs);
+        if let Ok(n) = r {
+            for buf in written_slices(bufs, n) {
+                hash_written(&mut self.hasher, &mut self.hashed, &mut self.filter, buf);
+            }
+            self.written += n as u64;
+        }
+        r
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Seeking is passed through, a seek moving the inner writer away from the end of the hashed
+/// data makes the digest [non linear](WriteHasher::is_digest_linear)
+#[cfg(feature = "stdio")]
+impl<D, T: std::io::Seek> std::io::Seek for WriteHasher<D, T> {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        let position = self.inner.seek(pos)?;
+        self.seek.complete(Some(pos), position, self.written);
+        Ok(position)
+    }
+    fn stream_position(&mut self) -> std::io::Result<u64> {
+        let position = self.inner.stream_position()?;
+        self.seek.complete(None, position, self.written);
+        Ok(position)
+    }
+}
+
+/// Reads are passed through to a duplex inner stream without being hashed
+#[cfg(feature = "stdio")]
+impl<D, T: std::io::Read> std::io::Read for WriteHasher<D, T> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.inner.read(buf)
+    }
+    fn read_vectored(&mut self, bufs: &mut [std::io::IoSliceMut<'_>]) -> std::io::Result<usize> {
+        self.inner.read_vectored(bufs)
+    }
+}
+
+/// Reads are passed through to a duplex inner stream without being hashed
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+impl<D, T: tokio::io::AsyncRead> tokio::io::AsyncRead for WriteHasher<D, T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        self.project().inner.poll_read(cx, buf)
+    }
+}
+
+/// Reads are passed through to a duplex inner stream without being hashed
+#[cfg(feature = "futures")]
+impl<D, T: futures::io::AsyncRead> futures::io::AsyncRead for WriteHasher<D, T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut [u8],
+    ) -> std::task::Poll<futures::io::Result<usize>> {
+        self.project().inner.poll_read(cx, buf)
+    }
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        bufs: &mut [std::io::IoSliceMut<'_>],
+    ) -> std::task::Poll<futures::io::Result<usize>> {
+        self.project().inner.poll_read_vectored(cx, bufs)
+    }
+}
+
+/// `std::io::Write` has no impl for `Pin<P>` (unlike the async traits), these let a pinned
+/// hasher be used the same way with either of them
+#[cfg(feature = "stdio")]
+impl<D: MinDigest, T: std::io::Write> std::io::Write for Pin<Box<WriteHasher<D, T>>>
+where
+    WriteHasher<D, T>: Unpin,
+{
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.as_mut().get_mut().write(buf)
+    }
+    fn write_vectored(&mut self, bufs: &[std::io::IoSlice<'_>]) -> std::io::Result<usize> {
+        self.as_mut().get_mut().write_vectored(bufs)
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.as_mut().get_mut().flush()
+    }
+}
+
+#[cfg(feature = "stdio")]
+impl<D: MinDigest, T: std::io::Write> std::io::Write for Pin<&mut WriteHasher<D, T>>
+where
+    WriteHasher<D, T>: Unpin,
+{
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.as_mut().get_mut().write(buf)
+    }
+    fn write_vectored(&mut self, bufs: &[std::io::IoSlice<'_>]) -> std::io::Result<usize> {
+        self.as_mut().get_mut().write_vectored(bufs)
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.as_mut().get_mut().flush()
+    }
+}
+
+/// A writer that reports the length of every write the inner writer accepted and the total so
+/// far, see [`WriteHasher::with_progress`]
+#[cfg_attr(any(feature = "futures", feature = "tokio"), pin_project::pin_project)]
+#[cfg(any(feature = "stdio", feature = "tokio", feature = "futures"))]
+pub struct ProgressWriter<T, F> {
+    #[cfg_attr(any(feature = "futures", feature = "tokio"), pin)]
+    inner: T,
+    total: u64,
+    progress: F,
+}
+
+#[cfg(any(feature = "stdio", feature = "tokio", feature = "futures"))]
+impl<T, F> ProgressWriter<T, F> {
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Writing to the inner writer directly isn't reported.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+#[cfg(any(feature = "stdio", feature = "tokio", feature = "futures"))]
+fn report<F: FnMut(u64, u64)>(total: &mut u64, progress: &mut F, n: usize) {
+    if n > 0 {
+        *total += n as u64;
+        progress(n as u64, *total);
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+impl<T: tokio::io::AsyncWrite, F: FnMut(u64, u64)> tokio::io::AsyncWrite for ProgressWriter<T, F> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        let pw = self.project();
+        let r = pw.inner.poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = r {
+            report(pw.total, pw.progress, n);
+        }
+        r
+    }
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        bufs: &[std::io::IoSlice<'_>],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        let pw = self.project();
+        let r = pw.inner.poll_write_vectored(cx, bufs);
+        if let Poll::Ready(Ok(n)) = r {
+            report(pw.total, pw.progress, n);
+        }
+        r
+    }
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+    fn poll_shutdown(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        self.project().inner.poll_shutdown(cx)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[cfg(feature = "tokio")]
+impl<T: tokio::io::AsyncSeek, F> tokio::io::AsyncSeek for ProgressWriter<T, F> {
+    fn start_seek(self: Pin<&mut Self>, position: std::io::SeekFrom) -> std::io::Result<()> {
+        self.project().inner.start_seek(position)
+    }
+    fn poll_complete(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<u64>> {
+        self.project().inner.poll_complete(cx)
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<T: futures::io::AsyncWrite, F: FnMut(u64, u64)> futures::io::AsyncWrite
+    for ProgressWriter<T, F>
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<futures::io::Result<usize>> {
+        let pw = self.project();
+        let r = pw.inner.poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = r {
+            report(pw.total, pw.progress, n);
+        }
+        r
+    }
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        bufs: &[std::io::IoSlice<'_>],
+    ) -> std::task::Poll<futures::io::Result<usize>> {
+        let pw = self.project();
+        let r = pw.inner.poll_write_vectored(cx, bufs);
+        if let Poll::Ready(Ok(n)) = r {
+            report(pw.total, pw.progress, n);
+        }
+        r
+    }
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<futures::io::Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+    fn poll_close(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<futures::io::Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
+
+#[cfg(feature = "stdio")]
+impl<T: std::io::Write, F: FnMut(u64, u64)> std::io::Write for ProgressWriter<T, F> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        report(&mut self.total, &mut self.progress, n);
+        Ok(n)
+    }
+    fn write_vectored(&mut self, bufs: &[std::io::IoSlice<'_>]) -> std::io::Result<usize> {
+        let n = self.inner.write_vectored(bufs)?;
+        report(&mut self.total, &mut self.progress, n);
+        Ok(n)
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(feature = "stdio")]
+impl<T: std::io::Seek, F> std::io::Seek for ProgressWriter<T, F> {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        self.inner.seek(pos)
+    }
+    fn stream_position(&mut self) -> std::io::Result<u64> {
+        self.inner.stream_position()
+    }
+}
+
+/// A blocking [`std::io::Write`] that hashes the data on the blocking side and forwards it over a
+/// channel to a task writing it into an async [`tokio::io::AsyncWrite`].
+///
+/// This lets synchronous code (run on [`tokio::task::spawn_blocking`]) write into an async
+/// destination while the digest stays available synchronously. Call
+/// [`finish`](BlockingWriteHasher::finish) once done to wait for all the data to reach the async
+/// writer, then [`finalize`](MinDigest::finalize) the digest.
+///
+/// Data is hashed as soon as it is handed to the channel, so if `finish` (or a write / flush)
+/// returns an error the digest may cover bytes that never reached the async writer.
+///
+/// The task can be stopped with a [`CancelHandle`]: it drops any queued data, and every write,
+/// flush and `finish` after that fails with [`HashWriterError::Cancelled`] instead of waiting on
+/// the async writer.
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio-blocking")))]
+#[cfg(feature = "tokio-blocking")]
+pub struct BlockingWriteHasher<D, W> {
+    hasher: D,
+    sender: Option<tokio::sync::mpsc::Sender<BlockingMessage>>,
+    task: Option<tokio::task::JoinHandle<std::io::Result<W>>>,
+    handle: tokio::runtime::Handle,
+    cancel: CancelHandle,
+}
+
+/// Cancels the task of a [`BlockingWriteHasher`], clones cancel the same task
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio-blocking")))]
+#[cfg(feature = "tokio-blocking")]
+#[derive(Debug, Clone, Default)]
+pub struct CancelHandle {
+    inner: std::sync::Arc<CancelState>,
+}
+
+#[cfg(feature = "tokio-blocking")]
+#[derive(Debug, Default)]
+struct CancelState {
+    cancelled: std::sync::atomic::AtomicBool,
+    notify: tokio::sync::Notify,
+}
+
+#[cfg(feature = "tokio-blocking")]
+impl CancelHandle {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn cancel(&self) {
+        self.inner
+            .cancelled
+            .store(true, std::sync::atomic::Ordering::Release);
+        self.inner.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner
+            .cancelled
+            .load(std::sync::atomic::Ordering::Acquire)
+    }
+
+    /// Wait until [`cancel`](Self::cancel) is called
+    pub async fn cancelled(&self) {
+        loop {
+            // registered before checking so a cancel in between isn't missed
+            let notified = self.inner.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Run `future` to completion unless cancelled first
+    async fn or_cancelled<F: core::future::Future>(
+        &self,
+        future: F,
+    ) -> Result<F::Output, HashWriterError> {
+        use core::future::Future;
+        let mut cancelled = core::pin::pin!(self.cancelled());
+        let mut future = core::pin::pin!(future);
+        core::future::poll_fn(|cx| {
+            if cancelled.as_mut().poll(cx).is_ready() {
+                return Poll::Ready(Err(HashWriterError::Cancelled));
+            }
+            future.as_mut().poll(cx).map(Ok)
+        })
+        .await
+    }
+}
+
+#[cfg(feature = "tokio-blocking")]
+enum BlockingMessage {
+    Data(Vec<u8>),
+    Flush(tokio::sync::oneshot::Sender<std::io::Result<()>>),
+}
+
+#[cfg(feature = "tokio-blocking")]
+impl<D, W> BlockingWriteHasher<D, W>
+where
+    W: tokio::io::AsyncWrite + std::marker::Unpin + Send + 'static,
+{
+    /// Spawn the task writing into `inner` on the current tokio runtime.
+    ///
+    /// # Panics
+    /// Panics if called outside of a tokio runtime.
+    pub fn new_with_hasher(inner: W, hasher: D) -> Self {
+        Self::new_with_cancel(inner, hasher, CancelHandle::new())
+    }
+
+    /// Like [`new_with_hasher`](Self::new_with_hasher), with the task stopping once `cancel` is
+    /// cancelled.
+    ///
+    /// # Panics
+    /// Panics if called outside of a tokio runtime.
+    pub fn new_with_cancel(inner: W, hasher: D, cancel: CancelHandle) -> Self {
+        let handle = tokio::runtime::Handle::current();
+        let (sender, receiver) = tokio::sync::mpsc::channel(16);
+        let task = handle.spawn(forward_blocking(inner, receiver, cancel.clone()));
+        Self {
+            hasher,
+            sender: Some(sender),
+            task: Some(task),
+            handle,
+            cancel,
+        }
+    }
+
+    /// A handle cancelling this writer's task
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.cancel.clone()
+    }
+
+    pub fn new(inner: W) -> Self
+    where
+        D: Default,
+    {
+        Self::new_with_hasher(inner, Default::default())
+    }
+
+    fn send(&self, message: BlockingMessage) -> std::io::Result<()> {
+        if self.cancel.is_cancelled() {
+            return Err(HashWriterError::Cancelled.into());
+        }
+        self.sender
+            .as_ref()
+            .ok_or_else(|| std::io::Error::other("BlockingWriteHasher is already finished"))?
+            .blocking_send(message)
+            .map_err(|_| {
+                if self.cancel.is_cancelled() {
+                    return HashWriterError::Cancelled.into();
+                }
+                std::io::Error::new(
+                    std::io::ErrorKind::BrokenPipe,
+                    "the async writer task has stopped, call finish to get its error",
+                )
+            })
+    }
+
+    /// Wait for all the data written so far to be written to the async writer, shut it down and
+    /// return it.
+    ///
+    /// # Panics
+    /// Panics if called from an async context, call it from the blocking side.
+    pub fn finish(&mut self) -> std::io::Result<W> {
+        drop(self.sender.take());
+        let task = self
+            .task
+            .take()
+            .ok_or_else(|| std::io::Error::other("BlockingWriteHasher is already finished"))?;
+        self.handle.block_on(task).map_err(std::io::Error::other)?
+    }
+
+    /// [`finalize`](MinDigest::finalize) the digest, unless the writer was cancelled and the
+    /// digest may be over data that was dropped
+    pub fn try_finalize(self) -> Result<D::Output, HashWriterError>
+    where
+        D: MinDigest,
+    {
+        if self.cancel.is_cancelled() {
+            return Err(HashWriterError::Cancelled);
+        }
+        Ok(self.hasher.finalize())
+    }
+}
+
+#[cfg(feature = "tokio-blocking")]
+async fn forward_blocking<W: tokio::io::AsyncWrite + std::marker::Unpin>(
+    inner: W,
+    mut receiver: tokio::sync::mpsc::Receiver<BlockingMessage>,
+    cancel: CancelHandle,
+) -> std::io::Result<W> {
+    let r = cancel
+        .or_cancelled(forward_messages(inner, &mut receiver))
+        .await;
+    if r.is_err() {
+        // fail the pending and future sends on the blocking side and free the queued buffers
+        receiver.close();
+        while receiver.try_recv().is_ok() {}
+    }
+    r?
+}
+
+#[cfg(feature = "tokio-blocking")]
+async fn forward_messages<W: tokio::io::AsyncWrite + std::marker::Unpin>(
+    mut inner: W,
+    receiver: &mut tokio::sync::mpsc::Receiver<BlockingMessage>,
+) -> std::io::Result<W> {
+    while let Some(message) = receiver.recv().await {
+        match message {
+            BlockingMessage::Data(data) => {
+                let mut data = &data[..];
+                while !data.is_empty() {
+                    let n = core::future::poll_fn(|cx| Pin::new(&mut inner).poll_write(cx, data))
+                        .await?;
+                    if n == 0 {
+                        return Err(std::io::ErrorKind::WriteZero.into());
+                    }
+                    data = &data[n..];
+                }
+            }
+            BlockingMessage::Flush(ack) => {
+                let r = core::future::poll_fn(|cx| Pin::new(&mut inner).poll_flush(cx)).await;
+                let _ = ack.send(r);
+            }
+        }
+    }
+    core::future::poll_fn(|cx| Pin::new(&mut inner).poll_shutdown(cx)).await?;
+    Ok(inner)
+}
+
+#[cfg(feature = "tokio-blocking")]
+impl<D: MinDigest, W> std::io::Write for BlockingWriteHasher<D, W>
+where
+    W: tokio::io::AsyncWrite + std::marker::Unpin + Send + 'static,
+{
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.send(BlockingMessage::Data(buf.to_vec()))?;
+        self.hasher.update(buf);
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        let (ack, done) = tokio::sync::oneshot::channel();
+        self.send(BlockingMessage::Flush(ack))?;
+        done.blocking_recv()
+            .map_err(|_| std::io::Error::from(std::io::ErrorKind::BrokenPipe))?
+    }
+}
+
+#[cfg(feature = "tokio-blocking")]
+impl<D: MinDigest, W> MinDigest for BlockingWriteHasher<D, W> {
+    type Output = D::Output;
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        self.hasher.update(data)
+    }
+    fn finalize(self) -> D::Output {
+        self.hasher.finalize()
+    }
+}
+
+/// A tokio [`WriteHasher`] that hashes writes larger than a threshold on
+/// [`spawn_blocking`](tokio::task::spawn_blocking), so expensive digests don't hold up the async
+/// worker threads. Created with [`WriteHasher::with_blocking_threshold`].
+///
+/// Smaller writes are hashed inline. At most one update is in flight: every write, flush and
+/// shutdown waits for it first, so the updates happen in the order of the writes.
+///
+/// The blocking pool needs the data it hashes owned, so the hashed part of a large write is
+/// copied into a buffer that goes back and forth with the hasher. The buffer is kept for the
+/// next large write, so it ends up as large as the largest of them.
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio-offload")))]
+#[cfg(feature = "tokio-offload")]
+#[pin_project::pin_project]
+pub struct OffloadWriteHasher<D, T> {
+    offload: Offload<D>,
+    #[pin]
+    inner: T,
+    written: u64,
+    seek: SeekTracker,
+}
+
+/// The hashing half of an [`OffloadWriteHasher`], the hasher is either here or in flight
+#[cfg(feature = "tokio-offload")]
+struct Offload<D> {
+    hasher: Option<D>,
+    buf: Vec<u8>,
+    in_flight: Option<tokio::task::JoinHandle<(D, Vec<u8>)>>,
+    threshold: usize,
+    hashed: u64,
+    filter: HashFilter,
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio-offload")))]
+#[cfg(feature = "tokio-offload")]
+impl<D, T> WriteHasher<D, T> {
+    /// Hash writes of more than `threshold` bytes on tokio's blocking thread pool
+    ///
+    /// The counts, whether hashing is [paused](Self::pause_hashing), the
+    /// [hash ranges](Self::with_hash_ranges) and the state of seeking carry over.
+    pub fn with_blocking_threshold(self, threshold: usize) -> OffloadWriteHasher<D, T> {
+        OffloadWriteHasher {
+            offload: Offload {
+                hasher: Some(self.hasher),
+                buf: Vec::new(),
+                in_flight: None,
+                threshold,
+                hashed: self.hashed,
+                filter: self.filter,
+            },
+            inner: self.inner,
+            written: self.written,
+            seek: self.seek,
+        }
+    }
+}
+
+#[cfg(feature = "tokio-offload")]
+impl<D, T> OffloadWriteHasher<D, T> {
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Writing to the inner writer directly bypasses the hasher.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// The number of bytes the inner writer accepted, including those written while hashing was
+    /// paused
+    pub fn bytes_written(&self) -> u64 {
+        self.written
+    }
+
+    /// The number of written bytes that were hashed
+    pub fn bytes_hashed(&self) -> u64 {
+        self.offload.hashed
+    }
+
+    /// See [`WriteHasher::pause_hashing`]
+    pub fn pause_hashing(&mut self) {
+        self.offload.filter.paused = true;
+    }
+
+    pub fn resume_hashing(&mut self) {
+        self.offload.filter.paused = false;
+    }
+
+    pub fn is_hashing_paused(&self) -> bool {
+        self.offload.filter.paused
+    }
+
+    /// See [`WriteHasher::is_digest_linear`]
+    pub fn is_digest_linear(&self) -> bool {
+        !self.seek.moved
+    }
+}
+
+#[cfg(feature = "tokio-offload")]
+impl<D: MinDigest, T> OffloadWriteHasher<D, T> {
+    /// Wait for the update in flight and finalize the hash
+    ///
+    /// Fails if the update was cancelled, e.g. by the runtime shutting down.
+    ///
+    /// # Panics
+    /// Re-raises a panic of the hasher on the blocking thread
+    pub async fn finalize(self) -> std::io::Result<D::Output> {
+        Ok(self.finalize_into_parts().await?.0)
+    }
+
+    /// [`finalize`](Self::finalize), handing back the inner writer as well
+    pub async fn finalize_into_parts(mut self) -> std::io::Result<(D::Output, T)> {
+        core::future::poll_fn(|cx| self.offload.poll_in_flight(cx)).await?;
+        let hasher = self.offload.hasher.take().ok_or_else(hasher_lost)?;
+        Ok((hasher.finalize(), self.inner))
+    }
+
+    /// [`finalize`](Self::finalize), failing with [`HashWriterError::Invalidated`] if the digest
+    /// isn't [linear](Self::is_digest_linear)
+    pub async fn try_finalize(self) -> Result<D::Output, HashWriterError> {
+        if self.seek.moved {
+            return Err(HashWriterError::Invalidated);
+        }
+        Ok(self.finalize().await?)
+    }
+}
+
+#[cfg(feature = "tokio-offload")]
+fn hasher_lost() -> std::io::Error {
+    std::io::Error::other("the hashing task was cancelled")
+}
+
+#[cfg(feature = "tokio-offload")]
+impl<D> Offload<D> {
+    /// Wait for the update in flight, putting the hasher and the buffer back
+    fn poll_in_flight(&mut self, cx: &mut std::task::Context<'_>) -> Poll<std::io::Result<()>> {
+        use core::future::Future;
+        if let Some(task) = &mut self.in_flight {
+            let r = core::task::ready!(Pin::new(task).poll(cx));
+            self.in_flight = None;
+            match r {
+                Ok((hasher, buf)) => {
+                    self.hasher = Some(hasher);
+                    self.buf = buf;
+                }
+                Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+                Err(_) => return Poll::Ready(Err(hasher_lost())),
+            }
+        }
+        if self.hasher.is_none() {
+            return Poll::Ready(Err(hasher_lost()));
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(feature = "tokio-offload")]
+impl<D: MinDigest + Send + 'static> Offload<D> {
+    /// Hash the parts of `written`, the `n` bytes of a write, that the filter selects. On the
+    /// blocking pool if the write is larger than the threshold, inline otherwise.
+    fn hash<'a>(
+        &mut self,
+        written: impl Iterator<Item = &'a [u8]>,
+        n: usize,
+    ) -> std::io::Result<()> {
+        if n <= self.threshold {
+            let hasher = self.hasher.as_mut().ok_or_else(hasher_lost)?;
+            for buf in written {
+                hash_written(hasher, &mut self.hashed, &mut self.filter, buf);
+            }
+            return Ok(());
+        }
+        let mut data = core::mem::take(&mut self.buf);
+        data.clear();
+        for buf in written {
+            self.filter
+                .select(buf, |chunk| data.extend_from_slice(chunk));
+        }
+        self.hashed += data.len() as u64;
+        if data.is_empty() {
+            self.buf = data;
+            return Ok(());
+        }
+        let mut hasher = self.hasher.take().ok_or_else(hasher_lost)?;
+        self.in_flight = Some(tokio::task::spawn_blocking(move || {
+            hasher.update(&data);
+            (hasher, data)
+        }));
+        Ok(())
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio-offload")))]
+#[cfg(feature = "tokio-offload")]
+impl<D, T> tokio::io::AsyncWrite for OffloadWriteHasher<D, T>
+where
+    D: MinDigest + Send + 'static,
+    T: tokio::io::AsyncWrite,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        let ah = self.project();
+        core::task::ready!(ah.offload.poll_in_flight(cx))?;
+        let r = ah.inner.poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = r {
+            *ah.written += n as u64;
+            ah.offload.hash(core::iter::once(&buf[..n]), n)?;
+        }
+        r
+    }
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        bufs: &[std::io::IoSlice<'_>],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        let ah = self.project();
+        core::task::ready!(ah.offload.poll_in_flight(cx))?;
+        let r = ah.inner.poll_write_vectored(cx, bufs);
+        if let Poll::Ready(Ok(n)) = r {
+            *ah.written += n as u64;
+            ah.offload.hash(written_slices(bufs, n), n)?;
+        }
+        r
+    }
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        let ah = self.project();
+        core::task::ready!(ah.offload.poll_in_flight(cx))?;
+        ah.inner.poll_flush(cx)
+    }
+    fn poll_shutdown(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        let ah = self.project();
+        core::task::ready!(ah.offload.poll_in_flight(cx))?;
+        ah.inner.poll_shutdown(cx)
+    }
+}
+
+/// Seeking is passed through like with [`WriteHasher`], a seek moving the inner writer away from
+/// the end of the hashed data makes the digest [non linear](OffloadWriteHasher::is_digest_linear)
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio-offload")))]
+#[cfg(feature = "tokio-offload")]
+impl<D, T: tokio::io::AsyncSeek> tokio::io::AsyncSeek for OffloadWriteHasher<D, T> {
+    fn start_seek(self: Pin<&mut Self>, position: std::io::SeekFrom) -> std::io::Result<()> {
+        let ah = self.project();
+        ah.inner.start_seek(position)?;
+        ah.seek.pending = Some(position);
+        Ok(())
+    }
+    fn poll_complete(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<u64>> {
+        let ah = self.project();
+        let r = ah.inner.poll_complete(cx);
+        if let Poll::Ready(Ok(position)) = r {
+            let pos = ah.seek.pending.take();
+            ah.seek.complete(pos, position, *ah.written);
+        }
+        r
+    }
+}